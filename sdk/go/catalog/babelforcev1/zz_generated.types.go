@@ -75,6 +75,8 @@ type AudioInfoEvent struct {
 	Read AudioInfoItem `json:"read"`
 	// Write: Audio written to the telephony stream.
 	Write AudioInfoItem `json:"write"`
+	// Transport: Optional transport statistics sampled at the same time.
+	Transport *AudioTransportInfo `json:"transport,omitempty"`
 }
 
 // AudioInfoItem is generated from the protocol catalog.
@@ -97,6 +99,32 @@ type AudioSpeechStartedEvent struct {
 	Origin string `json:"origin"`
 }
 
+// AudioTransportInfo is generated from the protocol catalog.
+//
+// Transport health sampled with one `audio.info` report; unmeasured values are absent.
+type AudioTransportInfo struct {
+	// CandidatePair: Selected ICE candidate pair.
+	CandidatePair string `json:"candidate_pair,omitempty"`
+	// RTT: Latest transport round-trip time in milliseconds.
+	RTT *int64 `json:"rtt,omitempty"`
+	// Jitter: Interarrival jitter of received media in milliseconds.
+	Jitter *int64 `json:"jitter,omitempty"`
+	// PacketsReceived: Media packets received.
+	PacketsReceived *int64 `json:"packets_received,omitempty"`
+	// PacketsLost: Received media packets missing from the sequence.
+	PacketsLost *int64 `json:"packets_lost,omitempty"`
+	// BytesSent: Media bytes sent.
+	BytesSent *int64 `json:"bytes_sent,omitempty"`
+	// BytesReceived: Media bytes received.
+	BytesReceived *int64 `json:"bytes_received,omitempty"`
+	// NacksSent: Negative acknowledgements sent for received media.
+	NacksSent *int64 `json:"nacks_sent,omitempty"`
+	// NacksReceived: Negative acknowledgements received for sent media.
+	NacksReceived *int64 `json:"nacks_received,omitempty"`
+	// QueuedBytes: Bytes admitted for sending but not yet written.
+	QueuedBytes *int64 `json:"queued_bytes,omitempty"`
+}
+
 // CallHangupEvent is generated from the protocol catalog.
 //
 // Data emitted by `call.hangup` when the voice peer reports call termination.
@@ -0,0 +1,556 @@
+//! In-band DTMF tones on L16 audio, for media gateways that do not signal digits.
+
+use std::collections::VecDeque;
+use std::f64::consts::TAU;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::monitor::{Detect, Monitor};
+use super::{AudioStream, TappedFrame};
+use crate::{DtmfDigit, MediaFormat};
+
+const ROWS: [f64; 4] = [697.0, 770.0, 852.0, 941.0];
+const COLUMNS: [f64; 4] = [1_209.0, 1_336.0, 1_477.0, 1_633.0];
+/// Digits by row, then column.
+const KEYPAD: [&[u8; 4]; 4] = [b"123A", b"456B", b"789C", b"*0#D"];
+
+/// Analysis block length; 205 samples at 8 kHz, the classic Goertzel choice.
+const BLOCK: Duration = Duration::from_micros(25_600);
+/// Mean block power, relative to full scale, below which a block is silence (-40 dBFS).
+const MIN_POWER: f64 = 1e-4;
+/// Power ratio by which the chosen tone must exceed the others in its group.
+const DOMINANCE: f64 = 4.0;
+/// Largest power ratio between the two tones (8 dB).
+const MAX_TWIST: f64 = 6.3;
+/// Share of the block's energy the two tones must carry.
+const MIN_TONE_SHARE: f64 = 0.6;
+/// Consecutive agreeing blocks that register a press, and missing blocks that release it.
+const CONFIRM_BLOCKS: u8 = 2;
+
+/// Return the row and column frequencies of one digit.
+fn tones(digit: u8) -> Option<(f64, f64)> {
+    KEYPAD.iter().enumerate().find_map(|(row, keys)| {
+        keys.iter()
+            .position(|key| key.eq_ignore_ascii_case(&digit))
+            .map(|column| (ROWS[row], COLUMNS[column]))
+    })
+}
+
+/// Goertzel detector turning inbound frames into released digits.
+pub struct DtmfDetector {
+    sample_rate: u32,
+    channels: u16,
+    /// Mono samples of the block being filled, and the wall-clock time of its first sample.
+    block: Vec<f64>,
+    block_len: usize,
+    block_start: SystemTime,
+    /// End of the audio analysed so far, which keeps bursts of frames from overlapping.
+    cursor: SystemTime,
+    candidate: Option<Candidate>,
+    pressed: Option<Press>,
+}
+
+/// A digit seen in consecutive blocks but not yet confirmed.
+struct Candidate {
+    digit: u8,
+    since: SystemTime,
+    blocks: u8,
+}
+
+struct Press {
+    digit: u8,
+    pressed_at: SystemTime,
+    /// End of the last block that carried the digit.
+    until: SystemTime,
+    misses: u8,
+}
+
+impl DtmfDetector {
+    /// Construct a detector for frames in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an invalid-format error for anything but L16.
+    pub fn new(format: &MediaFormat) -> Result<Self, crate::Error> {
+        format.frame_bytes()?;
+        require_l16(format, "DTMF detection")?;
+        let block_len = samples(BLOCK, format.sample_rate).max(1);
+        Ok(Self {
+            sample_rate: format.sample_rate,
+            channels: format.channels,
+            block: Vec::with_capacity(block_len),
+            block_len,
+            block_start: UNIX_EPOCH,
+            cursor: UNIX_EPOCH,
+            candidate: None,
+            pressed: None,
+        })
+    }
+
+    /// Analyse the next frame, captured at `captured_at`, and return the digits it releases.
+    ///
+    /// Channels are averaged. Digits carry wall-clock press and release times derived from
+    /// the capture time, taken as the end of the frame unless that would overlap audio
+    /// already analysed.
+    pub fn process(&mut self, frame: &[u8], captured_at: SystemTime) -> Vec<DtmfDigit> {
+        let width = 2 * usize::from(self.channels);
+        let length = self.duration(frame.len() / width);
+        let frame_start = captured_at
+            .checked_sub(length)
+            .unwrap_or(captured_at)
+            .max(self.cursor);
+        self.cursor = frame_start + length;
+        let mut released = Vec::new();
+        for (index, sample) in frame.chunks_exact(width).enumerate() {
+            if self.block.is_empty() {
+                self.block_start = frame_start + self.duration(index);
+            }
+            let sum: f64 = sample
+                .chunks_exact(2)
+                .map(|bytes| f64::from(i16::from_le_bytes([bytes[0], bytes[1]])))
+                .sum();
+            self.block.push(sum / f64::from(self.channels) / 32_768.0);
+            if self.block.len() == self.block_len {
+                released.extend(self.analyse());
+                self.block.clear();
+            }
+        }
+        released
+    }
+
+    /// Release a digit still held, as when the audio ends mid-tone.
+    pub fn flush(&mut self) -> Option<DtmfDigit> {
+        self.candidate = None;
+        self.pressed.take().map(|press| Self::release(&press))
+    }
+
+    fn analyse(&mut self) -> Option<DtmfDigit> {
+        let start = self.block_start;
+        let end = start + self.duration(self.block_len);
+        let digit = self.classify();
+        if let Some(press) = self.pressed.as_mut() {
+            if digit == Some(press.digit) {
+                press.until = end;
+                press.misses = 0;
+                return None;
+            }
+            press.misses += 1;
+            if press.misses < CONFIRM_BLOCKS && digit.is_none() {
+                return None;
+            }
+            let released = self.pressed.take().map(|press| Self::release(&press));
+            self.track(digit, start, end);
+            return released;
+        }
+        self.track(digit, start, end);
+        None
+    }
+
+    /// Count `digit` towards a press, registering it once enough blocks agree.
+    fn track(&mut self, digit: Option<u8>, start: SystemTime, end: SystemTime) {
+        let Some(digit) = digit else {
+            self.candidate = None;
+            return;
+        };
+        let candidate = match self.candidate.take() {
+            Some(candidate) if candidate.digit == digit => candidate,
+            _ => Candidate {
+                digit,
+                since: start,
+                blocks: 0,
+            },
+        };
+        let blocks = candidate.blocks + 1;
+        if blocks < CONFIRM_BLOCKS {
+            self.candidate = Some(Candidate {
+                blocks,
+                ..candidate
+            });
+            return;
+        }
+        self.pressed = Some(Press {
+            digit,
+            pressed_at: candidate.since,
+            until: end,
+            misses: 0,
+        });
+    }
+
+    fn release(press: &Press) -> DtmfDigit {
+        DtmfDigit {
+            digit: char::from(press.digit).to_string(),
+            pressed_at: press.pressed_at,
+            released_at: press.until,
+        }
+    }
+
+    /// Return the digit the current block carries, if it passes every tone check.
+    #[allow(clippy::cast_precision_loss)]
+    fn classify(&self) -> Option<u8> {
+        let length = self.block.len() as f64;
+        let energy: f64 = self.block.iter().map(|sample| sample * sample).sum();
+        if energy / length < MIN_POWER {
+            return None;
+        }
+        let (row, row_power) = self.strongest(&ROWS)?;
+        let (column, column_power) = self.strongest(&COLUMNS)?;
+        let twist = row_power.max(column_power) / row_power.min(column_power);
+        if twist > MAX_TWIST {
+            return None;
+        }
+        // A pure tone of N samples has Goertzel power N/2 times its energy.
+        if 2.0 * (row_power + column_power) / (length * energy) < MIN_TONE_SHARE {
+            return None;
+        }
+        Some(KEYPAD[row][column])
+    }
+
+    /// Return the strongest of `frequencies` and its power, if it dominates the others.
+    fn strongest(&self, frequencies: &[f64; 4]) -> Option<(usize, f64)> {
+        let powers = frequencies.map(|frequency| self.goertzel(frequency));
+        let (index, peak) = powers
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|(_, left), (_, right)| left.total_cmp(right))?;
+        let dominant = powers
+            .iter()
+            .enumerate()
+            .all(|(other, power)| other == index || power * DOMINANCE < peak);
+        (dominant && peak > 0.0).then_some((index, peak))
+    }
+
+    fn goertzel(&self, frequency: f64) -> f64 {
+        let coefficient = 2.0 * (TAU * frequency / f64::from(self.sample_rate)).cos();
+        let (previous, before) = self
+            .block
+            .iter()
+            .fold((0.0, 0.0), |(previous, before), sample| {
+                (sample + coefficient * previous - before, previous)
+            });
+        previous * previous + before * before - coefficient * previous * before
+    }
+
+    fn duration(&self, samples: usize) -> Duration {
+        let nanos = u64::try_from(samples)
+            .unwrap_or(u64::MAX)
+            .saturating_mul(1_000_000_000)
+            / u64::from(self.sample_rate);
+        Duration::from_nanos(nanos)
+    }
+}
+
+impl Detect for DtmfDetector {
+    type Event = DtmfDigit;
+
+    fn detect(&mut self, frame: &TappedFrame) -> Vec<DtmfDigit> {
+        self.process(&frame.data, frame.captured_at)
+    }
+
+    fn finish(&mut self) -> Option<DtmfDigit> {
+        self.flush()
+    }
+}
+
+/// Runs a detector over a session's inbound audio until the stream closes, or until the monitor
+/// is stopped or dropped.
+pub struct DtmfMonitor {
+    monitor: Monitor<DtmfDigit>,
+}
+
+impl DtmfMonitor {
+    /// Start detecting digits in `audio`'s inbound frames.
+    ///
+    /// A digit still held when the stream closes is released then.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::AudioUnavailable`] before a format is negotiated, or the
+    /// [`DtmfDetector::new`] failures.
+    pub fn start(audio: &AudioStream) -> Result<Self, crate::Error> {
+        let format = audio.format().ok_or(crate::Error::AudioUnavailable)?;
+        Ok(Self {
+            monitor: Monitor::start(audio, DtmfDetector::new(&format)?),
+        })
+    }
+
+    /// Receive the next released digit, or `None` once the audio stream closes.
+    pub async fn recv(&mut self) -> Option<DtmfDigit> {
+        self.monitor.recv().await
+    }
+
+    /// Stop detecting. Dropping the monitor does the same.
+    pub fn stop(self) {}
+}
+
+/// Tone timing and level for generated digits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToneConfig {
+    /// How long each digit sounds.
+    pub duration: Duration,
+    /// Silence after each digit, so repeated digits register separately.
+    pub gap: Duration,
+    /// Peak level of each of the two tones, in dBFS.
+    pub level: f64,
+}
+
+impl Default for ToneConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_millis(100),
+            gap: Duration::from_millis(100),
+            level: -10.0,
+        }
+    }
+}
+
+impl ToneConfig {
+    pub(crate) fn validate(self) -> Result<(), crate::Error> {
+        if self.duration.is_zero() {
+            return Err(crate::Error::Configuration(
+                "DTMF tone duration must be positive".to_owned(),
+            ));
+        }
+        // Two tones at -6 dBFS each already reach full scale.
+        if self.level.is_nan() || self.level > -6.0 {
+            return Err(crate::Error::Configuration(
+                "DTMF tone level must be at most -6 dBFS".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Mixes queued digits into outbound frames, for driving IVRs in tests.
+pub struct DtmfGenerator {
+    config: ToneConfig,
+    sample_rate: u32,
+    channels: u16,
+    amplitude: f64,
+    segments: VecDeque<Segment>,
+}
+
+/// A stretch of tone, or of silence when `tones` is `None`.
+struct Segment {
+    tones: Option<(f64, f64)>,
+    offset: usize,
+    length: usize,
+}
+
+impl DtmfGenerator {
+    /// Construct a generator for frames in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error for an invalid `config` or an invalid-format error for
+    /// anything but L16.
+    pub fn new(format: &MediaFormat, config: ToneConfig) -> Result<Self, crate::Error> {
+        config.validate()?;
+        format.frame_bytes()?;
+        require_l16(format, "DTMF generation")?;
+        Ok(Self {
+            config,
+            sample_rate: format.sample_rate,
+            channels: format.channels,
+            amplitude: 32_767.0 * 10_f64.powf(config.level / 20.0),
+            segments: VecDeque::new(),
+        })
+    }
+
+    /// Queue `digits` after any digits still sounding.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error, queuing nothing, when any digit is not one of
+    /// `0`-`9`, `*`, `#`, or `A`-`D`.
+    pub fn queue(&mut self, digits: &str) -> Result<(), crate::Error> {
+        let tones = digits
+            .bytes()
+            .map(|digit| {
+                tones(digit).ok_or_else(|| {
+                    crate::ValidationError::new(format!(
+                        "unsupported DTMF digit {:?}",
+                        char::from(digit)
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let length = samples(self.config.duration, self.sample_rate);
+        let gap = samples(self.config.gap, self.sample_rate);
+        for pair in tones {
+            self.segments.push_back(Segment {
+                tones: Some(pair),
+                offset: 0,
+                length,
+            });
+            self.segments.push_back(Segment {
+                tones: None,
+                offset: 0,
+                length: gap,
+            });
+        }
+        Ok(())
+    }
+
+    /// Return whether every queued digit and gap has been mixed.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Add the queued tones to an L16 `frame`, saturating at full scale.
+    ///
+    /// Frames mixed while idle are left unchanged.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn mix(&mut self, frame: &mut [u8]) {
+        let width = 2 * usize::from(self.channels);
+        for sample in frame.chunks_exact_mut(width) {
+            let Some(segment) = self.segments.front_mut() else {
+                return;
+            };
+            if let Some((row, column)) = segment.tones {
+                let time = segment.offset as f64 / f64::from(self.sample_rate);
+                let tone =
+                    self.amplitude * ((TAU * row * time).sin() + (TAU * column * time).sin());
+                for bytes in sample.chunks_exact_mut(2) {
+                    let mixed = f64::from(i16::from_le_bytes([bytes[0], bytes[1]])) + tone;
+                    let mixed = mixed
+                        .round()
+                        .clamp(f64::from(i16::MIN), f64::from(i16::MAX));
+                    bytes.copy_from_slice(&(mixed as i16).to_le_bytes());
+                }
+            }
+            segment.offset += 1;
+            if segment.offset >= segment.length {
+                self.segments.pop_front();
+            }
+        }
+    }
+}
+
+/// Render `digits` with their gaps as L16 audio in `format`, ready for
+/// [`AudioStream::write`].
+///
+/// # Errors
+///
+/// Returns the [`DtmfGenerator::new`] and [`DtmfGenerator::queue`] failures.
+pub fn generate(
+    format: &MediaFormat,
+    digits: &str,
+    config: ToneConfig,
+) -> Result<Vec<u8>, crate::Error> {
+    let mut generator = DtmfGenerator::new(format, config)?;
+    generator.queue(digits)?;
+    let per_digit =
+        samples(config.duration, format.sample_rate) + samples(config.gap, format.sample_rate);
+    let mut audio = vec![0; digits.len() * per_digit * 2 * usize::from(format.channels)];
+    generator.mix(&mut audio);
+    Ok(audio)
+}
+
+fn require_l16(format: &MediaFormat, purpose: &str) -> Result<(), crate::Error> {
+    if format.encoding != "L16" {
+        return Err(crate::Error::InvalidMediaFormat(format!(
+            "{purpose} needs L16 audio, got {:?}",
+            format.encoding
+        )));
+    }
+    Ok(())
+}
+
+/// Whole samples in `duration` at `sample_rate`, rounded to the nearest.
+fn samples(duration: Duration, sample_rate: u32) -> usize {
+    let samples = (duration.as_nanos() * u128::from(sample_rate) + 500_000_000) / 1_000_000_000;
+    usize::try_from(samples).unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format() -> MediaFormat {
+        MediaFormat {
+            encoding: "L16".to_owned(),
+            sample_rate: 8_000,
+            bit_depth: 16,
+            channels: 1,
+            ptime: Duration::from_millis(20),
+        }
+    }
+
+    /// Feed `audio` in 20 ms frames captured back to back from the epoch.
+    fn detect(detector: &mut DtmfDetector, audio: &[u8], elapsed: &mut Duration) -> Vec<DtmfDigit> {
+        let mut events = Vec::new();
+        for frame in audio.chunks(320) {
+            *elapsed += Duration::from_millis(20);
+            events.extend(detector.process(frame, UNIX_EPOCH + *elapsed));
+        }
+        events
+    }
+
+    #[test]
+    fn generated_digits_are_detected_in_order_with_their_timing() {
+        let audio = generate(&format(), "1#9d00", ToneConfig::default()).unwrap();
+        let mut detector = DtmfDetector::new(&format()).unwrap();
+        let mut elapsed = Duration::ZERO;
+        let events = detect(&mut detector, &audio, &mut elapsed);
+        let digits: Vec<_> = events.iter().map(|event| event.digit.as_str()).collect();
+        assert_eq!(digits, ["1", "#", "9", "D", "0", "0"]);
+        for (event, index) in events.iter().zip(0_u64..) {
+            // Each digit sounds for 100 ms of every 200, measured to within a 25.6 ms block.
+            let onset = Duration::from_millis(index * 200);
+            let pressed = event.pressed_at.duration_since(UNIX_EPOCH).unwrap();
+            assert!(
+                pressed.abs_diff(onset) < Duration::from_millis(26),
+                "{event:?}"
+            );
+            let held = event.released_at.duration_since(event.pressed_at).unwrap();
+            assert!(
+                (Duration::from_millis(75)..=Duration::from_millis(125)).contains(&held),
+                "{event:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn mixed_tones_survive_speech_level_noise_but_noise_alone_is_not_a_digit() {
+        let mut generator = DtmfGenerator::new(&format(), ToneConfig::default()).unwrap();
+        generator.queue("5").unwrap();
+        let mut detector = DtmfDetector::new(&format()).unwrap();
+        let mut elapsed = Duration::ZERO;
+        let mut events = Vec::new();
+        let mut state: u32 = 1;
+        while !generator.is_idle() {
+            let mut frame: Vec<u8> = (0..160)
+                .flat_map(|_| {
+                    state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    let noise = i16::try_from((state >> 16) % 2_000).unwrap() - 1_000;
+                    noise.to_le_bytes()
+                })
+                .collect();
+            generator.mix(&mut frame);
+            events.extend(detect(&mut detector, &frame, &mut elapsed));
+        }
+        events.extend(detector.flush());
+        let digits: Vec<_> = events.iter().map(|event| event.digit.as_str()).collect();
+        assert_eq!(digits, ["5"]);
+    }
+
+    #[test]
+    fn invalid_digits_and_formats_are_rejected() {
+        let mut generator = DtmfGenerator::new(&format(), ToneConfig::default()).unwrap();
+        assert!(matches!(
+            generator.queue("12E"),
+            Err(crate::Error::Validation(_))
+        ));
+        assert!(generator.is_idle());
+        let mut pcmu = format();
+        pcmu.encoding = "PCMU".to_owned();
+        pcmu.bit_depth = 8;
+        assert!(DtmfDetector::new(&pcmu).is_err());
+        let loud = ToneConfig {
+            level: 0.0,
+            ..ToneConfig::default()
+        };
+        assert!(DtmfGenerator::new(&format(), loud).is_err());
+    }
+}
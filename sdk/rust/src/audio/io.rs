@@ -0,0 +1,347 @@
+//! Poll-based halves of [`AudioStream`] for `tokio::io` and `futures` combinators.
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+
+use futures_util::{Sink, Stream};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use super::AudioStream;
+use crate::MediaFrame;
+
+/// A boxed wait for buffer space or data, owning its stream so halves need no lifetime.
+type Wait = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+impl AudioStream {
+    /// Split the byte view into [`AsyncRead`] and [`AsyncWrite`] halves.
+    ///
+    /// The halves share the buffers behind [`AudioStream::read`] and [`AudioStream::write`],
+    /// including their observers, so mixing both styles interleaves their bytes.
+    #[must_use]
+    pub fn split(self: &Arc<Self>) -> (AudioReader, AudioWriter) {
+        (
+            AudioReader {
+                audio: Arc::clone(self),
+                wait: None,
+            },
+            AudioWriter {
+                audio: Arc::clone(self),
+                wait: None,
+                shut_down: false,
+            },
+        )
+    }
+
+    /// Split the frame view into a [`Stream`] of inbound timed frames and a [`Sink`] of
+    /// outbound bytes.
+    ///
+    /// The stream consumes the frames behind [`AudioStream::read_timed_frame`].
+    #[must_use]
+    pub fn frames(self: &Arc<Self>) -> (AudioFrameStream, AudioFrameSink) {
+        (
+            AudioFrameStream {
+                audio: Arc::clone(self),
+                wait: None,
+            },
+            AudioFrameSink {
+                audio: Arc::clone(self),
+                pending: Vec::new(),
+                written: 0,
+                wait: None,
+                closed: false,
+            },
+        )
+    }
+
+    fn inbound_readable(self: &Arc<Self>) -> Wait {
+        let audio = Arc::clone(self);
+        Box::pin(async move { audio.inbound.readable().await })
+    }
+
+    fn outbound_writable(self: &Arc<Self>) -> Wait {
+        let audio = Arc::clone(self);
+        Box::pin(async move { audio.outbound.writable().await })
+    }
+
+    fn timed_readable(self: &Arc<Self>) -> Wait {
+        let audio = Arc::clone(self);
+        Box::pin(async move { audio.timed.readable().await })
+    }
+}
+
+/// Inbound bytes as [`AsyncRead`]. Reads reach end of file once the stream closes and drains.
+pub struct AudioReader {
+    audio: Arc<AudioStream>,
+    wait: Option<Wait>,
+}
+
+impl AsyncRead for AudioReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(wait) = this.wait.as_mut() {
+                ready!(wait.as_mut().poll(context));
+                this.wait = None;
+            }
+            match this.audio.inbound.try_read(buf.initialize_unfilled()) {
+                Ok(Some(count)) => {
+                    buf.advance(count);
+                    this.audio.report(count, |observer| &observer.on_read);
+                    return Poll::Ready(Ok(()));
+                }
+                Ok(None) => this.wait = Some(this.audio.inbound_readable()),
+                Err(crate::Error::Closed) => return Poll::Ready(Ok(())),
+                Err(error) => return Poll::Ready(Err(io::Error::other(error))),
+            }
+        }
+    }
+}
+
+/// Outbound bytes as [`AsyncWrite`], with the stream's bounded backpressure.
+///
+/// Writes fail with [`io::ErrorKind::BrokenPipe`] after the stream closes or this half shuts
+/// down. Flushing returns at once: written bytes are already queued for the transport.
+pub struct AudioWriter {
+    audio: Arc<AudioStream>,
+    wait: Option<Wait>,
+    shut_down: bool,
+}
+
+impl AsyncWrite for AudioWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.shut_down {
+            return Poll::Ready(Err(broken_pipe(crate::Error::Closed)));
+        }
+        loop {
+            if let Some(wait) = this.wait.as_mut() {
+                ready!(wait.as_mut().poll(context));
+                this.wait = None;
+            }
+            match this.audio.try_write_outbound(buf) {
+                Ok(Some(count)) => return Poll::Ready(Ok(count)),
+                Ok(None) => this.wait = Some(this.audio.outbound_writable()),
+                Err(error) => return Poll::Ready(Err(broken_pipe(error))),
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Stop this half from writing; the session's audio stays open.
+    fn poll_shutdown(self: Pin<&mut Self>, _context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().shut_down = true;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Inbound timed frames as a [`Stream`] that ends once the stream closes and drains.
+pub struct AudioFrameStream {
+    audio: Arc<AudioStream>,
+    wait: Option<Wait>,
+}
+
+impl Stream for AudioFrameStream {
+    type Item = MediaFrame;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<MediaFrame>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(wait) = this.wait.as_mut() {
+                ready!(wait.as_mut().poll(context));
+                this.wait = None;
+            }
+            match this.audio.timed.try_pop() {
+                Ok(Some(frame)) => return Poll::Ready(Some(frame)),
+                Ok(None) => this.wait = Some(this.audio.timed_readable()),
+                Err(_) => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// Outbound byte frames as a [`Sink`] failing with [`crate::Error::Closed`] after closure.
+///
+/// Each item is queued whole before the next is accepted; item boundaries need not match the
+/// packetization frame, which the transport pump regroups.
+pub struct AudioFrameSink {
+    audio: Arc<AudioStream>,
+    pending: Vec<u8>,
+    written: usize,
+    wait: Option<Wait>,
+    closed: bool,
+}
+
+impl AudioFrameSink {
+    fn poll_drain(&mut self, context: &mut Context<'_>) -> Poll<Result<(), crate::Error>> {
+        while self.written < self.pending.len() {
+            if let Some(wait) = self.wait.as_mut() {
+                ready!(wait.as_mut().poll(context));
+                self.wait = None;
+            }
+            match self.audio.try_write_outbound(&self.pending[self.written..]) {
+                Ok(Some(count)) => self.written += count,
+                Ok(None) => self.wait = Some(self.audio.outbound_writable()),
+                Err(error) => {
+                    self.pending.clear();
+                    self.written = 0;
+                    return Poll::Ready(Err(error));
+                }
+            }
+        }
+        self.pending.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Sink<Vec<u8>> for AudioFrameSink {
+    type Error = crate::Error;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+    ) -> Poll<Result<(), crate::Error>> {
+        let this = self.get_mut();
+        if this.closed {
+            return Poll::Ready(Err(crate::Error::Closed));
+        }
+        this.poll_drain(context)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Vec<u8>) -> Result<(), crate::Error> {
+        let this = self.get_mut();
+        if this.closed {
+            return Err(crate::Error::Closed);
+        }
+        this.pending = item;
+        Ok(())
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+    ) -> Poll<Result<(), crate::Error>> {
+        self.get_mut().poll_drain(context)
+    }
+
+    /// Queue the last item and refuse further ones; the session's audio stays open.
+    fn poll_close(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+    ) -> Poll<Result<(), crate::Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(context))?;
+        this.closed = true;
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn broken_pipe(error: crate::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::MediaFormat;
+
+    fn format() -> MediaFormat {
+        MediaFormat {
+            encoding: "L16".to_owned(),
+            sample_rate: 8_000,
+            bit_depth: 16,
+            channels: 1,
+            ptime: Duration::from_millis(20),
+        }
+    }
+
+    #[tokio::test]
+    async fn byte_halves_apply_backpressure_and_end_with_the_stream() {
+        let audio = Arc::new(AudioStream::new(512));
+        audio.set_format(format()).unwrap();
+        let (mut reader, mut writer) = audio.split();
+
+        let pump = {
+            let audio = Arc::clone(&audio);
+            tokio::spawn(async move {
+                let mut frames = Vec::new();
+                while let Ok(frame) = audio.read_outbound_frame().await {
+                    frames.push(frame);
+                }
+                frames
+            })
+        };
+        writer.write_all(&[5; 640]).await.unwrap();
+        assert_eq!(audio.playback_stats().written, 640);
+
+        audio
+            .push_inbound_frame(MediaFrame::untimed(vec![1, 2, 3]))
+            .await
+            .unwrap();
+        audio
+            .push_inbound_frame(MediaFrame::untimed(vec![4]))
+            .await
+            .unwrap();
+        let mut first = [0; 2];
+        reader.read_exact(&mut first).await.unwrap();
+        assert_eq!(first, [1, 2]);
+        audio.close();
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(rest, [3, 4]);
+
+        let error = writer.write_all(&[6; 4]).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+        assert_eq!(pump.await.unwrap(), [vec![5; 320], vec![5; 320]]);
+    }
+
+    #[tokio::test]
+    async fn frame_halves_stream_timed_frames_and_sink_outbound_bytes() {
+        let audio = Arc::new(AudioStream::new(1_024));
+        audio.set_format(format()).unwrap();
+        let (mut frames, mut sink) = audio.frames();
+
+        let timed = MediaFrame {
+            data: vec![7; 4],
+            pts: Some(Duration::from_millis(20)),
+        };
+        audio.push_inbound_frame(timed.clone()).await.unwrap();
+        assert_eq!(frames.next().await, Some(timed));
+
+        sink.send(vec![8; 320]).await.unwrap();
+        assert_eq!(audio.playback_stats().written, 320);
+        assert_eq!(audio.read_outbound_frame().await.unwrap(), vec![8; 320]);
+        sink.close().await.unwrap();
+        assert!(matches!(
+            sink.send(vec![9; 320]).await,
+            Err(crate::Error::Closed)
+        ));
+
+        let (_, mut open) = audio.frames();
+        audio.close();
+        assert_eq!(frames.next().await, None);
+        assert!(matches!(
+            open.send(vec![9; 320]).await,
+            Err(crate::Error::Closed)
+        ));
+    }
+}
@@ -0,0 +1,440 @@
+//! Optional playout stage that reorders timed inbound frames and conceals gaps.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Notify;
+
+use super::AudioStream;
+use crate::{MediaFormat, MediaFrame};
+
+/// Consecutive repeated frames before concealment falls silent.
+const MAX_REPEATS: u32 = 3;
+
+/// How the jitter buffer fills a frame that is missing at its playout time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Concealment {
+    /// Play silence.
+    Silence,
+    /// Repeat the last L16 frame at half the level per consecutive loss, then fall silent.
+    #[default]
+    Repeat,
+}
+
+/// Inbound jitter-buffer configuration.
+///
+/// The playout delay adapts to measured interarrival jitter within these bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JitterBufferConfig {
+    pub min_delay: Duration,
+    /// Largest playout delay; a deeper backlog is discarded, and a gap this long pauses playout
+    /// until frames arrive again.
+    pub max_delay: Duration,
+    pub concealment: Concealment,
+}
+
+impl Default for JitterBufferConfig {
+    fn default() -> Self {
+        Self {
+            min_delay: Duration::from_millis(40),
+            max_delay: Duration::from_millis(300),
+            concealment: Concealment::default(),
+        }
+    }
+}
+
+impl JitterBufferConfig {
+    pub(crate) fn validate(&self) -> Result<(), crate::Error> {
+        if self.max_delay.is_zero() || self.min_delay > self.max_delay {
+            return Err(crate::Error::Configuration(
+                "jitter buffer delays must satisfy 0 <= min_delay <= max_delay, max_delay > 0"
+                    .to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Jitter-buffer counters since audio was bound.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JitterBufferStats {
+    /// Frames that arrived after their playout time and were dropped.
+    pub late: u64,
+    /// Frames missing at their playout time and concealed.
+    pub lost: u64,
+    /// Buffered frames dropped to bring the delay back to its target.
+    pub discarded: u64,
+    /// Interarrival jitter of timed frames, per RFC 3550.
+    pub jitter: Duration,
+    /// Current target playout delay.
+    pub delay: Duration,
+}
+
+/// Shared buffer between the inbound media reader and the playout clock.
+pub(crate) struct JitterBuffer {
+    state: Mutex<JitterState>,
+    arrived: Notify,
+}
+
+impl JitterBuffer {
+    pub(crate) fn new(
+        config: JitterBufferConfig,
+        format: &MediaFormat,
+    ) -> Result<Self, crate::Error> {
+        Ok(Self {
+            state: Mutex::new(JitterState::new(config, format)?),
+            arrived: Notify::new(),
+        })
+    }
+
+    pub(crate) fn insert(&self, frame: MediaFrame) {
+        self.lock().insert(frame, Instant::now());
+        self.arrived.notify_one();
+    }
+
+    /// Stop admission; playout drains what is buffered and then finishes.
+    pub(crate) fn close(&self) {
+        self.lock().closed = true;
+        self.arrived.notify_one();
+    }
+
+    pub(crate) fn stats(&self) -> JitterBufferStats {
+        self.lock().stats
+    }
+
+    /// Release one frame per packetization interval into `audio` until closed and drained.
+    ///
+    /// Playout starts one target delay after the first frame and pauses after a gap of
+    /// `max_delay`, restarting the same way. `convert` adapts each released frame to the audio
+    /// stream's format and may hold it back.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::Closed`] when the audio stream closes.
+    pub(crate) async fn play(
+        &self,
+        audio: &AudioStream,
+        mut convert: impl FnMut(MediaFrame) -> Vec<MediaFrame> + Send,
+    ) -> Result<(), crate::Error> {
+        loop {
+            let (delay, ptime) = loop {
+                let notified = self.arrived.notified();
+                {
+                    let state = self.lock();
+                    if !state.frames.is_empty() {
+                        break (state.stats.delay, state.ptime);
+                    }
+                    if state.closed {
+                        return Ok(());
+                    }
+                }
+                notified.await;
+            };
+            tokio::time::sleep(delay).await;
+            let mut clock = tokio::time::interval(ptime);
+            loop {
+                clock.tick().await;
+                let Some(frame) = self.lock().pop() else {
+                    break;
+                };
+                for frame in convert(frame) {
+                    audio.push_inbound_frame(frame).await?;
+                }
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, JitterState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Frame bookkeeping indexed by `pts / ptime`; untimed frames take consecutive indexes.
+struct JitterState {
+    config: JitterBufferConfig,
+    ptime: Duration,
+    silence: Vec<u8>,
+    l16: bool,
+    frames: BTreeMap<u64, Vec<u8>>,
+    /// Next index to play while playout runs.
+    next: Option<u64>,
+    next_untimed: u64,
+    last_arrival: Option<(Instant, Duration)>,
+    /// Jitter in nanoseconds, scaled by 16 like the RFC 3550 integer estimator.
+    jitter: u128,
+    last_played: Option<Vec<u8>>,
+    losses: u32,
+    closed: bool,
+    stats: JitterBufferStats,
+}
+
+impl JitterState {
+    fn new(config: JitterBufferConfig, format: &MediaFormat) -> Result<Self, crate::Error> {
+        config.validate()?;
+        let mut state = Self {
+            config,
+            ptime: format.ptime,
+            silence: format.silence()?,
+            l16: format.encoding == "L16",
+            frames: BTreeMap::new(),
+            next: None,
+            next_untimed: 0,
+            last_arrival: None,
+            jitter: 0,
+            last_played: None,
+            losses: 0,
+            closed: false,
+            stats: JitterBufferStats::default(),
+        };
+        state.adapt();
+        Ok(state)
+    }
+
+    fn insert(&mut self, frame: MediaFrame, arrival: Instant) {
+        if self.closed {
+            return;
+        }
+        let index = match frame.pts {
+            Some(pts) => {
+                self.measure(pts, arrival);
+                let ptime = self.ptime.as_nanos();
+                u64::try_from((pts.as_nanos() + ptime / 2) / ptime).unwrap_or(u64::MAX)
+            }
+            None => self.next_untimed,
+        };
+        self.next_untimed = self.next_untimed.max(index.saturating_add(1));
+        if self.next.is_some_and(|next| index < next) {
+            self.stats.late += 1;
+            return;
+        }
+        self.frames.entry(index).or_insert(frame.data);
+    }
+
+    /// Update the RFC 3550 jitter estimate from media time against arrival time.
+    fn measure(&mut self, pts: Duration, arrival: Instant) {
+        if let Some((last_arrival, last_pts)) = self.last_arrival.replace((arrival, pts)) {
+            let arrived = signed(arrival.saturating_duration_since(last_arrival));
+            let spaced = signed(pts) - signed(last_pts);
+            let deviation = (arrived - spaced).unsigned_abs();
+            self.jitter = self.jitter + deviation - (self.jitter + 8) / 16;
+            self.adapt();
+        }
+    }
+
+    /// Target four jitter estimates beyond one frame, rounded up to whole frames.
+    fn adapt(&mut self) {
+        let ptime = self.ptime.as_nanos();
+        let wanted = ptime + self.jitter / 16 * 4;
+        let frames = u32::try_from(wanted.div_ceil(ptime)).unwrap_or(u32::MAX);
+        self.stats.jitter = nanos(self.jitter / 16);
+        self.stats.delay = self
+            .ptime
+            .saturating_mul(frames)
+            .clamp(self.config.min_delay, self.config.max_delay);
+    }
+
+    /// Return the frame due now, concealing a gap, or `None` to pause playout.
+    fn pop(&mut self) -> Option<MediaFrame> {
+        let next = self.next.or_else(|| self.frames.keys().next().copied())?;
+        let data = if let Some(data) = self.frames.remove(&next) {
+            self.losses = 0;
+            self.last_played = Some(data.clone());
+            data
+        } else {
+            if self.frames.is_empty() && (self.closed || self.gap() >= self.config.max_delay) {
+                self.next = None;
+                self.losses = 0;
+                return None;
+            }
+            self.stats.lost += 1;
+            self.losses += 1;
+            self.conceal()
+        };
+        self.next = Some(next + 1);
+        self.shrink();
+        Some(MediaFrame {
+            data,
+            pts: Some(
+                self.ptime
+                    .saturating_mul(u32::try_from(next).unwrap_or(u32::MAX)),
+            ),
+        })
+    }
+
+    fn gap(&self) -> Duration {
+        self.ptime.saturating_mul(self.losses)
+    }
+
+    /// Cut a backlog beyond `max_delay` at once, then drift one frame per tick toward the target.
+    fn shrink(&mut self) {
+        let (Some(mut next), Some(&newest)) = (self.next, self.frames.keys().next_back()) else {
+            return;
+        };
+        let drift = (self.stats.delay * 2).max(self.stats.delay + self.ptime);
+        if self.backlog(next, newest) > self.config.max_delay {
+            // Jump in one step: a PTS jump can put millions of empty indexes before `newest`.
+            let kept = u64::try_from(self.config.max_delay.as_nanos() / self.ptime.as_nanos())
+                .unwrap_or(u64::MAX);
+            next = (newest + 1).saturating_sub(kept);
+            let backlog = self.frames.split_off(&next);
+            let discarded = std::mem::replace(&mut self.frames, backlog).len();
+            self.stats.discarded += u64::try_from(discarded).unwrap_or(u64::MAX);
+        }
+        if self.backlog(next, newest) > drift {
+            self.discard(next);
+            next += 1;
+        }
+        self.next = Some(next);
+    }
+
+    fn backlog(&self, next: u64, newest: u64) -> Duration {
+        let frames = (newest + 1).saturating_sub(next);
+        self.ptime
+            .saturating_mul(u32::try_from(frames).unwrap_or(u32::MAX))
+    }
+
+    fn discard(&mut self, index: u64) {
+        if self.frames.remove(&index).is_some() {
+            self.stats.discarded += 1;
+        }
+    }
+
+    fn conceal(&self) -> Vec<u8> {
+        match (&self.last_played, self.config.concealment) {
+            (Some(last), Concealment::Repeat) if self.l16 && self.losses <= MAX_REPEATS => last
+                .chunks_exact(2)
+                .flat_map(|sample| {
+                    (i16::from_le_bytes([sample[0], sample[1]]) >> self.losses).to_le_bytes()
+                })
+                .collect(),
+            _ => self.silence.clone(),
+        }
+    }
+}
+
+fn signed(duration: Duration) -> i128 {
+    i128::try_from(duration.as_nanos()).unwrap_or(i128::MAX)
+}
+
+fn nanos(value: u128) -> Duration {
+    Duration::from_nanos(u64::try_from(value).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format() -> MediaFormat {
+        MediaFormat {
+            encoding: "L16".to_owned(),
+            sample_rate: 8_000,
+            bit_depth: 16,
+            channels: 1,
+            ptime: Duration::from_millis(20),
+        }
+    }
+
+    fn frame(index: u32, sample: i16) -> MediaFrame {
+        MediaFrame {
+            data: (0..160).flat_map(|_| sample.to_le_bytes()).collect(),
+            pts: Some(Duration::from_millis(20) * index),
+        }
+    }
+
+    fn first_sample(frame: &MediaFrame) -> i16 {
+        i16::from_le_bytes([frame.data[0], frame.data[1]])
+    }
+
+    #[test]
+    fn reorders_conceals_and_drops_late_frames() {
+        let mut state = JitterState::new(JitterBufferConfig::default(), &format()).unwrap();
+        let start = Instant::now();
+        for (index, sample) in [(0, 800), (2, 400), (1, 1_000), (4, 200)] {
+            state.insert(frame(index, sample), start);
+        }
+        let played: Vec<_> = (0..5).map(|_| state.pop().unwrap()).collect();
+        let samples: Vec<_> = played.iter().map(first_sample).collect();
+        // Frame 3 never arrived: its slot repeats frame 2 at half level.
+        assert_eq!(samples, [800, 1_000, 400, 200, 200]);
+        assert_eq!(played[3].pts, Some(Duration::from_millis(60)));
+
+        state.insert(frame(3, 600), start);
+        state.insert(frame(2, 600), start);
+        let counts = (state.stats.late, state.stats.lost, state.stats.discarded);
+        assert_eq!(counts, (2, 1, 0));
+    }
+
+    #[test]
+    fn repeated_concealment_fades_then_pauses_playout() {
+        let config = JitterBufferConfig {
+            max_delay: Duration::from_millis(100),
+            ..JitterBufferConfig::default()
+        };
+        let mut state = JitterState::new(config, &format()).unwrap();
+        state.insert(frame(0, 1_600), Instant::now());
+        assert_eq!(first_sample(&state.pop().unwrap()), 1_600);
+        let concealed: Vec<_> = (0..5)
+            .map(|_| first_sample(&state.pop().unwrap()))
+            .collect();
+        assert_eq!(concealed, [800, 400, 200, 0, 0]);
+        assert!(state.pop().is_none());
+        assert_eq!(state.stats.lost, 5);
+
+        // Playout restarts at the next arrival rather than at the old position.
+        state.insert(frame(40, 100), Instant::now());
+        assert_eq!(state.pop().unwrap().pts, Some(Duration::from_millis(800)));
+    }
+
+    #[test]
+    fn delay_follows_jitter_within_bounds_and_backlog_is_discarded() {
+        let mut state = JitterState::new(JitterBufferConfig::default(), &format()).unwrap();
+        assert_eq!(state.stats.delay, Duration::from_millis(40));
+        let start = Instant::now();
+        // Alternate 0 and 40 ms arrival spacing for 20 ms frames.
+        for index in 0..64 {
+            let arrival = start + Duration::from_millis(40) * (index / 2);
+            state.insert(frame(index, 1), arrival);
+        }
+        assert!(state.stats.jitter > Duration::from_millis(15));
+        assert!(state.stats.delay >= Duration::from_millis(80));
+        assert!(state.stats.delay <= Duration::from_millis(300));
+
+        // The 64-frame backlog is cut to the maximum delay, then drifts toward the target.
+        let first = state.pop().unwrap();
+        assert_eq!(first.pts, Some(Duration::ZERO));
+        let buffered = u32::try_from(state.frames.len()).unwrap();
+        assert_eq!(
+            Duration::from_millis(20) * (buffered + 1),
+            Duration::from_millis(300)
+        );
+        assert_eq!(state.stats.discarded, u64::from(63 - buffered));
+        state.pop().unwrap();
+        assert_eq!(state.frames.len(), usize::try_from(buffered).unwrap() - 2);
+
+        // A PTS jump of millions of frames is cut at once instead of index by index.
+        let newest = 26_843_545;
+        state.insert(frame(newest, 1), start);
+        let discarded = state.stats.discarded;
+        assert_eq!(
+            state.pop().unwrap().pts,
+            Some(Duration::from_millis(20) * 52)
+        );
+        assert_eq!(state.frames.len(), 1);
+        assert_eq!(state.next, Some(u64::from(newest) - 14));
+        assert_eq!(state.stats.discarded, discarded + 11);
+
+        assert!(
+            JitterState::new(
+                JitterBufferConfig {
+                    min_delay: Duration::from_millis(400),
+                    ..JitterBufferConfig::default()
+                },
+                &format()
+            )
+            .is_err()
+        );
+    }
+}
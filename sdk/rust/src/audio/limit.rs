@@ -0,0 +1,56 @@
+//! Latency caps on the session audio buffers.
+
+use std::time::Duration;
+
+use crate::MediaFormat;
+
+/// What a capped buffer does with audio that would exceed its limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Wait for the consumer, as uncapped buffers do.
+    #[default]
+    Block,
+    /// Discard the oldest buffered frames to admit new audio.
+    DropOldest,
+    /// Discard the part of new audio that does not fit.
+    DropNewest,
+}
+
+/// Cap on one direction's buffered audio, in time rather than bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferLimit {
+    /// Most audio the buffer holds, rounded up to whole packetization frames.
+    pub max_delay: Duration,
+    pub overflow: Overflow,
+}
+
+impl BufferLimit {
+    pub(crate) fn validate(self) -> Result<(), crate::Error> {
+        if self.max_delay.is_zero() {
+            return Err(crate::Error::Configuration(
+                "audio buffer max_delay must be positive".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Return the limit in whole frames of `format`, never less than one.
+    pub(super) fn frames(self, format: &MediaFormat) -> usize {
+        let frames = self
+            .max_delay
+            .as_nanos()
+            .div_ceil(format.ptime.as_nanos().max(1));
+        usize::try_from(frames).unwrap_or(usize::MAX).max(1)
+    }
+}
+
+/// Audio discarded by capped buffers since the stream was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AudioBufferStats {
+    /// Inbound bytes dropped before the application read them.
+    pub inbound_dropped: u64,
+    /// Outbound bytes dropped before they were sent.
+    pub outbound_dropped: u64,
+    /// Timed inbound frames dropped before [`super::AudioStream::read_timed_frame`] took them.
+    pub timed_dropped: u64,
+}
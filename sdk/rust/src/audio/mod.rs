@@ -1,12 +1,38 @@
 //! Session-owned bounded duplex byte audio and timed-frame observation.
 
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use tokio::sync::Notify;
 
 use crate::{MediaFormat, MediaFrame};
 
+pub mod dtmf;
+mod io;
+mod jitter;
+mod limit;
+mod monitor;
+mod pacer;
+mod playback;
+pub mod recorder;
+mod resample;
+mod tap;
+pub mod vad;
+
+pub use io::{AudioFrameSink, AudioFrameStream, AudioReader, AudioWriter};
+pub(crate) use jitter::JitterBuffer;
+pub use jitter::{Concealment, JitterBufferConfig, JitterBufferStats};
+pub use limit::{AudioBufferStats, BufferLimit, Overflow};
+pub(crate) use pacer::{Pacer, PacerCounters};
+pub use pacer::{PacerConfig, PacerStats, Underrun};
+pub use playback::PlaybackStats;
+pub(crate) use playback::duration_of;
+pub(crate) use resample::Resampler;
+pub use resample::{ResamplerConfig, ResamplerQuality};
+pub use tap::{AudioTap, Direction, TappedFrame};
+
 /// Bounded duplex byte stream used by one session audio channel.
 pub struct AudioStream {
     inbound: Arc<ByteBuffer>,
@@ -14,6 +40,14 @@ pub struct AudioStream {
     format: Mutex<Option<MediaFormat>>,
     timed: Arc<FrameBuffer>,
     observers: Mutex<Vec<AudioObserver>>,
+    taps: tap::Taps,
+    /// Outbound frames sent so far, which time outbound taps.
+    sent: AtomicU32,
+    written: AtomicU64,
+    played: AtomicU64,
+    cleared: AtomicU64,
+    inbound_limit: Option<BufferLimit>,
+    outbound_limit: Option<BufferLimit>,
 }
 
 /// Synchronous byte-count callbacks for application reads and writes.
@@ -38,9 +72,32 @@ impl AudioStream {
             format: Mutex::new(None),
             timed: Arc::new(FrameBuffer::new()),
             observers: Mutex::new(Vec::new()),
+            taps: tap::Taps::default(),
+            sent: AtomicU32::new(0),
+            written: AtomicU64::new(0),
+            played: AtomicU64::new(0),
+            cleared: AtomicU64::new(0),
+            inbound_limit: None,
+            outbound_limit: None,
         }
     }
 
+    /// Cap each direction's buffered audio once the format is known.
+    ///
+    /// A cap never raises the byte capacity given to [`AudioStream::new`]. The inbound cap also
+    /// bounds the timed frames behind [`AudioStream::read_timed_frame`], which never block and
+    /// drop their oldest frame unless the policy is [`Overflow::DropNewest`].
+    #[must_use]
+    pub fn with_limits(
+        mut self,
+        inbound: Option<BufferLimit>,
+        outbound: Option<BufferLimit>,
+    ) -> Self {
+        self.inbound_limit = inbound;
+        self.outbound_limit = outbound;
+        self
+    }
+
     /// Return the immutable negotiated format, when media is bound.
     #[must_use]
     pub fn format(&self) -> Option<MediaFormat> {
@@ -67,6 +124,7 @@ impl AudioStream {
                 "format is already negotiated as {existing:?}"
             ))),
             None => {
+                self.apply_limits(&format)?;
                 *selected = Some(format);
                 Ok(())
             }
@@ -80,16 +138,7 @@ impl AudioStream {
     /// Returns [`crate::Error::Closed`] after buffered data drains.
     pub async fn read(&self, output: &mut [u8]) -> Result<usize, crate::Error> {
         let count = self.inbound.read(output).await?;
-        let callbacks: Vec<_> = self
-            .observers
-            .lock()
-            .unwrap_or_else(std::sync::PoisonError::into_inner)
-            .iter()
-            .map(|observer| Arc::clone(&observer.on_read))
-            .collect();
-        for callback in &callbacks {
-            callback(count);
-        }
+        self.report(count, |observer| &observer.on_read);
         Ok(count)
     }
 
@@ -99,18 +148,80 @@ impl AudioStream {
     ///
     /// Returns [`crate::Error::Closed`] after shutdown.
     pub async fn write(&self, input: &[u8]) -> Result<usize, crate::Error> {
-        let count = self.outbound.write(input).await?;
+        let mut written = 0;
+        while written < input.len() {
+            let notified = self.outbound.writable.notified();
+            match self.try_write_outbound(&input[written..]) {
+                Ok(Some(count)) => written += count,
+                Ok(None) => notified.await,
+                Err(error) if written == 0 => return Err(error),
+                Err(_) => break,
+            }
+        }
+        Ok(written)
+    }
+
+    /// Append what fits of `input` without waiting, counting and reporting it as written.
+    ///
+    /// Every outbound writer goes through here so [`AudioStream::playback_stats`] sees all of
+    /// them.
+    fn try_write_outbound(&self, input: &[u8]) -> Result<Option<usize>, crate::Error> {
+        let count = self.outbound.try_write(input)?;
+        if let Some(count) = count {
+            self.written.fetch_add(byte_count(count), Ordering::Relaxed);
+            self.report(count, |observer| &observer.on_write);
+        }
+        Ok(count)
+    }
+
+    /// Run one kind of observer callback outside the observer lock.
+    fn report(
+        &self,
+        count: usize,
+        callback: impl Fn(&AudioObserver) -> &Arc<dyn Fn(usize) + Send + Sync>,
+    ) {
         let callbacks: Vec<_> = self
             .observers
             .lock()
             .unwrap_or_else(std::sync::PoisonError::into_inner)
             .iter()
-            .map(|observer| Arc::clone(&observer.on_write))
+            .map(|observer| Arc::clone(callback(observer)))
             .collect();
         for callback in &callbacks {
             callback(count);
         }
-        Ok(count)
+    }
+
+    fn apply_limits(&self, format: &MediaFormat) -> Result<(), crate::Error> {
+        let frame_bytes = format.frame_bytes()?;
+        if let Some(limit) = self.inbound_limit {
+            let frames = limit.frames(format);
+            self.inbound.limit(
+                frames.saturating_mul(frame_bytes),
+                frame_bytes,
+                limit.overflow,
+            );
+            self.timed.limit(frames, limit.overflow);
+        }
+        if let Some(limit) = self.outbound_limit {
+            let frames = limit.frames(format);
+            self.outbound.limit(
+                frames.saturating_mul(frame_bytes),
+                frame_bytes,
+                limit.overflow,
+            );
+        }
+        Ok(())
+    }
+
+    /// Report the audio dropped by capped buffers.
+    #[must_use]
+    pub fn buffer_stats(&self) -> AudioBufferStats {
+        AudioBufferStats {
+            inbound_dropped: self.inbound.dropped(),
+            outbound_dropped: self.outbound.dropped(),
+            timed_dropped: self.timed.dropped(),
+        }
     }
 
     /// Register byte-count callbacks for subsequent application I/O.
@@ -121,6 +232,23 @@ impl AudioStream {
             .push(observer);
     }
 
+    /// Subscribe to copies of inbound frames as the media pump buffers them and outbound frames
+    /// as they are sent, including inserted silence.
+    ///
+    /// Inbound frames are copied before the application reads them, so a tap also sees frames
+    /// that a capped inbound buffer then drops or [`AudioStream::clear_read_buffer`] discards.
+    ///
+    /// Up to `capacity` frames wait for the subscriber; later ones are dropped without stalling
+    /// the session. The tap ends when the stream closes; one taken after that is already ended.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `capacity` is zero.
+    #[must_use]
+    pub fn tap(&self, capacity: usize) -> AudioTap {
+        self.taps.subscribe(capacity)
+    }
+
     /// Remove every currently buffered inbound byte without affecting waiters.
     #[must_use]
     pub fn clear_read_buffer(&self) -> usize {
@@ -141,7 +269,66 @@ impl AudioStream {
                 crate::Error::InvalidMediaFormat("audio format is not negotiated".to_owned())
             })?
             .frame_bytes()?;
-        self.outbound.read_exact_or_drop(size).await
+        let frame = self.outbound.read_exact_or_drop(size).await?;
+        self.played
+            .fetch_add(byte_count(frame.len()), Ordering::Relaxed);
+        Ok(frame)
+    }
+
+    /// Take one outbound packetization frame if a complete one is already buffered.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::Closed`] after closure or a format error before negotiation.
+    pub(crate) fn try_read_outbound_frame(&self) -> Result<Option<Vec<u8>>, crate::Error> {
+        let size = self
+            .format()
+            .ok_or_else(|| {
+                crate::Error::InvalidMediaFormat("audio format is not negotiated".to_owned())
+            })?
+            .frame_bytes()?;
+        let frame = self.outbound.try_read_exact_or_drop(size)?;
+        if let Some(frame) = &frame {
+            self.played
+                .fetch_add(byte_count(frame.len()), Ordering::Relaxed);
+        }
+        Ok(frame)
+    }
+
+    /// Count outbound bytes written by the application but not yet sent.
+    #[must_use]
+    pub fn outbound_buffered(&self) -> usize {
+        self.outbound.len()
+    }
+
+    /// Remove every outbound byte the media pump has not taken yet, for example on barge-in.
+    #[must_use]
+    pub fn clear_write_buffer(&self) -> usize {
+        let cleared = self.outbound.clear();
+        self.cleared
+            .fetch_add(byte_count(cleared), Ordering::Relaxed);
+        cleared
+    }
+
+    /// Report outbound bytes written, played by the media pump, queued, cleared, and dropped.
+    #[must_use]
+    pub fn playback_stats(&self) -> PlaybackStats {
+        PlaybackStats {
+            written: self.written.load(Ordering::Relaxed),
+            played: self.played.load(Ordering::Relaxed),
+            queued: byte_count(self.outbound.len()),
+            cleared: self.cleared.load(Ordering::Relaxed),
+            dropped: self.outbound.dropped(),
+        }
+    }
+
+    /// Return how much application audio the media pump has taken, as playback time.
+    ///
+    /// Inserted silence does not advance the position. Returns `None` before negotiation.
+    #[must_use]
+    pub fn playback_position(&self) -> Option<Duration> {
+        let format = self.format()?;
+        duration_of(&format, self.played.load(Ordering::Relaxed)).ok()
     }
 
     /// Admit one inbound timed transport frame to both byte and frame views.
@@ -151,9 +338,20 @@ impl AudioStream {
     /// Returns [`crate::Error::Closed`] after shutdown.
     pub async fn push_inbound_frame(&self, frame: MediaFrame) -> Result<(), crate::Error> {
         self.inbound.write_all(&frame.data).await?;
+        self.taps
+            .publish(Direction::Inbound, &frame.data, frame.pts);
         self.timed.push(frame)
     }
 
+    /// Copy one frame leaving for the peer to every tap, timed by its place in the sent stream.
+    pub(crate) fn tap_outbound_frame(&self, data: &[u8]) {
+        let index = self.sent.fetch_add(1, Ordering::Relaxed);
+        let pts = self
+            .format()
+            .map(|format| format.ptime.saturating_mul(index));
+        self.taps.publish(Direction::Outbound, data, pts);
+    }
+
     /// Observe the next inbound media frame with its transport PTS.
     ///
     /// # Errors
@@ -163,16 +361,20 @@ impl AudioStream {
         self.timed.pop().await
     }
 
-    /// Close both byte directions and the timed observer idempotently.
+    /// Close both byte directions, the timed observer, and every tap idempotently.
     pub fn close(&self) {
         self.inbound.close();
         self.outbound.close();
         self.timed.close();
+        self.taps.close();
     }
 }
 
+fn byte_count(count: usize) -> u64 {
+    u64::try_from(count).unwrap_or(u64::MAX)
+}
+
 struct ByteBuffer {
-    capacity: usize,
     state: Mutex<ByteState>,
     readable: Notify,
     writable: Notify,
@@ -180,15 +382,23 @@ struct ByteBuffer {
 
 struct ByteState {
     bytes: VecDeque<u8>,
+    capacity: usize,
+    overflow: Overflow,
+    /// Granularity of dropped audio, one packetization frame once limited.
+    unit: usize,
+    dropped: u64,
     closed: bool,
 }
 
 impl ByteBuffer {
     fn new(capacity: usize) -> Self {
         Self {
-            capacity,
             state: Mutex::new(ByteState {
                 bytes: VecDeque::with_capacity(capacity),
+                capacity,
+                overflow: Overflow::Block,
+                unit: 1,
+                dropped: 0,
                 closed: false,
             }),
             readable: Notify::new(),
@@ -200,30 +410,77 @@ impl ByteBuffer {
         let mut written = 0;
         while written < input.len() {
             let notified = self.writable.notified();
-            {
-                let mut state = self.state.lock().unwrap();
-                if state.closed {
-                    return if written == 0 {
-                        Err(crate::Error::Closed)
-                    } else {
-                        Ok(written)
-                    };
-                }
-                let available = self.capacity - state.bytes.len();
-                if available > 0 {
-                    let count = available.min(input.len() - written);
-                    state.bytes.extend(&input[written..written + count]);
-                    written += count;
-                    drop(state);
-                    self.readable.notify_waiters();
-                    continue;
-                }
+            match self.try_write(&input[written..]) {
+                Ok(Some(count)) => written += count,
+                Ok(None) => notified.await,
+                Err(error) if written == 0 => return Err(error),
+                Err(_) => break,
             }
-            notified.await;
         }
         Ok(written)
     }
 
+    /// Append what fits without waiting, or `None` when the buffer is full.
+    ///
+    /// Dropping policies never report a full buffer: the returned count includes audio they
+    /// discarded.
+    fn try_write(&self, input: &[u8]) -> Result<Option<usize>, crate::Error> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Err(crate::Error::Closed);
+        }
+        if input.is_empty() {
+            return Ok(Some(0));
+        }
+        let available = state.capacity - state.bytes.len();
+        let (count, consumed) = match state.overflow {
+            Overflow::Block if available == 0 => return Ok(None),
+            Overflow::Block => (available.min(input.len()), available.min(input.len())),
+            Overflow::DropNewest if input.len() <= available => (input.len(), input.len()),
+            Overflow::DropNewest => (available - available % state.unit, input.len()),
+            Overflow::DropOldest => {
+                let count = input.len().min(state.capacity);
+                let excess = count.saturating_sub(available);
+                let discard = excess
+                    .div_ceil(state.unit)
+                    .saturating_mul(state.unit)
+                    .min(state.bytes.len());
+                state.bytes.drain(..discard);
+                state.dropped += u64::try_from(discard).unwrap_or(u64::MAX);
+                (count, count)
+            }
+        };
+        state.bytes.extend(&input[..count]);
+        state.dropped += u64::try_from(consumed - count).unwrap_or(u64::MAX);
+        drop(state);
+        self.readable.notify_waiters();
+        Ok(Some(consumed))
+    }
+
+    /// Lower the capacity and select the overflow policy.
+    fn limit(&self, capacity: usize, unit: usize, overflow: Overflow) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = state.capacity.min(capacity.max(unit));
+        state.unit = unit;
+        state.overflow = overflow;
+    }
+
+    fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
+    /// Wait until a write could make progress or would fail.
+    async fn writable(&self) {
+        let notified = self.writable.notified();
+        {
+            let state = self.state.lock().unwrap();
+            if state.closed || state.bytes.len() < state.capacity {
+                return;
+            }
+        }
+        notified.await;
+    }
+
     async fn write_all(&self, input: &[u8]) -> Result<(), crate::Error> {
         let written = self.write(input).await?;
         if written == input.len() {
@@ -234,30 +491,48 @@ impl ByteBuffer {
     }
 
     async fn read(&self, output: &mut [u8]) -> Result<usize, crate::Error> {
-        if output.is_empty() {
-            return Ok(0);
-        }
         loop {
             let notified = self.readable.notified();
-            {
-                let mut state = self.state.lock().unwrap();
-                if !state.bytes.is_empty() {
-                    let count = output.len().min(state.bytes.len());
-                    for byte in output.iter_mut().take(count) {
-                        *byte = state.bytes.pop_front().unwrap();
-                    }
-                    drop(state);
-                    self.writable.notify_waiters();
-                    return Ok(count);
-                }
-                if state.closed {
-                    return Err(crate::Error::Closed);
-                }
+            if let Some(count) = self.try_read(output)? {
+                return Ok(count);
             }
             notified.await;
         }
     }
 
+    /// Take buffered bytes without waiting, or `None` when none are buffered yet.
+    fn try_read(&self, output: &mut [u8]) -> Result<Option<usize>, crate::Error> {
+        if output.is_empty() {
+            return Ok(Some(0));
+        }
+        let mut state = self.state.lock().unwrap();
+        if !state.bytes.is_empty() {
+            let count = output.len().min(state.bytes.len());
+            for byte in output.iter_mut().take(count) {
+                *byte = state.bytes.pop_front().unwrap();
+            }
+            drop(state);
+            self.writable.notify_waiters();
+            return Ok(Some(count));
+        }
+        if state.closed {
+            return Err(crate::Error::Closed);
+        }
+        Ok(None)
+    }
+
+    /// Wait until a read could make progress or would fail.
+    async fn readable(&self) {
+        let notified = self.readable.notified();
+        {
+            let state = self.state.lock().unwrap();
+            if state.closed || !state.bytes.is_empty() {
+                return;
+            }
+        }
+        notified.await;
+    }
+
     async fn read_exact_or_drop(&self, size: usize) -> Result<Vec<u8>, crate::Error> {
         loop {
             let notified = self.readable.notified();
@@ -278,6 +553,25 @@ impl ByteBuffer {
         }
     }
 
+    fn try_read_exact_or_drop(&self, size: usize) -> Result<Option<Vec<u8>>, crate::Error> {
+        let mut state = self.state.lock().unwrap();
+        if state.bytes.len() >= size {
+            let frame = state.bytes.drain(..size).collect();
+            drop(state);
+            self.writable.notify_waiters();
+            return Ok(Some(frame));
+        }
+        if state.closed {
+            state.bytes.clear();
+            return Err(crate::Error::Closed);
+        }
+        Ok(None)
+    }
+
+    fn len(&self) -> usize {
+        self.state.lock().unwrap().bytes.len()
+    }
+
     fn clear(&self) -> usize {
         let mut state = self.state.lock().unwrap();
         let cleared = state.bytes.len();
@@ -301,6 +595,9 @@ struct FrameBuffer {
 
 struct FrameState {
     frames: VecDeque<MediaFrame>,
+    limit: Option<usize>,
+    overflow: Overflow,
+    dropped: u64,
     closed: bool,
 }
 
@@ -309,6 +606,9 @@ impl FrameBuffer {
         Self {
             state: Mutex::new(FrameState {
                 frames: VecDeque::new(),
+                limit: None,
+                overflow: Overflow::Block,
+                dropped: 0,
                 closed: false,
             }),
             readable: Notify::new(),
@@ -320,28 +620,62 @@ impl FrameBuffer {
         if state.closed {
             return Err(crate::Error::Closed);
         }
+        if state.limit.is_some_and(|limit| state.frames.len() >= limit) {
+            state.dropped += 1;
+            if state.overflow == Overflow::DropNewest {
+                return Ok(());
+            }
+            state.frames.pop_front();
+        }
         state.frames.push_back(frame);
         drop(state);
         self.readable.notify_one();
         Ok(())
     }
 
+    fn limit(&self, frames: usize, overflow: Overflow) {
+        let mut state = self.state.lock().unwrap();
+        state.limit = Some(frames);
+        state.overflow = overflow;
+    }
+
+    fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
     async fn pop(&self) -> Result<MediaFrame, crate::Error> {
         loop {
             let notified = self.readable.notified();
-            {
-                let mut state = self.state.lock().unwrap();
-                if let Some(frame) = state.frames.pop_front() {
-                    return Ok(frame);
-                }
-                if state.closed {
-                    return Err(crate::Error::Closed);
-                }
+            if let Some(frame) = self.try_pop()? {
+                return Ok(frame);
             }
             notified.await;
         }
     }
 
+    fn try_pop(&self) -> Result<Option<MediaFrame>, crate::Error> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(frame) = state.frames.pop_front() {
+            return Ok(Some(frame));
+        }
+        if state.closed {
+            return Err(crate::Error::Closed);
+        }
+        Ok(None)
+    }
+
+    /// Wait until a pop could make progress or would fail.
+    async fn readable(&self) {
+        let notified = self.readable.notified();
+        {
+            let state = self.state.lock().unwrap();
+            if state.closed || !state.frames.is_empty() {
+                return;
+            }
+        }
+        notified.await;
+    }
+
     fn close(&self) {
         self.state.lock().unwrap().closed = true;
         self.readable.notify_waiters();
@@ -350,8 +684,6 @@ impl FrameBuffer {
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
-
     use super::*;
 
     fn format() -> MediaFormat {
@@ -409,6 +741,50 @@ mod tests {
         assert_eq!(stream.read_timed_frame().await.unwrap(), second);
     }
 
+    #[tokio::test]
+    async fn limits_drop_whole_frames_by_policy_and_count_them() {
+        let limit = |overflow| BufferLimit {
+            max_delay: Duration::from_millis(30),
+            overflow,
+        };
+        let stream = AudioStream::new(1_024).with_limits(
+            Some(limit(Overflow::DropOldest)),
+            Some(limit(Overflow::DropNewest)),
+        );
+        stream.set_format(format()).unwrap();
+
+        // 30 ms rounds up to two 320-byte frames in each direction.
+        for index in 1..=3 {
+            stream
+                .push_inbound_frame(MediaFrame::untimed(vec![index; 320]))
+                .await
+                .unwrap();
+        }
+        let mut inbound = [0; 640];
+        assert_eq!(stream.read(&mut inbound).await.unwrap(), 640);
+        assert_eq!(inbound[..320], [2; 320]);
+        assert_eq!(inbound[320..], [3; 320]);
+        assert_eq!(stream.read_timed_frame().await.unwrap().data, [2; 320]);
+
+        // A write that does not fit in whole frames is dropped without blocking.
+        assert_eq!(stream.write(&[4; 480]).await.unwrap(), 480);
+        assert_eq!(stream.write(&[5; 480]).await.unwrap(), 480);
+        assert_eq!(stream.read_outbound_frame().await.unwrap(), [4; 320]);
+        assert_eq!(stream.write(&[6; 480]).await.unwrap(), 480);
+        let frame = stream.read_outbound_frame().await.unwrap();
+        assert_eq!(frame[..160], [4; 160]);
+        assert_eq!(frame[160..], [6; 160]);
+
+        assert_eq!(
+            stream.buffer_stats(),
+            AudioBufferStats {
+                inbound_dropped: 320,
+                outbound_dropped: 480,
+                timed_dropped: 1,
+            }
+        );
+    }
+
     #[tokio::test]
     async fn clearing_does_not_poison_a_blocked_reader_and_close_unblocks_it() {
         let stream = Arc::new(AudioStream::new(32));
@@ -440,4 +816,51 @@ mod tests {
             Err(crate::Error::Closed)
         ));
     }
+
+    #[tokio::test]
+    async fn playback_counts_only_audio_the_pump_takes() {
+        let stream = AudioStream::new(4_096);
+        assert_eq!(stream.playback_position(), None);
+        stream.set_format(format()).unwrap();
+        stream.write(&[1; 1_000]).await.unwrap();
+        stream.read_outbound_frame().await.unwrap();
+        assert!(stream.try_read_outbound_frame().unwrap().is_some());
+        assert_eq!(stream.playback_position(), Some(Duration::from_millis(40)));
+        assert_eq!(stream.clear_write_buffer(), 360);
+        assert_eq!(
+            stream.playback_stats(),
+            PlaybackStats {
+                written: 1_000,
+                played: 640,
+                queued: 0,
+                cleared: 360,
+                dropped: 0,
+            }
+        );
+        assert!(stream.try_read_outbound_frame().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn playback_stats_account_for_audio_a_capped_buffer_drops() {
+        let stream = AudioStream::new(4_096).with_limits(
+            None,
+            Some(BufferLimit {
+                max_delay: Duration::from_millis(40),
+                overflow: Overflow::DropNewest,
+            }),
+        );
+        stream.set_format(format()).unwrap();
+        assert_eq!(stream.write(&[1; 960]).await.unwrap(), 960);
+        stream.read_outbound_frame().await.unwrap();
+        assert_eq!(
+            stream.playback_stats(),
+            PlaybackStats {
+                written: 960,
+                played: 320,
+                queued: 320,
+                cleared: 0,
+                dropped: 320,
+            }
+        );
+    }
 }
@@ -0,0 +1,130 @@
+//! Background detectors over a session's inbound audio, shared by the VAD and DTMF monitors.
+
+use std::future::Future;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use super::{AudioStream, Direction, TappedFrame};
+
+/// Frames a detector's tap may queue before further frames are dropped.
+const TAP_CAPACITY: usize = 64;
+
+/// Events a monitor may queue for a slow receiver before further ones are dropped.
+const EVENT_CAPACITY: usize = 32;
+
+/// A detector that [`Monitor`] feeds with inbound frames.
+pub(super) trait Detect: Send + Sync + 'static {
+    type Event: Send + 'static;
+
+    /// Return the events one inbound frame completes.
+    fn detect(&mut self, frame: &TappedFrame) -> Vec<Self::Event>;
+
+    /// Return an event still pending once the stream closes.
+    fn finish(&mut self) -> Option<Self::Event> {
+        None
+    }
+
+    /// Act on an event before it is queued for the receiver.
+    fn observe(&self, _event: &Self::Event) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+}
+
+/// Runs a detector over a stream's inbound frames until the stream closes or the monitor drops.
+pub(super) struct Monitor<E> {
+    events: mpsc::Receiver<E>,
+    task: JoinHandle<()>,
+}
+
+impl<E: Send + 'static> Monitor<E> {
+    pub(super) fn start<D: Detect<Event = E>>(audio: &AudioStream, mut detector: D) -> Self {
+        let mut tap = audio.tap(TAP_CAPACITY);
+        let (sender, events) = mpsc::channel(EVENT_CAPACITY);
+        let task = tokio::spawn(async move {
+            while let Some(frame) = tap.recv().await {
+                if frame.direction != Direction::Inbound {
+                    continue;
+                }
+                for event in detector.detect(&frame) {
+                    detector.observe(&event).await;
+                    // Events are advisory: a full or dropped receiver must not stop detection.
+                    let _ = sender.try_send(event);
+                }
+            }
+            if let Some(event) = detector.finish() {
+                detector.observe(&event).await;
+                let _ = sender.try_send(event);
+            }
+        });
+        Self { events, task }
+    }
+
+    pub(super) async fn recv(&mut self) -> Option<E> {
+        self.events.recv().await
+    }
+}
+
+impl<E> Drop for Monitor<E> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{MediaFormat, MediaFrame};
+
+    /// Reports each inbound frame's length and notes when the monitor task drops it.
+    struct Lengths(Arc<AtomicBool>);
+
+    impl Detect for Lengths {
+        type Event = usize;
+
+        fn detect(&mut self, frame: &TappedFrame) -> Vec<usize> {
+            vec![frame.data.len()]
+        }
+    }
+
+    impl Drop for Lengths {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Release);
+        }
+    }
+
+    #[tokio::test]
+    async fn detects_inbound_frames_and_stops_when_dropped() {
+        let audio = AudioStream::new(4_096);
+        audio
+            .set_format(MediaFormat {
+                encoding: "L16".to_owned(),
+                sample_rate: 8_000,
+                bit_depth: 16,
+                channels: 1,
+                ptime: Duration::from_millis(20),
+            })
+            .unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let mut monitor = Monitor::start(&audio, Lengths(Arc::clone(&dropped)));
+        audio.tap_outbound_frame(&[0; 320]);
+        audio
+            .push_inbound_frame(MediaFrame::untimed(vec![0; 160]))
+            .await
+            .unwrap();
+        assert_eq!(monitor.recv().await, Some(160));
+
+        drop(monitor);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !dropped.load(Ordering::Acquire) {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+}
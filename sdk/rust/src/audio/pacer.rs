@@ -0,0 +1,162 @@
+//! Optional real-time release of outbound frames.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+
+use super::AudioStream;
+use crate::MediaFormat;
+
+/// What the pacer sends when a frame is due and the application has not written one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Underrun {
+    /// Send a silent frame, keeping the media stream continuous.
+    #[default]
+    Silence,
+    /// Send nothing for that interval.
+    Skip,
+}
+
+/// Outbound pacing configuration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PacerConfig {
+    pub underrun: Underrun,
+}
+
+/// Pacer counters since audio was bound.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PacerStats {
+    /// Application frames released to the transport.
+    pub frames: u64,
+    /// Intervals after the first frame in which no complete frame was buffered.
+    pub underruns: u64,
+    /// Audio written by the application but not yet released.
+    pub ahead: Duration,
+}
+
+/// Counters shared between the outbound pump and [`crate::Session::pacer_stats`].
+pub(crate) struct PacerCounters {
+    frames: AtomicU64,
+    underruns: AtomicU64,
+    frame_bytes: usize,
+    ptime: Duration,
+}
+
+impl PacerCounters {
+    pub(crate) fn stats(&self, audio: &AudioStream) -> PacerStats {
+        let buffered = u128::try_from(audio.outbound_buffered()).unwrap_or(u128::MAX);
+        let frame_bytes = u128::try_from(self.frame_bytes.max(1)).unwrap_or(u128::MAX);
+        let ahead = self.ptime.as_nanos().saturating_mul(buffered) / frame_bytes;
+        PacerStats {
+            frames: self.frames.load(Ordering::Relaxed),
+            underruns: self.underruns.load(Ordering::Relaxed),
+            ahead: Duration::from_nanos(u64::try_from(ahead).unwrap_or(u64::MAX)),
+        }
+    }
+}
+
+/// Releases one outbound frame per packetization interval on the runtime's monotonic clock.
+///
+/// The clock starts with the first application frame; missed ticks are skipped rather than
+/// sent as a burst.
+pub(crate) struct Pacer {
+    underrun: Underrun,
+    silence: Vec<u8>,
+    clock: Option<Interval>,
+    counters: Arc<PacerCounters>,
+}
+
+impl Pacer {
+    pub(crate) fn new(config: PacerConfig, format: &MediaFormat) -> Result<Self, crate::Error> {
+        let frame_bytes = format.frame_bytes()?;
+        Ok(Self {
+            underrun: config.underrun,
+            silence: format.silence()?,
+            clock: None,
+            counters: Arc::new(PacerCounters {
+                frames: AtomicU64::new(0),
+                underruns: AtomicU64::new(0),
+                frame_bytes,
+                ptime: format.ptime,
+            }),
+        })
+    }
+
+    pub(crate) fn counters(&self) -> Arc<PacerCounters> {
+        Arc::clone(&self.counters)
+    }
+
+    /// Wait for the next interval and return its frame, or `None` to skip it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::Closed`] after the audio stream closes.
+    pub(crate) async fn next_frame(
+        &mut self,
+        audio: &AudioStream,
+    ) -> Result<Option<Vec<u8>>, crate::Error> {
+        let Some(clock) = self.clock.as_mut() else {
+            let frame = audio.read_outbound_frame().await?;
+            let ptime = self.counters.ptime;
+            let mut clock = tokio::time::interval_at(Instant::now() + ptime, ptime);
+            clock.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            self.clock = Some(clock);
+            self.counters.frames.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(frame));
+        };
+        clock.tick().await;
+        if let Some(frame) = audio.try_read_outbound_frame()? {
+            self.counters.frames.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(frame));
+        }
+        self.counters.underruns.fetch_add(1, Ordering::Relaxed);
+        Ok(match self.underrun {
+            Underrun::Silence => Some(self.silence.clone()),
+            Underrun::Skip => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format() -> MediaFormat {
+        MediaFormat {
+            encoding: "L16".to_owned(),
+            sample_rate: 8_000,
+            bit_depth: 16,
+            channels: 1,
+            ptime: Duration::from_millis(20),
+        }
+    }
+
+    #[tokio::test]
+    async fn skips_underruns_and_reports_buffered_audio_as_ahead() {
+        let audio = AudioStream::new(64 * 1024);
+        audio.set_format(format()).unwrap();
+        let config = PacerConfig {
+            underrun: Underrun::Skip,
+        };
+        let mut pacer = Pacer::new(config, &format()).unwrap();
+        let counters = pacer.counters();
+
+        // Two and a half frames: the half frame counts towards `ahead` but is never sent.
+        audio.write(&[1; 800]).await.unwrap();
+        assert_eq!(counters.stats(&audio).ahead, Duration::from_millis(50));
+        assert_eq!(pacer.next_frame(&audio).await.unwrap(), Some(vec![1; 320]));
+        assert_eq!(pacer.next_frame(&audio).await.unwrap(), Some(vec![1; 320]));
+        assert_eq!(pacer.next_frame(&audio).await.unwrap(), None);
+
+        let stats = counters.stats(&audio);
+        assert_eq!((stats.frames, stats.underruns), (2, 1));
+        assert_eq!(stats.ahead, Duration::from_millis(10));
+        audio.close();
+        assert!(matches!(
+            pacer.next_frame(&audio).await,
+            Err(crate::Error::Closed)
+        ));
+    }
+}
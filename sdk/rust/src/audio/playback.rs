@@ -0,0 +1,32 @@
+//! Outbound playback accounting: what the media pump has taken versus what is still queued.
+
+use std::time::Duration;
+
+use crate::MediaFormat;
+
+/// Outbound byte counters since the stream was created.
+///
+/// Bytes accepted by any outbound writer are eventually played, cleared, dropped by a capped
+/// buffer, or still queued.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlaybackStats {
+    /// Bytes the application wrote, including any a capped buffer dropped.
+    pub written: u64,
+    /// Bytes the media pump took for sending.
+    pub played: u64,
+    /// Bytes waiting for the media pump.
+    pub queued: u64,
+    /// Bytes discarded by [`super::AudioStream::clear_write_buffer`].
+    pub cleared: u64,
+    /// Bytes a capped buffer discarded instead of queueing, or evicted from the queue.
+    pub dropped: u64,
+}
+
+/// Return how long `bytes` of `format` audio play for.
+pub(crate) fn duration_of(format: &MediaFormat, bytes: u64) -> Result<Duration, crate::Error> {
+    let frame_bytes = u128::try_from(format.frame_bytes()?).unwrap_or(u128::MAX);
+    let nanos = u128::from(bytes).saturating_mul(format.ptime.as_nanos()) / frame_bytes;
+    Ok(Duration::from_nanos(
+        u64::try_from(nanos).unwrap_or(u64::MAX),
+    ))
+}
@@ -0,0 +1,688 @@
+//! Time-aligned WAV recording of a session's inbound and outbound audio.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use super::{AudioStream, AudioTap, Direction, TappedFrame};
+use crate::MediaFormat;
+
+/// Frames a recorder's tap may queue before further frames are dropped.
+const TAP_CAPACITY: usize = 256;
+
+/// Packetization intervals a direction may lag the wall clock before silence is inserted.
+const SLACK_FRAMES: u32 = 3;
+
+/// Where and how a recording is written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// One stereo file with the caller (inbound) left and the agent (outbound) right.
+    Stereo(PathBuf),
+    /// One mono file per direction, each the length of the whole recording.
+    DualMono { caller: PathBuf, agent: PathBuf },
+}
+
+/// Recording configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecorderConfig {
+    pub layout: Layout,
+    /// Written to each file's `INFO/IKEY` chunk when non-empty.
+    pub keywords: Vec<String>,
+}
+
+impl RecorderConfig {
+    #[must_use]
+    pub fn new(layout: Layout) -> Self {
+        Self {
+            layout,
+            keywords: Vec::new(),
+        }
+    }
+}
+
+/// Totals for a finished recording.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecordingSummary {
+    /// Length of the recorded audio, excluding paused time.
+    pub duration: Duration,
+    /// Frames lost because the recorder fell behind the session.
+    pub dropped: u64,
+}
+
+/// Records a session's audio until stopped or until the audio stream closes.
+///
+/// Both directions share one timeline: a direction that was idle is filled with silence so the
+/// tracks stay aligned. Dropping the recorder without [`Recorder::stop`] still finalizes the
+/// files in the background.
+pub struct Recorder {
+    pause: Arc<Mutex<PauseClock>>,
+    stop: Option<oneshot::Sender<()>>,
+    task: JoinHandle<Result<RecordingSummary, crate::Error>>,
+}
+
+impl Recorder {
+    /// Create the configured files and start recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::AudioUnavailable`] before a format is negotiated, an
+    /// invalid-format error for multichannel audio, or a recording error when a file already
+    /// exists or cannot be created.
+    pub fn start(audio: &AudioStream, config: &RecorderConfig) -> Result<Self, crate::Error> {
+        let format = audio.format().ok_or(crate::Error::AudioUnavailable)?;
+        WavFormat::of(&format, 1)
+            .map_err(|error| crate::Error::InvalidMediaFormat(error.to_string()))?;
+        let tracks = match &config.layout {
+            Layout::Stereo(path) => {
+                Tracks::stereo(create(path)?, &format, &config.keywords).map_err(recording_error)?
+            }
+            Layout::DualMono { caller, agent } => {
+                Tracks::dual_mono([create(caller)?, create(agent)?], &format, &config.keywords)
+                    .map_err(recording_error)?
+            }
+        };
+        let pause = Arc::new(Mutex::new(PauseClock::new(SystemTime::now())));
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(record(
+            audio.tap(TAP_CAPACITY),
+            tracks,
+            Arc::clone(&pause),
+            stopped,
+        ));
+        Ok(Self {
+            pause,
+            stop: Some(stop),
+            task,
+        })
+    }
+
+    /// Stop writing audio; the paused interval is left out of the recording.
+    pub fn pause(&self) {
+        lock(&self.pause).pause(SystemTime::now());
+    }
+
+    /// Continue writing audio after [`Recorder::pause`].
+    pub fn resume(&self) {
+        lock(&self.pause).resume(SystemTime::now());
+    }
+
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        lock(&self.pause).paused_since.is_some()
+    }
+
+    /// Write the queued audio, finalize the WAV headers, and close the files.
+    ///
+    /// # Errors
+    ///
+    /// Returns a recording error when writing failed at any point.
+    pub async fn stop(mut self) -> Result<RecordingSummary, crate::Error> {
+        if let Some(stop) = self.stop.take() {
+            // The recorder may already have finished because the audio stream closed.
+            let _ = stop.send(());
+        }
+        match (&mut self.task).await {
+            Ok(result) => result,
+            Err(error) => Err(crate::Error::Recording(format!("recorder task: {error}"))),
+        }
+    }
+}
+
+/// A frame placed on the recording timeline, on its way to the file writer.
+type Placed = (Direction, Vec<u8>, Duration);
+
+async fn record(
+    mut tap: AudioTap,
+    tracks: Tracks<BufWriter<File>>,
+    pause: Arc<Mutex<PauseClock>>,
+    mut stopped: oneshot::Receiver<()>,
+) -> Result<RecordingSummary, crate::Error> {
+    // File I/O blocks, so a blocking thread writes while this task keeps draining the tap.
+    let (frames, placed) = mpsc::channel(TAP_CAPACITY);
+    let writer = tokio::task::spawn_blocking(move || write(tracks, placed));
+    loop {
+        tokio::select! {
+            frame = tap.recv() => {
+                let Some(frame) = frame else {
+                    break;
+                };
+                if !place(&frames, &pause, frame).await {
+                    break;
+                }
+            }
+            _ = &mut stopped => {
+                while let Some(frame) = tap.try_recv() {
+                    if !place(&frames, &pause, frame).await {
+                        break;
+                    }
+                }
+                break;
+            }
+        }
+    }
+    drop(frames);
+    let duration = writer
+        .await
+        .map_err(|error| crate::Error::Recording(format!("recorder writer: {error}")))??;
+    Ok(RecordingSummary {
+        duration,
+        dropped: tap.dropped(),
+    })
+}
+
+/// Send a frame captured outside a pause to the writer; `false` once the writer has stopped.
+async fn place(
+    frames: &mpsc::Sender<Placed>,
+    pause: &Mutex<PauseClock>,
+    frame: TappedFrame,
+) -> bool {
+    let Some(offset) = lock(pause).offset(frame.captured_at) else {
+        return true;
+    };
+    frames
+        .send((frame.direction, frame.data, offset))
+        .await
+        .is_ok()
+}
+
+/// Write placed frames until the sender closes, then finalize the files.
+fn write(
+    mut tracks: Tracks<BufWriter<File>>,
+    mut placed: mpsc::Receiver<Placed>,
+) -> Result<Duration, crate::Error> {
+    while let Some((direction, data, offset)) = placed.blocking_recv() {
+        tracks
+            .push(direction, &data, offset)
+            .map_err(recording_error)?;
+    }
+    tracks.finish().map_err(recording_error)
+}
+
+fn create(path: &PathBuf) -> Result<BufWriter<File>, crate::Error> {
+    File::create_new(path)
+        .map(BufWriter::new)
+        .map_err(|error| crate::Error::Recording(format!("{}: {error}", path.display())))
+}
+
+fn recording_error(error: impl std::fmt::Display) -> crate::Error {
+    crate::Error::Recording(error.to_string())
+}
+
+fn lock(pause: &Mutex<PauseClock>) -> std::sync::MutexGuard<'_, PauseClock> {
+    pause
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Maps capture times onto the recording timeline with paused intervals removed.
+struct PauseClock {
+    started: SystemTime,
+    paused_since: Option<SystemTime>,
+    paused: Duration,
+}
+
+impl PauseClock {
+    fn new(started: SystemTime) -> Self {
+        Self {
+            started,
+            paused_since: None,
+            paused: Duration::ZERO,
+        }
+    }
+
+    fn pause(&mut self, now: SystemTime) {
+        self.paused_since.get_or_insert(now);
+    }
+
+    fn resume(&mut self, now: SystemTime) {
+        if let Some(since) = self.paused_since.take() {
+            self.paused += now.duration_since(since).unwrap_or_default();
+        }
+    }
+
+    /// Return the timeline offset of a frame, or `None` when it was captured while paused.
+    fn offset(&self, captured_at: SystemTime) -> Option<Duration> {
+        if self.paused_since.is_some_and(|since| captured_at >= since) {
+            return None;
+        }
+        let elapsed = captured_at.duration_since(self.started).unwrap_or_default();
+        Some(elapsed.saturating_sub(self.paused))
+    }
+}
+
+enum Output<W> {
+    Stereo(WavWriter<W>),
+    DualMono([WavWriter<W>; 2]),
+}
+
+/// Caller and agent tracks positioned on a shared sample timeline.
+struct Tracks<W> {
+    output: Output<W>,
+    sample_bytes: usize,
+    silence: Vec<u8>,
+    sample_rate: u32,
+    slack: u64,
+    /// Samples placed on each track so far, written or pending.
+    cursor: [u64; 2],
+    pending: [Vec<u8>; 2],
+}
+
+impl<W: Write + Seek> Tracks<W> {
+    fn stereo(writer: W, format: &MediaFormat, keywords: &[String]) -> io::Result<Self> {
+        let wav = WavFormat::of(format, 2)?;
+        Self::new(
+            Output::Stereo(WavWriter::new(writer, wav, keywords)?),
+            format,
+        )
+    }
+
+    fn dual_mono(writers: [W; 2], format: &MediaFormat, keywords: &[String]) -> io::Result<Self> {
+        let wav = WavFormat::of(format, 1)?;
+        let [caller, agent] = writers;
+        Self::new(
+            Output::DualMono([
+                WavWriter::new(caller, wav, keywords)?,
+                WavWriter::new(agent, wav, keywords)?,
+            ]),
+            format,
+        )
+    }
+
+    fn new(output: Output<W>, format: &MediaFormat) -> io::Result<Self> {
+        let sample_bytes = usize::from(format.bit_depth / 8);
+        let silence = format.silence().map_err(io::Error::other)?;
+        Ok(Self {
+            output,
+            sample_bytes,
+            silence: silence[..sample_bytes].to_vec(),
+            sample_rate: format.sample_rate,
+            slack: samples_in(format.ptime * SLACK_FRAMES, format.sample_rate),
+            cursor: [0; 2],
+            pending: [Vec::new(), Vec::new()],
+        })
+    }
+
+    /// Place a frame that finished arriving `offset` into the recording.
+    ///
+    /// Either track lagging the frame's time by more than the slack is filled with silence;
+    /// smaller lags are taken as arrival jitter and the frame follows its track directly.
+    fn push(&mut self, direction: Direction, data: &[u8], offset: Duration) -> io::Result<()> {
+        let own = track(direction);
+        let samples = u64::try_from(data.len() / self.sample_bytes).unwrap_or(u64::MAX);
+        let end = samples_in(offset, self.sample_rate);
+        let start = end.saturating_sub(samples);
+        if start > self.cursor[own] + self.slack {
+            self.pad(own, start);
+        }
+        let other = 1 - own;
+        if end > self.cursor[other] + self.slack {
+            self.pad(other, end - self.slack);
+        }
+        let whole = data.len() - data.len() % self.sample_bytes;
+        self.pending[own].extend_from_slice(&data[..whole]);
+        self.cursor[own] += samples;
+        self.flush()
+    }
+
+    fn pad(&mut self, index: usize, until: u64) {
+        let missing = until.saturating_sub(self.cursor[index]);
+        let count = usize::try_from(missing).unwrap_or(usize::MAX);
+        for _ in 0..count {
+            self.pending[index].extend_from_slice(&self.silence);
+        }
+        self.cursor[index] = self.cursor[index].max(until);
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.output {
+            Output::Stereo(writer) => {
+                let ready = self.pending[0].len().min(self.pending[1].len());
+                let ready = ready - ready % self.sample_bytes;
+                let [left, right] = &mut self.pending;
+                let mut interleaved = Vec::with_capacity(ready * 2);
+                for (left, right) in left[..ready]
+                    .chunks_exact(self.sample_bytes)
+                    .zip(right[..ready].chunks_exact(self.sample_bytes))
+                {
+                    interleaved.extend_from_slice(left);
+                    interleaved.extend_from_slice(right);
+                }
+                left.drain(..ready);
+                right.drain(..ready);
+                writer.write(&interleaved)
+            }
+            Output::DualMono(writers) => {
+                for (writer, pending) in writers.iter_mut().zip(&mut self.pending) {
+                    writer.write(pending)?;
+                    pending.clear();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Even out the tracks, finalize every header, and return the recorded duration.
+    fn finish(mut self) -> io::Result<Duration> {
+        let length = self.cursor[0].max(self.cursor[1]);
+        self.pad(0, length);
+        self.pad(1, length);
+        self.flush()?;
+        match self.output {
+            Output::Stereo(writer) => {
+                writer.finish()?;
+            }
+            Output::DualMono(writers) => {
+                for writer in writers {
+                    writer.finish()?;
+                }
+            }
+        }
+        Ok(Duration::from_nanos(
+            length.saturating_mul(1_000_000_000) / u64::from(self.sample_rate),
+        ))
+    }
+}
+
+fn track(direction: Direction) -> usize {
+    match direction {
+        Direction::Inbound => 0,
+        Direction::Outbound => 1,
+    }
+}
+
+fn samples_in(duration: Duration, sample_rate: u32) -> u64 {
+    let samples = duration.as_nanos() * u128::from(sample_rate) / 1_000_000_000;
+    u64::try_from(samples).unwrap_or(u64::MAX)
+}
+
+/// The `fmt ` chunk fields of one file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct WavFormat {
+    tag: u16,
+    channels: u16,
+    sample_rate: u32,
+    bit_depth: u16,
+}
+
+impl WavFormat {
+    fn of(format: &MediaFormat, channels: u16) -> io::Result<Self> {
+        if format.channels != 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "recording needs mono audio, got {} channels",
+                    format.channels
+                ),
+            ));
+        }
+        let tag = match format.encoding.as_str() {
+            "L16" => 1,
+            "PCMA" => 6,
+            "PCMU" => 7,
+            encoding => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("cannot record {encoding:?} audio"),
+                ));
+            }
+        };
+        Ok(Self {
+            tag,
+            channels,
+            sample_rate: format.sample_rate,
+            bit_depth: format.bit_depth,
+        })
+    }
+
+    fn block_align(self) -> u16 {
+        self.channels * self.bit_depth / 8
+    }
+}
+
+/// Streams a RIFF/WAVE file whose size fields are patched in by [`WavWriter::finish`].
+struct WavWriter<W> {
+    inner: W,
+    format: WavFormat,
+    /// Position of the `fact` sample count, present for the non-PCM G.711 tags.
+    fact: Option<u64>,
+    data_size: u64,
+    data_len: u64,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    fn new(mut inner: W, format: WavFormat, keywords: &[String]) -> io::Result<Self> {
+        let pcm = format.tag == 1;
+        inner.write_all(b"RIFF\0\0\0\0WAVE")?;
+        inner.write_all(b"fmt ")?;
+        inner.write_all(&(if pcm { 16_u32 } else { 18 }).to_le_bytes())?;
+        inner.write_all(&format.tag.to_le_bytes())?;
+        inner.write_all(&format.channels.to_le_bytes())?;
+        inner.write_all(&format.sample_rate.to_le_bytes())?;
+        let byte_rate = format.sample_rate * u32::from(format.block_align());
+        inner.write_all(&byte_rate.to_le_bytes())?;
+        inner.write_all(&format.block_align().to_le_bytes())?;
+        inner.write_all(&format.bit_depth.to_le_bytes())?;
+        let fact = if pcm {
+            None
+        } else {
+            inner.write_all(&0_u16.to_le_bytes())?;
+            inner.write_all(b"fact\x04\0\0\0")?;
+            let position = inner.stream_position()?;
+            inner.write_all(&0_u32.to_le_bytes())?;
+            Some(position)
+        };
+        if !keywords.is_empty() {
+            write_keywords(&mut inner, &keywords.join("; "))?;
+        }
+        inner.write_all(b"data")?;
+        let data_size = inner.stream_position()?;
+        inner.write_all(&0_u32.to_le_bytes())?;
+        Ok(Self {
+            inner,
+            format,
+            fact,
+            data_size,
+            data_len: 0,
+        })
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.inner.write_all(data)?;
+        self.data_len += u64::try_from(data.len()).unwrap_or(u64::MAX);
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        if self.data_len % 2 == 1 {
+            self.inner.write_all(&[0])?;
+        }
+        let end = self.inner.stream_position()?;
+        self.patch(4, end - 8)?;
+        self.patch(self.data_size, self.data_len)?;
+        if let Some(fact) = self.fact {
+            let frames = self.data_len / u64::from(self.format.block_align());
+            self.patch(fact, frames)?;
+        }
+        self.inner.seek(SeekFrom::Start(end))?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Write a size field, saturating at the format's 4 GiB limit.
+    fn patch(&mut self, position: u64, value: u64) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(position))?;
+        self.inner
+            .write_all(&u32::try_from(value).unwrap_or(u32::MAX).to_le_bytes())
+    }
+}
+
+fn write_keywords(inner: &mut impl Write, keywords: &str) -> io::Result<()> {
+    let mut text = keywords.as_bytes().to_vec();
+    text.push(0);
+    let text_size = text.len();
+    if text.len() % 2 == 1 {
+        text.push(0);
+    }
+    let size = |length: usize| {
+        u32::try_from(length)
+            .map(u32::to_le_bytes)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "keywords are too long"))
+    };
+    inner.write_all(b"LIST")?;
+    inner.write_all(&size(12 + text.len())?)?;
+    inner.write_all(b"INFOIKEY")?;
+    inner.write_all(&size(text_size)?)?;
+    inner.write_all(&text)
+}
+
+/// Read the samples of a mono WAV file whose encoding and rate match `format`.
+///
+/// Accepts the PCM and G.711 files [`Recorder`] writes.
+pub(crate) fn read_wav(path: &Path, format: &MediaFormat) -> Result<Vec<u8>, crate::Error> {
+    let bytes = std::fs::read(path).map_err(|error| {
+        crate::Error::Configuration(format!("cannot read {}: {error}", path.display()))
+    })?;
+    let malformed =
+        || crate::Error::InvalidMediaFormat(format!("{} is not a WAV file", path.display()));
+    let (Some(b"RIFF"), Some(b"WAVE")) = (bytes.get(..4), bytes.get(8..12)) else {
+        return Err(malformed());
+    };
+    let mut found = None;
+    let mut data = None;
+    let mut position = 12;
+    while let (Some(id), Some(size)) = (
+        bytes.get(position..position + 4),
+        bytes.get(position + 4..position + 8),
+    ) {
+        let size = usize::try_from(u32::from_le_bytes([size[0], size[1], size[2], size[3]]))
+            .unwrap_or(usize::MAX);
+        let start = position + 8;
+        let body = bytes
+            .get(start..start.saturating_add(size).min(bytes.len()))
+            .unwrap_or_default();
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let field = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+                found = Some(WavFormat {
+                    tag: field(0),
+                    channels: field(2),
+                    sample_rate: u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+                    bit_depth: field(14),
+                });
+            }
+            b"data" => data = Some(body.to_vec()),
+            _ => {}
+        }
+        position = start.saturating_add(size).saturating_add(size % 2);
+    }
+    let (Some(found), Some(data)) = (found, data) else {
+        return Err(malformed());
+    };
+    let expected = WavFormat::of(format, format.channels).ok();
+    if expected != Some(found) {
+        return Err(crate::Error::InvalidMediaFormat(format!(
+            "{} does not hold {}/{}/{} audio",
+            path.display(),
+            format.encoding,
+            format.sample_rate,
+            format.channels
+        )));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn format(encoding: &str, bit_depth: u16) -> MediaFormat {
+        MediaFormat {
+            encoding: encoding.to_owned(),
+            sample_rate: 8_000,
+            bit_depth,
+            channels: 1,
+            ptime: Duration::from_millis(20),
+        }
+    }
+
+    fn u32_at(bytes: &[u8], position: usize) -> u32 {
+        u32::from_le_bytes(bytes[position..position + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn stereo_tracks_stay_aligned_across_idle_directions() {
+        let mut tracks = Tracks::stereo(Cursor::new(Vec::new()), &format("L16", 16), &[]).unwrap();
+        let ms = Duration::from_millis;
+        // Caller speaks for two frames, the agent answers after a 100 ms silence.
+        tracks.push(Direction::Inbound, &[1; 320], ms(20)).unwrap();
+        tracks.push(Direction::Inbound, &[2; 320], ms(40)).unwrap();
+        tracks
+            .push(Direction::Outbound, &[3; 320], ms(160))
+            .unwrap();
+        // A frame arriving a little late still follows its track directly.
+        tracks
+            .push(Direction::Outbound, &[4; 320], ms(190))
+            .unwrap();
+        let Output::Stereo(writer) = &tracks.output else {
+            unreachable!()
+        };
+        assert_eq!(tracks.cursor, [1_040, 1_440]);
+        assert_eq!(writer.data_len, 4_160);
+        let duration = tracks.finish().unwrap();
+        assert_eq!(duration, ms(180));
+    }
+
+    #[test]
+    fn wav_headers_are_finalized_with_sizes_and_keywords() {
+        let mut tracks = Tracks::dual_mono(
+            [Cursor::new(Vec::new()), Cursor::new(Vec::new())],
+            &format("PCMU", 8),
+            &["support".to_owned()],
+        )
+        .unwrap();
+        tracks
+            .push(Direction::Inbound, &[0x10; 159], Duration::from_millis(20))
+            .unwrap();
+        let Output::DualMono([caller, agent]) = tracks.output else {
+            unreachable!()
+        };
+        let caller = caller.finish().unwrap().into_inner();
+        let agent = agent.finish().unwrap().into_inner();
+
+        assert_eq!(&caller[..4], b"RIFF");
+        assert_eq!(u32_at(&caller, 4) as usize, caller.len() - 8);
+        assert_eq!(&caller[12..16], b"fmt ");
+        assert_eq!(u32_at(&caller, 16), 18);
+        assert_eq!(u16::from_le_bytes([caller[20], caller[21]]), 7);
+        assert_eq!(&caller[38..42], b"fact");
+        assert_eq!(u32_at(&caller, 46), 159);
+        assert_eq!(&caller[50..54], b"LIST");
+        assert_eq!(&caller[58..66], b"INFOIKEY");
+        assert_eq!(&caller[70..78], b"support\0");
+        assert_eq!(&caller[78..82], b"data");
+        assert_eq!(u32_at(&caller, 82), 159);
+        // The odd-length data chunk is padded to an even size.
+        assert_eq!(caller.len(), 86 + 160);
+        assert_eq!(u32_at(&agent, 82), 0);
+    }
+
+    #[test]
+    fn paused_intervals_are_left_out_of_the_timeline() {
+        let start = SystemTime::UNIX_EPOCH;
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut clock = PauseClock::new(start);
+        assert_eq!(clock.offset(at(100)), Some(Duration::from_millis(100)));
+        clock.pause(at(200));
+        clock.pause(at(250));
+        assert_eq!(clock.offset(at(300)), None);
+        clock.resume(at(500));
+        assert_eq!(clock.offset(at(600)), Some(Duration::from_millis(300)));
+    }
+}
@@ -0,0 +1,458 @@
+//! Sample-rate and channel conversion between the media channel and the application.
+
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use crate::{MediaFormat, MediaFrame};
+
+/// Interpolation used to convert between sample rates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResamplerQuality {
+    /// Linear interpolation: no latency and little CPU, but audible aliasing when downsampling.
+    Linear,
+    /// Windowed sinc with 8 zero crossings per side.
+    #[default]
+    Medium,
+    /// Windowed sinc with 32 zero crossings per side.
+    High,
+}
+
+impl ResamplerQuality {
+    /// Zero crossings of the sinc kernel on each side of an output sample.
+    const fn zero_crossings(self) -> usize {
+        match self {
+            Self::Linear => 0,
+            Self::Medium => 8,
+            Self::High => 32,
+        }
+    }
+}
+
+/// Application-side L16 layout that the session converts to and from the channel format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResamplerConfig {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub quality: ResamplerQuality,
+}
+
+impl ResamplerConfig {
+    pub(crate) fn validate(self) -> Result<(), crate::Error> {
+        if self.sample_rate == 0 {
+            return Err(crate::Error::Configuration(
+                "resampler sample rate must be positive".to_owned(),
+            ));
+        }
+        if self.channels == 0 {
+            return Err(crate::Error::Configuration(
+                "resampler channel count must be positive".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Return the application format paired with `channel`, which must be L16.
+    pub(crate) fn format(self, channel: &MediaFormat) -> Result<MediaFormat, crate::Error> {
+        if channel.encoding != "L16" {
+            return Err(crate::Error::InvalidMediaFormat(format!(
+                "resampling needs an L16 channel format, got {:?}",
+                channel.encoding
+            )));
+        }
+        let format = MediaFormat {
+            encoding: "L16".to_owned(),
+            sample_rate: self.sample_rate,
+            bit_depth: 16,
+            channels: self.channels,
+            ptime: channel.ptime,
+        };
+        format.frame_bytes()?;
+        Ok(format)
+    }
+}
+
+/// Streaming converter for one direction; filter state carries across frames.
+pub(crate) struct Resampler {
+    from_rate: u32,
+    to_rate: u32,
+    from_channels: usize,
+    to_channels: usize,
+    /// Input samples each side of an output position that contribute to it.
+    reach: usize,
+    /// Sinc zero crossings per side, or zero for linear interpolation.
+    zero_crossings: usize,
+    /// Lowpass cutoff relative to the input Nyquist frequency.
+    cutoff: f64,
+    /// Unconsumed input per output channel, starting at absolute input index `base`.
+    history: Vec<Vec<f32>>,
+    base: i64,
+    /// Absolute index of the next input sample.
+    received: i64,
+    /// Output samples produced so far.
+    produced: u64,
+    /// Output bytes not yet grouped into a whole frame.
+    pending: Vec<u8>,
+    frame_bytes: usize,
+}
+
+impl Resampler {
+    pub(crate) fn new(
+        from: &MediaFormat,
+        to: &MediaFormat,
+        quality: ResamplerQuality,
+    ) -> Result<Self, crate::Error> {
+        let frame_bytes = to.frame_bytes()?;
+        from.frame_bytes()?;
+        let zero_crossings = quality.zero_crossings();
+        let cutoff = (f64::from(to.sample_rate) / f64::from(from.sample_rate)).min(1.0);
+        let reach = if zero_crossings == 0 {
+            1
+        } else {
+            // Widening the kernel by the inverse cutoff keeps its zero crossings when it lowpasses.
+            to_usize((usize_f64(zero_crossings) / cutoff).ceil())
+        };
+        let to_channels = usize::from(to.channels);
+        // The kernel is centred on each output position, so prime it with `reach` silent samples.
+        let lead = i64::try_from(reach).unwrap_or(i64::MAX);
+        Ok(Self {
+            from_rate: from.sample_rate,
+            to_rate: to.sample_rate,
+            from_channels: usize::from(from.channels),
+            to_channels,
+            reach,
+            zero_crossings,
+            cutoff,
+            history: vec![vec![0.0; reach]; to_channels],
+            base: -lead,
+            received: 0,
+            produced: 0,
+            pending: Vec::new(),
+            frame_bytes,
+        })
+    }
+
+    /// Convert one timed frame and return the whole output frames it completes, each timed at
+    /// the source time of its first sample.
+    pub(crate) fn convert(&mut self, frame: &MediaFrame) -> Vec<MediaFrame> {
+        let start = i64_f64(self.received);
+        let frames = self.convert_frames(&frame.data);
+        let sample_bytes = 2 * self.to_channels;
+        let frame_samples = u64::try_from(self.frame_bytes / sample_bytes).unwrap_or(u64::MAX);
+        let held = (frames.len() * self.frame_bytes + self.pending.len()) / sample_bytes;
+        let first = self.produced - u64::try_from(held).unwrap_or(self.produced);
+        frames
+            .into_iter()
+            .zip(0_u64..)
+            .map(|(data, index)| {
+                let pts = frame.pts.map(|pts| {
+                    // Positions are in input samples; earlier outputs sit in previous frames.
+                    let offset = self.position(first + index * frame_samples) - start;
+                    let nanos = offset.abs() * 1_000_000_000.0 / f64::from(self.from_rate);
+                    let shift = Duration::from_nanos(to_u64(nanos.round()));
+                    if offset < 0.0 {
+                        pts.saturating_sub(shift)
+                    } else {
+                        pts + shift
+                    }
+                });
+                MediaFrame { data, pts }
+            })
+            .collect()
+    }
+
+    /// Convert audio and return the whole output frames it completes.
+    pub(crate) fn convert_frames(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        let converted = self.process(data);
+        self.pending.extend_from_slice(&converted);
+        let whole = self.pending.len() / self.frame_bytes * self.frame_bytes;
+        self.pending
+            .drain(..whole)
+            .as_slice()
+            .chunks_exact(self.frame_bytes)
+            .map(<[u8]>::to_vec)
+            .collect()
+    }
+
+    /// Append interleaved L16 input and return the output it completes.
+    fn process(&mut self, data: &[u8]) -> Vec<u8> {
+        let samples: Vec<f32> = data
+            .chunks_exact(2)
+            .map(|sample| f32::from(i16::from_le_bytes([sample[0], sample[1]])))
+            .collect();
+        let frames = samples.len() / self.from_channels.max(1);
+        for frame in samples.chunks_exact(self.from_channels.max(1)) {
+            for (channel, history) in self.history.iter_mut().enumerate() {
+                history.push(mix(frame, channel, self.to_channels));
+            }
+        }
+        self.received += i64::try_from(frames).unwrap_or(i64::MAX);
+
+        let mut output = Vec::new();
+        let reach = i64::try_from(self.reach).unwrap_or(i64::MAX);
+        loop {
+            let position = self.position(self.produced);
+            let (whole, fraction) = (floor(position), position.fract());
+            if whole + reach >= self.received {
+                break;
+            }
+            for history in &self.history {
+                let index = usize::try_from(whole - self.base).unwrap_or(0);
+                let sample = if self.zero_crossings == 0 {
+                    let current = f64::from(history[index]);
+                    let next = f64::from(history[index + 1]);
+                    current + (next - current) * fraction
+                } else {
+                    self.sinc(history, index, fraction)
+                };
+                output.extend_from_slice(&saturate(sample).to_le_bytes());
+            }
+            self.produced += 1;
+        }
+
+        // Keep only the input the next output position still reaches back to.
+        let keep_from = floor(self.position(self.produced)) - reach;
+        let drop = usize::try_from(keep_from - self.base).unwrap_or(0);
+        if drop > 0 {
+            for history in &mut self.history {
+                history.drain(..drop.min(history.len()));
+            }
+            self.base += i64::try_from(drop).unwrap_or(0);
+        }
+        output
+    }
+
+    /// Return the input position of output sample `index`.
+    fn position(&self, index: u64) -> f64 {
+        let scaled = u128::from(index) * u128::from(self.from_rate);
+        let whole = scaled / u128::from(self.to_rate);
+        let remainder = scaled % u128::from(self.to_rate);
+        u128_f64(whole) + u128_f64(remainder) / f64::from(self.to_rate)
+    }
+
+    fn sinc(&self, history: &[f32], index: usize, fraction: f64) -> f64 {
+        let span = usize_f64(self.reach);
+        let (mut sum, mut weights) = (0.0, 0.0);
+        for tap in index + 1 - self.reach..=index + self.reach {
+            let Some(sample) = history.get(tap) else {
+                continue;
+            };
+            let distance = usize_f64(tap) - usize_f64(index) - fraction;
+            if distance.abs() >= span {
+                continue;
+            }
+            let argument = PI * distance * self.cutoff;
+            let sinc = if argument == 0.0 {
+                1.0
+            } else {
+                argument.sin() / argument
+            };
+            // Blackman window over the kernel span.
+            let phase = PI * (distance / span + 1.0);
+            let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+            let weight = sinc * window;
+            sum += f64::from(*sample) * weight;
+            weights += weight;
+        }
+        // Normalizing the taps keeps unity gain at DC whatever the fractional phase.
+        if weights == 0.0 { 0.0 } else { sum / weights }
+    }
+}
+
+/// Output channel `channel` from one interleaved input frame.
+///
+/// Mono output averages every input channel, mono input is duplicated, and other layouts map
+/// channels by index.
+fn mix(frame: &[f32], channel: usize, to_channels: usize) -> f32 {
+    if to_channels == 1 && frame.len() > 1 {
+        #[allow(clippy::cast_precision_loss)]
+        let count = frame.len() as f32;
+        return frame.iter().sum::<f32>() / count;
+    }
+    frame[channel % frame.len()]
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn i64_f64(value: i64) -> f64 {
+    value as f64
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn floor(position: f64) -> i64 {
+    // Positions stay far below 2^53 samples, so the truncation is exact.
+    position.floor() as i64
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn usize_f64(value: usize) -> f64 {
+    value as f64
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn u128_f64(value: u128) -> f64 {
+    value as f64
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_usize(value: f64) -> usize {
+    value.max(0.0) as usize
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_u64(value: f64) -> u64 {
+    value.max(0.0) as u64
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn saturate(sample: f64) -> i16 {
+    // The clamp keeps the value inside the i16 range before the cast.
+    sample.round().clamp(-32_768.0, 32_767.0) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(sample_rate: u32, channels: u16) -> MediaFormat {
+        MediaFormat {
+            encoding: "L16".to_owned(),
+            sample_rate,
+            bit_depth: 16,
+            channels,
+            ptime: Duration::from_millis(20),
+        }
+    }
+
+    fn tone(sample_rate: u32, frequency: f64, count: usize) -> Vec<u8> {
+        (0..count)
+            .flat_map(|index| {
+                let time = usize_f64(index) / f64::from(sample_rate);
+                saturate(8_000.0 * (2.0 * PI * frequency * time).sin()).to_le_bytes()
+            })
+            .collect()
+    }
+
+    fn samples(data: &[u8]) -> Vec<i16> {
+        data.chunks_exact(2)
+            .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+            .collect()
+    }
+
+    fn level(data: &[u8]) -> f64 {
+        let values = samples(data);
+        let energy: f64 = values.iter().map(|value| f64::from(*value).powi(2)).sum();
+        (energy / usize_f64(values.len())).sqrt()
+    }
+
+    #[test]
+    fn keeps_duration_level_and_pts_across_frames() {
+        for quality in [
+            ResamplerQuality::Linear,
+            ResamplerQuality::Medium,
+            ResamplerQuality::High,
+        ] {
+            let mut resampler =
+                Resampler::new(&format(8_000, 1), &format(24_000, 1), quality).unwrap();
+            let input = tone(8_000, 400.0, 8_000);
+            let mut output = Vec::new();
+            let mut emitted = 0_u32;
+            for (index, chunk) in input.chunks(320).enumerate() {
+                let frame = MediaFrame {
+                    data: chunk.to_vec(),
+                    pts: Some(Duration::from_millis(20) * u32::try_from(index).unwrap()),
+                };
+                for converted in resampler.convert(&frame) {
+                    // Whole frames only, each timed where its own audio starts.
+                    assert_eq!(converted.data.len(), 960, "{quality:?}");
+                    assert_eq!(
+                        converted.pts,
+                        Some(Duration::from_millis(20) * emitted),
+                        "{quality:?}"
+                    );
+                    emitted += 1;
+                    output.extend(converted.data);
+                }
+            }
+            // One second in, one second out less the kernel's look-ahead, in whole frames.
+            let produced = output.len() / 2;
+            assert!(
+                (23_520..=24_000).contains(&produced),
+                "{quality:?}: {produced}"
+            );
+            let ratio = level(&output[4_800..]) / level(&input[1_600..]);
+            assert!((0.97..1.03).contains(&ratio), "{quality:?}: {ratio}");
+        }
+    }
+
+    #[test]
+    fn downsampling_filters_content_above_the_new_nyquist() {
+        let input = tone(48_000, 12_000.0, 48_000);
+        let mut linear = Resampler::new(
+            &format(48_000, 1),
+            &format(16_000, 1),
+            ResamplerQuality::Linear,
+        )
+        .unwrap();
+        let mut high = Resampler::new(
+            &format(48_000, 1),
+            &format(16_000, 1),
+            ResamplerQuality::High,
+        )
+        .unwrap();
+        let aliased: Vec<u8> = linear.convert_frames(&input).concat();
+        let filtered: Vec<u8> = high.convert_frames(&input).concat();
+        assert_eq!(filtered.len() % 640, 0);
+        assert!(level(&aliased[640..]) > 2_000.0);
+        assert!(level(&filtered[640..]) < 200.0);
+    }
+
+    #[test]
+    fn channels_are_mixed_down_or_duplicated_and_frames_regrouped() {
+        let stereo: Vec<u8> = [1_000_i16, 3_000]
+            .repeat(160)
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        let mut down = Resampler::new(
+            &format(8_000, 2),
+            &format(8_000, 1),
+            ResamplerQuality::Linear,
+        )
+        .unwrap();
+        // Linear interpolation holds back one input sample, so the first frame is one short.
+        assert!(down.convert_frames(&stereo).is_empty());
+        let frames = down.convert_frames(&stereo);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(samples(&frames[0])[10], 2_000);
+
+        let mut up = Resampler::new(
+            &format(8_000, 1),
+            &format(8_000, 2),
+            ResamplerQuality::Medium,
+        )
+        .unwrap();
+        let mono: Vec<u8> = [500_i16; 160]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        // The kernel's look-ahead holds back the end of the first frame.
+        assert!(up.convert(&MediaFrame::untimed(mono.clone())).is_empty());
+        let converted = up.convert(&MediaFrame::untimed(mono));
+        assert_eq!(converted.len(), 1);
+        assert_eq!(converted[0].pts, None);
+        let values = samples(&converted[0].data);
+        assert_eq!(values[40], values[41]);
+        assert!(
+            ResamplerConfig {
+                sample_rate: 16_000,
+                channels: 1,
+                quality: ResamplerQuality::Medium,
+            }
+            .format(&MediaFormat {
+                encoding: "PCMU".to_owned(),
+                bit_depth: 8,
+                ..format(8_000, 1)
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Bounded copies of session audio frames for recording, monitoring, and analytics.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::mpsc;
+
+/// Which way a tapped frame travelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Peer audio arriving for the application.
+    Inbound,
+    /// Application audio sent to the peer.
+    Outbound,
+}
+
+/// One copied frame in the audio stream's format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TappedFrame {
+    pub direction: Direction,
+    pub data: Vec<u8>,
+    /// Inbound transport PTS, or the outbound frame's offset in the sent stream.
+    pub pts: Option<Duration>,
+    /// Wall-clock time at which the frame passed the tap.
+    pub captured_at: SystemTime,
+}
+
+/// One subscriber's receiving end; frames it cannot keep up with are dropped and counted.
+pub struct AudioTap {
+    receiver: mpsc::Receiver<TappedFrame>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl AudioTap {
+    /// Receive the next frame, or `None` once the audio stream closes and the tap drains.
+    pub async fn recv(&mut self) -> Option<TappedFrame> {
+        self.receiver.recv().await
+    }
+
+    /// Return a buffered frame without waiting.
+    pub fn try_recv(&mut self) -> Option<TappedFrame> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Count frames dropped because this tap was full.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+struct Subscriber {
+    sender: mpsc::Sender<TappedFrame>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Fan-out of copied frames to every live tap, never waiting on a subscriber.
+#[derive(Default)]
+pub(super) struct Taps {
+    state: Mutex<TapState>,
+}
+
+#[derive(Default)]
+struct TapState {
+    subscribers: Vec<Subscriber>,
+    closed: bool,
+}
+
+impl Taps {
+    pub(super) fn subscribe(&self, capacity: usize) -> AudioTap {
+        let (sender, receiver) = mpsc::channel(capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let mut state = self.lock();
+        // A tap taken after the stream closed ends immediately instead of waiting forever.
+        if !state.closed {
+            state.subscribers.push(Subscriber {
+                sender,
+                dropped: Arc::clone(&dropped),
+            });
+        }
+        AudioTap { receiver, dropped }
+    }
+
+    pub(super) fn publish(&self, direction: Direction, data: &[u8], pts: Option<Duration>) {
+        let mut state = self.lock();
+        let subscribers = &mut state.subscribers;
+        if subscribers.is_empty() {
+            return;
+        }
+        let frame = TappedFrame {
+            direction,
+            data: data.to_vec(),
+            pts,
+            captured_at: SystemTime::now(),
+        };
+        subscribers.retain(
+            |subscriber| match subscriber.sender.try_send(frame.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    subscriber.dropped.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            },
+        );
+    }
+
+    /// Drop every sender so subscribers finish after draining, and end later subscriptions.
+    pub(super) fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        state.subscribers.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TapState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn slow_subscribers_drop_frames_without_blocking_others() {
+        let taps = Taps::default();
+        let mut slow = taps.subscribe(1);
+        let mut fast = taps.subscribe(8);
+        let gone = taps.subscribe(1);
+        drop(gone);
+        for index in 0..3_u8 {
+            taps.publish(
+                Direction::Inbound,
+                &[index],
+                Some(Duration::from_millis(20) * u32::from(index)),
+            );
+        }
+        assert_eq!(taps.lock().subscribers.len(), 2);
+        assert_eq!(slow.dropped(), 2);
+        assert_eq!(slow.recv().await.unwrap().data, [0]);
+        assert!(slow.try_recv().is_none());
+        for index in 0..3 {
+            let frame = fast.recv().await.unwrap();
+            assert_eq!(frame.direction, Direction::Inbound);
+            assert_eq!(frame.data, [index]);
+            assert_eq!(
+                frame.pts,
+                Some(Duration::from_millis(20) * u32::from(index))
+            );
+        }
+        assert_eq!(fast.dropped(), 0);
+
+        taps.publish(Direction::Outbound, &[9], None);
+        taps.close();
+        assert_eq!(fast.recv().await.unwrap().direction, Direction::Outbound);
+        assert!(fast.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn subscribing_after_close_yields_an_ended_tap() {
+        let taps = Taps::default();
+        taps.close();
+        let mut late = taps.subscribe(4);
+        taps.publish(Direction::Inbound, &[1], None);
+        assert!(taps.lock().subscribers.is_empty());
+        assert!(late.recv().await.is_none());
+        assert_eq!(late.dropped(), 0);
+    }
+}
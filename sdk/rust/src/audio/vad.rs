@@ -0,0 +1,318 @@
+//! Energy-based voice activity detection on inbound L16 audio.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use super::monitor::{Detect, Monitor};
+use super::{AudioStream, TappedFrame};
+use crate::MediaFormat;
+
+/// Detection thresholds and timing.
+///
+/// Speech starts when frames stay at or above `start_threshold` for `min_speech`, and ends
+/// once they stay below `end_threshold` for `hangover`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VadConfig {
+    /// Frame RMS level, in dBFS, that counts towards speech starting.
+    pub start_threshold: f64,
+    /// Frame RMS level, in dBFS, below which speech counts as paused.
+    pub end_threshold: f64,
+    pub min_speech: Duration,
+    pub hangover: Duration,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            start_threshold: -35.0,
+            end_threshold: -45.0,
+            min_speech: Duration::from_millis(100),
+            hangover: Duration::from_millis(300),
+        }
+    }
+}
+
+impl VadConfig {
+    pub(crate) fn validate(self) -> Result<(), crate::Error> {
+        if !(self.end_threshold <= self.start_threshold && self.start_threshold <= 0.0) {
+            return Err(crate::Error::Configuration(
+                "VAD thresholds must satisfy end_threshold <= start_threshold <= 0 dBFS".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A speech boundary, timed by the detector's position in the analysed audio.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VadEvent {
+    /// Reported `min_speech` after `at`, the start of the first loud frame.
+    SpeechStarted { at: Duration },
+    /// Reported `hangover` after `at`, the end of the last loud frame.
+    SpeechEnded { at: Duration },
+}
+
+/// Frame-by-frame detector for callers that analyse audio themselves.
+pub struct VoiceActivityDetector {
+    config: VadConfig,
+    sample_rate: u32,
+    channels: u16,
+    position: Duration,
+    speaking: bool,
+    /// Start and length of the current loud run while silent.
+    onset: Option<(Duration, Duration)>,
+    /// End of the last loud frame and the quiet time since, while speaking.
+    voiced_until: Duration,
+    quiet: Duration,
+}
+
+impl VoiceActivityDetector {
+    /// Construct a detector for frames in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error for invalid thresholds or an invalid-format error for
+    /// anything but L16.
+    pub fn new(config: VadConfig, format: &MediaFormat) -> Result<Self, crate::Error> {
+        config.validate()?;
+        format.frame_bytes()?;
+        if format.encoding != "L16" {
+            return Err(crate::Error::InvalidMediaFormat(format!(
+                "voice activity detection needs L16 audio, got {:?}",
+                format.encoding
+            )));
+        }
+        Ok(Self {
+            config,
+            sample_rate: format.sample_rate,
+            channels: format.channels,
+            position: Duration::ZERO,
+            speaking: false,
+            onset: None,
+            voiced_until: Duration::ZERO,
+            quiet: Duration::ZERO,
+        })
+    }
+
+    #[must_use]
+    pub fn is_speaking(&self) -> bool {
+        self.speaking
+    }
+
+    /// Analyse the next frame and return the boundary it completes, if any.
+    pub fn process(&mut self, frame: &[u8]) -> Option<VadEvent> {
+        let start = self.position;
+        let samples = frame.len() / 2 / usize::from(self.channels);
+        let nanos = u64::try_from(samples)
+            .unwrap_or(u64::MAX)
+            .saturating_mul(1_000_000_000)
+            / u64::from(self.sample_rate);
+        let length = Duration::from_nanos(nanos);
+        self.position += length;
+        let level = level(frame);
+        if self.speaking {
+            if level >= self.config.end_threshold {
+                self.voiced_until = self.position;
+                self.quiet = Duration::ZERO;
+                return None;
+            }
+            self.quiet += length;
+            if self.quiet < self.config.hangover {
+                return None;
+            }
+            self.speaking = false;
+            return Some(VadEvent::SpeechEnded {
+                at: self.voiced_until,
+            });
+        }
+        if level < self.config.start_threshold {
+            self.onset = None;
+            return None;
+        }
+        let (at, voiced) = self.onset.get_or_insert((start, Duration::ZERO));
+        *voiced += length;
+        if *voiced < self.config.min_speech {
+            return None;
+        }
+        let at = *at;
+        self.onset = None;
+        self.speaking = true;
+        self.voiced_until = self.position;
+        self.quiet = Duration::ZERO;
+        Some(VadEvent::SpeechStarted { at })
+    }
+}
+
+/// RMS level of little-endian L16 samples in dBFS; silence is negative infinity.
+#[allow(clippy::cast_precision_loss)]
+fn level(frame: &[u8]) -> f64 {
+    let samples = frame.len() / 2;
+    if samples == 0 {
+        return f64::NEG_INFINITY;
+    }
+    let energy: f64 = frame
+        .chunks_exact(2)
+        .map(|sample| f64::from(i16::from_le_bytes([sample[0], sample[1]])).powi(2))
+        .sum();
+    let rms = (energy / samples as f64).sqrt() / 32_768.0;
+    20.0 * rms.log10()
+}
+
+/// Work run at every speech start before the event is queued, such as a protocol emission.
+pub(crate) type OnSpeechStarted =
+    Box<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// A detector plus the optional hook run at every start.
+struct Speech {
+    detector: VoiceActivityDetector,
+    on_started: Option<OnSpeechStarted>,
+}
+
+impl Detect for Speech {
+    type Event = VadEvent;
+
+    fn detect(&mut self, frame: &TappedFrame) -> Vec<VadEvent> {
+        self.detector.process(&frame.data).into_iter().collect()
+    }
+
+    fn observe(&self, event: &VadEvent) -> impl Future<Output = ()> + Send {
+        let started = match (event, &self.on_started) {
+            (VadEvent::SpeechStarted { .. }, Some(on_started)) => Some(on_started()),
+            _ => None,
+        };
+        async move {
+            if let Some(started) = started {
+                started.await;
+            }
+        }
+    }
+}
+
+/// Runs a detector over a session's inbound audio until the stream closes, or until the monitor
+/// is stopped or dropped.
+pub struct VadMonitor {
+    monitor: Monitor<VadEvent>,
+}
+
+impl VadMonitor {
+    /// Start detecting speech in `audio`'s inbound frames.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::AudioUnavailable`] before a format is negotiated, or the
+    /// [`VoiceActivityDetector::new`] failures.
+    pub fn start(audio: &AudioStream, config: VadConfig) -> Result<Self, crate::Error> {
+        Self::spawn(audio, config, None)
+    }
+
+    /// Start detecting speech and run `on_started` at every start, before the event is queued.
+    pub(crate) fn start_with(
+        audio: &AudioStream,
+        config: VadConfig,
+        on_started: OnSpeechStarted,
+    ) -> Result<Self, crate::Error> {
+        Self::spawn(audio, config, Some(on_started))
+    }
+
+    fn spawn(
+        audio: &AudioStream,
+        config: VadConfig,
+        on_started: Option<OnSpeechStarted>,
+    ) -> Result<Self, crate::Error> {
+        let format = audio.format().ok_or(crate::Error::AudioUnavailable)?;
+        let detector = VoiceActivityDetector::new(config, &format)?;
+        Ok(Self {
+            monitor: Monitor::start(
+                audio,
+                Speech {
+                    detector,
+                    on_started,
+                },
+            ),
+        })
+    }
+
+    /// Receive the next speech boundary, or `None` once the audio stream closes.
+    pub async fn recv(&mut self) -> Option<VadEvent> {
+        self.monitor.recv().await
+    }
+
+    /// Stop detecting. Dropping the monitor does the same.
+    pub fn stop(self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format() -> MediaFormat {
+        MediaFormat {
+            encoding: "L16".to_owned(),
+            sample_rate: 8_000,
+            bit_depth: 16,
+            channels: 1,
+            ptime: Duration::from_millis(20),
+        }
+    }
+
+    /// One 20 ms frame of a constant-magnitude square wave.
+    fn frame(amplitude: i16) -> Vec<u8> {
+        (0..160)
+            .flat_map(|index| {
+                let sample = if index % 2 == 0 {
+                    amplitude
+                } else {
+                    -amplitude
+                };
+                sample.to_le_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn speech_needs_min_speech_to_start_and_hangover_to_end() {
+        let mut detector = VoiceActivityDetector::new(VadConfig::default(), &format()).unwrap();
+        let ms = Duration::from_millis;
+        let loud = frame(3_000); // About -21 dBFS.
+        let murmur = frame(300); // About -41 dBFS: below the start, above the end threshold.
+        let quiet = frame(30);
+        let mut events = Vec::new();
+        let mut feed = |frame: &[u8], count: usize| {
+            for _ in 0..count {
+                events.extend(detector.process(frame));
+            }
+        };
+        // A 60 ms click is too short to count as speech.
+        feed(&quiet, 2);
+        feed(&loud, 3);
+        feed(&quiet, 2);
+        // Speech from 140 ms; a murmur and a short pause do not end it.
+        feed(&loud, 5);
+        feed(&murmur, 5);
+        feed(&quiet, 10);
+        feed(&loud, 1);
+        feed(&quiet, 15);
+        assert_eq!(
+            events,
+            [
+                VadEvent::SpeechStarted { at: ms(140) },
+                VadEvent::SpeechEnded { at: ms(560) },
+            ]
+        );
+        assert!(!detector.is_speaking());
+    }
+
+    #[test]
+    fn only_l16_and_ordered_thresholds_are_accepted() {
+        let mut pcmu = format();
+        pcmu.encoding = "PCMU".to_owned();
+        pcmu.bit_depth = 8;
+        assert!(VoiceActivityDetector::new(VadConfig::default(), &pcmu).is_err());
+        let inverted = VadConfig {
+            end_threshold: -20.0,
+            ..VadConfig::default()
+        };
+        assert!(VoiceActivityDetector::new(inverted, &format()).is_err());
+    }
+}
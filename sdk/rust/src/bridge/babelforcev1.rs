@@ -1,16 +1,46 @@
-//! Operational bridge for the frozen `babelforce.v1` catalog.
+//! Operational bridges for the frozen `babelforce.v1` catalog.
 
+use std::collections::HashMap;
 use std::future::Future;
+use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
+use std::task::{Context, Poll};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
+use futures_util::Stream;
 use serde_json::{Map, Value};
+use tokio::sync::{Notify, mpsc, oneshot};
 
+use crate::audio::recorder::{Layout, Recorder, RecorderConfig, RecordingSummary};
+use crate::audio::vad::{VadConfig, VadMonitor};
+use crate::audio::{AudioStream, duration_of};
 use crate::catalog::babelforcev1 as catalog;
-use crate::{AudioObserver, Handler, HandlerContext, MediaFormat, SessionState};
+use crate::{
+    AudioObserver, DtmfChannel, DtmfDigit, Handler, HandlerContext, MediaFormat, Notifier,
+    SessionState, TransportStats,
+};
+
+mod latency;
+mod prompt;
+mod simulated;
+mod termination;
+mod tools;
+mod transcript;
+
+pub use latency::{
+    LatencyAction, LatencyMonitor, LatencyMonitorConfig, LatencySample, LatencyStats,
+    LatencyThreshold, LocalRole,
+};
+pub use prompt::{
+    Interruption, Prompt, PromptEnd, PromptHandle, PromptOutcome, PromptPlayer, PromptSource,
+};
+pub use simulated::{CallerAction, ScriptStep, SimulatedRecording, SimulatedTelephony};
+pub use termination::{HangupReason, TerminationFailure, TerminationOutcome, TerminationPolicy};
+pub use tools::{ToolCallOutcome, ToolCallRecord, ToolRegistry};
+pub use transcript::{AgentTurn, Transcript, TranscriptAggregator, TranscriptEntry};
 
 /// Default packetization interval used by the v1 bridge.
 pub const DEFAULT_PTIME: Duration = Duration::from_millis(20);
@@ -24,7 +54,9 @@ pub struct VoiceBridgeConfig {
     pub call: catalog::CallInfo,
     pub application: catalog::AppInfo,
     pub metadata: Option<Map<String, Value>>,
-    pub audio_format: MediaFormat,
+    /// Offered formats in order of preference; audio binds in whichever the application selects.
+    pub audio_formats: Vec<MediaFormat>,
+    pub termination: TerminationPolicy,
 }
 
 impl VoiceBridgeConfig {
@@ -34,9 +66,22 @@ impl VoiceBridgeConfig {
             call,
             application,
             metadata: None,
-            audio_format: default_media_format(),
+            audio_formats: vec![default_media_format()],
+            termination: TerminationPolicy::default(),
         }
     }
+
+    fn validate(&self) -> Result<(), crate::Error> {
+        if self.audio_formats.is_empty() {
+            return Err(crate::Error::Configuration(
+                "at least one audio format must be offered".to_owned(),
+            ));
+        }
+        for format in &self.audio_formats {
+            format.frame_bytes()?;
+        }
+        self.termination.validate()
+    }
 }
 
 /// Callback registered with a telephony implementation for DTMF events.
@@ -84,18 +129,76 @@ pub trait TelephonyAdapter: Send + Sync {
     ///
     /// Returns adapter registration failures.
     fn on_hangup(&self, callback: HangupCallback) -> Result<(), crate::Error>;
+
+    /// Receive the outcome of each `agent.tool.call` once a [`ToolRegistry`] has run it, for
+    /// call analytics. Ignored by default.
+    async fn tool_call(&self, _record: ToolCallRecord) -> Result<(), crate::Error> {
+        Ok(())
+    }
+}
+
+/// Telephony-layer receiver for the events an application emits to the voice side.
+///
+/// Every method defaults to ignoring its event, so adapters implement only what they use.
+#[async_trait]
+pub trait ApplicationEventAdapter: Send + Sync {
+    async fn agent_tool_call(
+        &self,
+        _event: catalog::AgentToolCallEvent,
+    ) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    /// Receive speech starts after any barge-in has cleared buffered playback.
+    async fn audio_speech_started(
+        &self,
+        _event: catalog::AudioSpeechStartedEvent,
+    ) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    async fn input_transcript(
+        &self,
+        _event: catalog::InputTranscriptEvent,
+    ) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    async fn output_transcript_delta(
+        &self,
+        _event: catalog::OutputTranscriptDeltaEvent,
+    ) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    async fn output_transcript_done(
+        &self,
+        _event: catalog::OutputTranscriptDoneEvent,
+    ) -> Result<(), crate::Error> {
+        Ok(())
+    }
 }
 
+/// `audio.speech.started` origin for caller speech, which the application receives.
+const SENDER: &str = "sender";
+
 /// Voice-side generated-role implementation plus the v1 initialization policy.
 pub struct VoiceBridge {
     telephony: Arc<dyn TelephonyAdapter>,
     config: VoiceBridgeConfig,
     audio_hook: RwLock<Arc<AudioHook>>,
+    events: RwLock<Option<Arc<dyn ApplicationEventAdapter>>>,
+    tools: RwLock<Option<Arc<ToolRegistry>>>,
+    barge_in: AtomicBool,
     initialized: AtomicBool,
     initializing: AtomicBool,
     context: Mutex<Option<HandlerContext>>,
     dtmf_sequence: Arc<AtomicI64>,
     observation_interval: Mutex<Option<Duration>>,
+    observe_transport: AtomicBool,
+    terminating: AtomicBool,
+    termination: Mutex<Option<TerminationOutcome>>,
+    terminated: Notify,
 }
 
 impl VoiceBridge {
@@ -105,11 +208,18 @@ impl VoiceBridge {
             telephony,
             config,
             audio_hook: RwLock::new(Arc::new(|_| Box::pin(async { Ok(()) }))),
+            events: RwLock::new(None),
+            tools: RwLock::new(None),
+            barge_in: AtomicBool::new(true),
             initialized: AtomicBool::new(false),
             initializing: AtomicBool::new(false),
             context: Mutex::new(None),
             dtmf_sequence: Arc::new(AtomicI64::new(0)),
             observation_interval: Mutex::new(None),
+            observe_transport: AtomicBool::new(false),
+            terminating: AtomicBool::new(false),
+            termination: Mutex::new(None),
+            terminated: Notify::new(),
         })
     }
 
@@ -122,6 +232,22 @@ impl VoiceBridge {
         *write_lock(&self.audio_hook) = Arc::new(move |context| Box::pin(hook(context)));
     }
 
+    /// Deliver application-emitted events to `adapter`; without one they are dropped.
+    pub fn set_event_adapter(&self, adapter: Arc<dyn ApplicationEventAdapter>) {
+        *write_lock(&self.events) = Some(adapter);
+    }
+
+    /// Run `agent.tool.call` events through `registry` before the event adapter sees them.
+    pub fn set_tool_registry(&self, registry: Arc<ToolRegistry>) {
+        *write_lock(&self.tools) = Some(registry);
+    }
+
+    /// Choose whether caller speech, `audio.speech.started` with origin `sender`, discards
+    /// application audio not yet played. Barge-in is on by default.
+    pub fn set_barge_in(&self, enabled: bool) {
+        self.barge_in.store(enabled, Ordering::Release);
+    }
+
     /// Emit generated `audio.info` events at `interval` after initialization.
     ///
     /// # Errors
@@ -142,6 +268,21 @@ impl VoiceBridge {
         Ok(())
     }
 
+    /// Add the session's [`TransportStats`] to every observed `audio.info` event.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error after initialization has started.
+    pub fn observe_transport(&self) -> Result<(), crate::Error> {
+        if self.initializing.load(Ordering::Acquire) || self.initialized.load(Ordering::Acquire) {
+            return Err(crate::Error::Configuration(
+                "transport observation must be configured before session initialization".to_owned(),
+            ));
+        }
+        self.observe_transport.store(true, Ordering::Release);
+        Ok(())
+    }
+
     /// Build the runtime handler from generated Voice registrations.
     ///
     /// # Errors
@@ -149,48 +290,122 @@ impl VoiceBridge {
     /// Returns duplicate-registration configuration failures.
     pub fn handler(self: &Arc<Self>) -> Result<Handler, crate::Error> {
         let role: Arc<dyn catalog::VoiceHandler> = Arc::clone(self) as Arc<_>;
+        let events: Arc<dyn catalog::VoiceEventHandler> = Arc::clone(self) as Arc<_>;
         let bridge = Arc::clone(self);
         let begin_bridge = Arc::clone(self);
-        Ok(Handler::new(catalog::voice_handlers(role), [])?
-            .with_request_middleware(move |_, _| {
-                let initialized = bridge.initialized.load(Ordering::Acquire);
-                async move {
-                    if initialized {
-                        Ok(())
-                    } else {
-                        Err(crate::Error::Handler(crate::WireError {
-                            code: 500,
-                            message: "session not initialized".to_owned(),
-                            data: None,
-                        }))
-                    }
+        let end_bridge = Arc::clone(self);
+        Ok(Handler::new(
+            catalog::voice_handlers(role),
+            catalog::voice_event_handlers(events),
+        )?
+        .with_request_middleware(move |_, _| {
+            let initialized = bridge.initialized.load(Ordering::Acquire);
+            async move {
+                if initialized {
+                    Ok(())
+                } else {
+                    Err(crate::Error::Handler(crate::WireError {
+                        code: 500,
+                        message: "session not initialized".to_owned(),
+                        data: None,
+                    }))
                 }
-            })
-            .with_on_begin(move |context| {
-                let bridge = Arc::clone(&begin_bridge);
-                async move { bridge.begin(context).await }
-            }))
+            }
+        })
+        .with_on_begin(move |context| {
+            let bridge = Arc::clone(&begin_bridge);
+            async move { bridge.begin(context).await }
+        })
+        .with_on_end(move |state| end_bridge.session_ended(state)))
     }
 
-    /// Ask the application peer to terminate this initialized session.
+    /// Ask the application peer to terminate this initialized session, following
+    /// [`VoiceBridgeConfig::termination`].
+    ///
+    /// When a termination sequence is already running, wait for it instead. The sequence runs on
+    /// its own task, so dropping this future does not stop it from recording an outcome.
     ///
     /// # Errors
     ///
     /// Returns initialization, timeout, validation, remote, or transport failures.
-    pub async fn terminate(&self, reason: impl Into<String>) -> Result<(), crate::Error> {
+    pub async fn terminate(
+        self: &Arc<Self>,
+        reason: impl Into<String>,
+    ) -> Result<(), crate::Error> {
         let context = mutex_lock(&self.context)
             .clone()
             .ok_or_else(|| crate::Error::SessionFailed("session not initialized".to_owned()))?;
-        let request = catalog::SessionTerminateRequest {
-            reason: reason.into(),
-        };
-        tokio::time::timeout(
-            Duration::from_secs(5),
-            catalog::ApplicationPeer::new(context).session_terminate(request),
-        )
-        .await
-        .map_err(|_| crate::Error::Timeout)??;
-        Ok(())
+        self.run_termination(context, reason.into(), None).await
+    }
+
+    /// Wait until a termination sequence, from [`Self::terminate`] or a telephony hangup, has
+    /// finished or the session has ended some other way, so the telephony layer can release the
+    /// channel.
+    pub async fn terminated(&self) -> TerminationOutcome {
+        loop {
+            let terminated = self.terminated.notified();
+            if let Some(outcome) = mutex_lock(&self.termination).clone() {
+                return outcome;
+            }
+            terminated.await;
+        }
+    }
+
+    async fn run_termination(
+        self: &Arc<Self>,
+        context: HandlerContext,
+        reason: String,
+        hangup_error: Option<String>,
+    ) -> Result<(), crate::Error> {
+        if self.terminating.swap(true, Ordering::AcqRel) {
+            return match self.terminated().await.error {
+                Some(error) => Err(crate::Error::SessionFailed(error)),
+                None => Ok(()),
+            };
+        }
+        let (result_tx, result_rx) = oneshot::channel();
+        let bridge = Arc::clone(self);
+        tokio::spawn(async move {
+            let policy = &bridge.config.termination;
+            let result = termination::send_terminate(context.clone(), policy, reason.clone()).await;
+            let error = hangup_error.or_else(|| result.as_ref().err().map(ToString::to_string));
+            let closed = error.is_none() || policy.on_failure == TerminationFailure::Close;
+            if closed {
+                let _ = context.close();
+            }
+            bridge.finish_termination(TerminationOutcome {
+                reason,
+                acknowledged: matches!(result, Ok(true)),
+                error,
+                closed,
+            });
+            let _ = result_tx.send(result.map(drop));
+        });
+        result_rx.await.unwrap_or_else(|_| {
+            Err(crate::Error::SessionFailed(
+                "termination sequence stopped".to_owned(),
+            ))
+        })
+    }
+
+    /// Record the outcome [`Self::terminated`] resolves to and wake its waiters.
+    fn finish_termination(&self, outcome: TerminationOutcome) {
+        *mutex_lock(&self.termination) = Some(outcome);
+        self.terminated.notify_waiters();
+    }
+
+    /// Resolve [`Self::terminated`] when the session ends without a termination sequence, for
+    /// example after the application's `session.terminate` or a transport failure.
+    fn session_ended(&self, state: SessionState) {
+        if self.terminating.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.finish_termination(TerminationOutcome {
+            reason: String::new(),
+            acknowledged: false,
+            error: (state == SessionState::Failed).then(|| "session failed".to_owned()),
+            closed: true,
+        });
     }
 
     async fn begin(self: Arc<Self>, context: HandlerContext) -> Result<(), crate::Error> {
@@ -199,23 +414,25 @@ impl VoiceBridge {
                 "session already initialized".to_owned(),
             ));
         }
-        self.config.audio_format.frame_bytes()?;
+        self.config.validate()?;
         let response = catalog::ApplicationPeer::new(context.clone())
             .session_initialize(catalog::SessionInitializeRequest {
                 application: self.config.application.clone(),
                 call: self.config.call.clone(),
-                audio_codec_offerings: vec![audio_codec(&self.config.audio_format)],
+                audio_codec_offerings: self.config.audio_formats.iter().map(audio_codec).collect(),
                 metadata: self.config.metadata.clone(),
             })
             .await?;
-        let selected = media_format(
-            response.audio_codec.as_ref(),
-            self.config.audio_format.ptime,
-        )?;
-        if selected != self.config.audio_format {
+        let codec = response.audio_codec.as_ref().ok_or_else(|| {
+            crate::Error::InvalidMediaFormat("audio codec is required".to_owned())
+        })?;
+        let (selected, _) = select_format(&self.config.audio_formats, std::slice::from_ref(codec))
+            .ok_or(crate::Error::AudioFormatConflict)?;
+        context.accept_audio().await?;
+        let audio = context.audio().ok_or(crate::Error::AudioUnavailable)?;
+        if audio.format().as_ref() != Some(&selected) {
             return Err(crate::Error::AudioFormatConflict);
         }
-        context.accept_audio().await?;
         *mutex_lock(&self.context) = Some(context.clone());
         self.initialized.store(true, Ordering::Release);
 
@@ -225,15 +442,29 @@ impl VoiceBridge {
             })
             .await?;
         if let Some(interval) = *mutex_lock(&self.observation_interval) {
-            Self::start_audio_observation(context.clone(), interval)?;
+            Self::start_audio_observation(
+                context.clone(),
+                interval,
+                self.observe_transport.load(Ordering::Acquire),
+            )?;
         }
         let audio_hook = Arc::clone(&read_lock(&self.audio_hook));
         audio_hook(context.clone()).await?;
-        self.register_telephony_callbacks(context)?;
+        Self::register_telephony_callbacks(&self, context)?;
         Ok(())
     }
 
-    fn register_telephony_callbacks(&self, context: HandlerContext) -> Result<(), crate::Error> {
+    fn register_telephony_callbacks(
+        self: &Arc<Self>,
+        context: HandlerContext,
+    ) -> Result<(), crate::Error> {
+        if let Some(channel) = context.dtmf() {
+            tokio::spawn(forward_transport_dtmf(
+                context.clone(),
+                channel,
+                Arc::clone(&self.dtmf_sequence),
+            ));
+        }
         let dtmf_context = context.clone();
         let sequence = Arc::clone(&self.dtmf_sequence);
         self.telephony.on_dtmf(Arc::new(move |mut event| {
@@ -246,17 +477,24 @@ impl VoiceBridge {
             }
         }))?;
 
+        // The telephony adapter holds the callback, so hold the bridge weakly.
+        let bridge = Arc::downgrade(self);
         self.telephony.on_hangup(Arc::new(move |event| {
             let context = context.clone();
+            let Some(bridge) = bridge.upgrade() else {
+                return;
+            };
             if let Ok(runtime) = tokio::runtime::Handle::try_current() {
                 runtime.spawn(async move {
-                    let _ = catalog::VoiceEvents::new(context.clone())
-                        .call_hangup(event)
-                        .await;
-                    let _ = catalog::ApplicationPeer::new(context)
-                        .session_terminate(catalog::SessionTerminateRequest {
-                            reason: "hangup".to_owned(),
-                        })
+                    let policy = &bridge.config.termination;
+                    let reason = policy.hangup_reason.resolve(event.reason.as_deref());
+                    let hangup = termination::emit_hangup(context.clone(), policy, event).await;
+                    let _ = bridge
+                        .run_termination(
+                            context,
+                            reason,
+                            hangup.err().map(|error| error.to_string()),
+                        )
                         .await;
                 });
             }
@@ -266,6 +504,7 @@ impl VoiceBridge {
     fn start_audio_observation(
         context: HandlerContext,
         interval: Duration,
+        transport: bool,
     ) -> Result<(), crate::Error> {
         let audio = context.audio().ok_or(crate::Error::AudioUnavailable)?;
         let counters = Arc::new(AudioCounters::default());
@@ -279,9 +518,33 @@ impl VoiceBridge {
                 write.write.fetch_add(count_i64(count), Ordering::Relaxed);
             }),
         });
-        tokio::spawn(observe_audio(context, counters, interval));
+        tokio::spawn(observe_audio(context, counters, interval, transport));
         Ok(())
     }
+
+    fn event_adapter(&self) -> Option<Arc<dyn ApplicationEventAdapter>> {
+        read_lock(&self.events).clone()
+    }
+}
+
+/// Emit digits received in-band on the session transport like telephony DTMF callbacks.
+async fn forward_transport_dtmf(
+    context: HandlerContext,
+    channel: Arc<dyn DtmfChannel>,
+    sequence: Arc<AtomicI64>,
+) {
+    while let Ok(digit) = channel.read_dtmf().await {
+        let Ok(event) = dtmf_event(digit, sequence.fetch_add(1, Ordering::Relaxed)) else {
+            continue;
+        };
+        if catalog::VoiceEvents::new(context.clone())
+            .dtmf(event)
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
 }
 
 #[derive(Default)]
@@ -293,7 +556,12 @@ struct AudioCounters {
 }
 
 #[allow(clippy::cast_precision_loss)]
-async fn observe_audio(context: HandlerContext, counters: Arc<AudioCounters>, interval: Duration) {
+async fn observe_audio(
+    context: HandlerContext,
+    counters: Arc<AudioCounters>,
+    interval: Duration,
+    transport: bool,
+) {
     let mut timer = tokio::time::interval(interval);
     timer.tick().await;
     loop {
@@ -320,6 +588,11 @@ async fn observe_audio(context: HandlerContext, counters: Arc<AudioCounters>, in
                 bytes_per_second: write as f64 / seconds,
                 bytes_total: write_total,
             },
+            transport: if transport {
+                context.stats().await.map(transport_info)
+            } else {
+                None
+            },
         };
         if catalog::VoiceEvents::new(context.clone())
             .audio_info(event)
@@ -409,6 +682,656 @@ impl catalog::VoiceHandler for VoiceBridge {
     }
 }
 
+#[async_trait]
+impl catalog::VoiceEventHandler for VoiceBridge {
+    async fn agent_tool_call(
+        &self,
+        context: HandlerContext,
+        event: catalog::AgentToolCallEvent,
+    ) -> Result<(), crate::Error> {
+        let registry = read_lock(&self.tools).clone();
+        if let Some(registry) = registry {
+            registry.dispatch(context, event.clone(), Arc::clone(&self.telephony))?;
+        }
+        match self.event_adapter() {
+            Some(adapter) => adapter.agent_tool_call(event).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn audio_speech_started(
+        &self,
+        context: HandlerContext,
+        event: catalog::AudioSpeechStartedEvent,
+    ) -> Result<(), crate::Error> {
+        if self.barge_in.load(Ordering::Acquire)
+            && event.origin == SENDER
+            && let Some(audio) = context.audio()
+        {
+            let _ = audio.clear_read_buffer();
+        }
+        match self.event_adapter() {
+            Some(adapter) => adapter.audio_speech_started(event).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn input_transcript(
+        &self,
+        _context: HandlerContext,
+        event: catalog::InputTranscriptEvent,
+    ) -> Result<(), crate::Error> {
+        match self.event_adapter() {
+            Some(adapter) => adapter.input_transcript(event).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn output_transcript_delta(
+        &self,
+        _context: HandlerContext,
+        event: catalog::OutputTranscriptDeltaEvent,
+    ) -> Result<(), crate::Error> {
+        match self.event_adapter() {
+            Some(adapter) => adapter.output_transcript_delta(event).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn output_transcript_done(
+        &self,
+        _context: HandlerContext,
+        event: catalog::OutputTranscriptDoneEvent,
+    ) -> Result<(), crate::Error> {
+        match self.event_adapter() {
+            Some(adapter) => adapter.output_transcript_done(event).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// How the application side binds the session's audio after selecting a codec.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AudioBinding {
+    /// Open the channel with the selected format before answering, as [`VoiceBridge`] expects.
+    #[default]
+    Open,
+    /// Accept a channel the voice peer opens, after answering.
+    Accept,
+}
+
+/// Application-side codec preferences and audio binding.
+#[derive(Clone, Debug)]
+pub struct ApplicationBridgeConfig {
+    /// Accepted formats in order of preference; the first one offered is selected.
+    pub audio_formats: Vec<MediaFormat>,
+    pub binding: AudioBinding,
+}
+
+impl Default for ApplicationBridgeConfig {
+    fn default() -> Self {
+        Self {
+            audio_formats: vec![default_media_format()],
+            binding: AudioBinding::Open,
+        }
+    }
+}
+
+/// Application-side generated-role implementation with typed event streams and requests.
+///
+/// Event streams end when the session ends, or earlier once `session.terminate` is handled or
+/// `application.move` succeeds. Events arriving before a stream is subscribed are not kept, and
+/// a stream that falls [`EVENT_STREAM_CAPACITY`] events behind drops newer ones and counts them.
+pub struct ApplicationBridge {
+    config: ApplicationBridgeConfig,
+    context: Mutex<Option<HandlerContext>>,
+    initialization: Mutex<Option<catalog::SessionInitializeRequest>>,
+    /// Selected wire format, which measures audio cleared on the voice side.
+    format: Mutex<Option<MediaFormat>>,
+    output: Mutex<OutputTurn>,
+    dtmf: Subscribers<catalog::DtmfEvent>,
+    hangups: Subscribers<catalog::CallHangupEvent>,
+    session_updates: Subscribers<catalog::SessionUpdatedEvent>,
+    audio_info: Subscribers<catalog::AudioInfoEvent>,
+}
+
+/// Agent text emitted since the last `output.transcript.done`.
+#[derive(Default)]
+struct OutputTurn {
+    /// Each delta with the outbound playback time where its audio starts.
+    deltas: Vec<(Duration, String)>,
+    /// Playback time the caller heard before a clear cut the turn.
+    heard: Option<Duration>,
+}
+
+impl ApplicationBridge {
+    #[must_use]
+    pub fn new(config: ApplicationBridgeConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            context: Mutex::new(None),
+            initialization: Mutex::new(None),
+            format: Mutex::new(None),
+            output: Mutex::new(OutputTurn::default()),
+            dtmf: Subscribers::default(),
+            hangups: Subscribers::default(),
+            session_updates: Subscribers::default(),
+            audio_info: Subscribers::default(),
+        })
+    }
+
+    /// Build the runtime handler from generated Application registrations.
+    ///
+    /// # Errors
+    ///
+    /// Returns duplicate-registration configuration failures.
+    pub fn handler(self: &Arc<Self>) -> Result<Handler, crate::Error> {
+        let role: Arc<dyn catalog::ApplicationHandler> = Arc::clone(self) as Arc<_>;
+        let events: Arc<dyn catalog::ApplicationEventHandler> = Arc::clone(self) as Arc<_>;
+        let bridge = Arc::clone(self);
+        Ok(Handler::new(
+            catalog::application_handlers(role),
+            catalog::application_event_handlers(events),
+        )?
+        .with_on_end(move |_| bridge.end_streams()))
+    }
+
+    /// Return the `session.initialize` request once it has been answered.
+    #[must_use]
+    pub fn initialization(&self) -> Option<catalog::SessionInitializeRequest> {
+        mutex_lock(&self.initialization).clone()
+    }
+
+    /// Subscribe to `dtmf` events.
+    #[must_use]
+    pub fn dtmf(&self) -> EventStream<catalog::DtmfEvent> {
+        self.dtmf.subscribe()
+    }
+
+    /// Subscribe to `call.hangup` events.
+    #[must_use]
+    pub fn hangups(&self) -> EventStream<catalog::CallHangupEvent> {
+        self.hangups.subscribe()
+    }
+
+    /// Subscribe to `session.updated` events.
+    #[must_use]
+    pub fn session_updates(&self) -> EventStream<catalog::SessionUpdatedEvent> {
+        self.session_updates.subscribe()
+    }
+
+    /// Subscribe to `audio.info` events.
+    #[must_use]
+    pub fn audio_info(&self) -> EventStream<catalog::AudioInfoEvent> {
+        self.audio_info.subscribe()
+    }
+
+    /// Ask the voice peer to hang up the call.
+    ///
+    /// # Errors
+    ///
+    /// Returns initialization, validation, remote, or transport failures.
+    pub async fn hangup(&self, reason: impl Into<String>) -> Result<(), crate::Error> {
+        self.peer()?
+            .call_hangup(catalog::CallHangupRequest {
+                reason: reason.into(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Leave the current graph node, which ends the session once the voice peer responds.
+    ///
+    /// # Errors
+    ///
+    /// Returns initialization, validation, remote, or transport failures.
+    pub async fn application_move(
+        &self,
+        request: catalog::ApplicationMoveRequest,
+    ) -> Result<catalog::ApplicationMoveResponse, crate::Error> {
+        let response = self.peer()?.application_move(request).await?;
+        self.end_streams();
+        Ok(response)
+    }
+
+    /// Discard audio still queued locally and not yet played on the voice peer, returning the
+    /// bytes the voice peer cleared.
+    ///
+    /// The current agent turn is cut where playback stopped, so
+    /// [`Self::output_transcript_done`] reports only what the caller heard.
+    ///
+    /// # Errors
+    ///
+    /// Returns initialization, remote, or transport failures.
+    pub async fn clear_audio_buffer(&self) -> Result<i64, crate::Error> {
+        let audio = self.context()?.audio();
+        // Clearing locally first stops the media pump from sending what the peer is asked to drop.
+        let played = audio.as_ref().and_then(|audio| {
+            let _ = audio.clear_write_buffer();
+            audio.playback_position()
+        });
+        let response = self
+            .peer()?
+            .audio_buffer_clear(catalog::AudioBufferClearRequest(Map::new()))
+            .await?;
+        let format = mutex_lock(&self.format).clone();
+        if let (Some(played), Some(format)) = (played, format) {
+            let cleared = duration_of(&format, u64::try_from(response.len).unwrap_or(0))?;
+            let mut output = mutex_lock(&self.output);
+            if !output.deltas.is_empty() {
+                output.heard = Some(played.saturating_sub(cleared));
+            }
+        }
+        Ok(response.len)
+    }
+
+    /// Emit `output.transcript.delta`, noting where its audio starts in the outbound stream.
+    ///
+    /// Emit each delta before writing the audio it transcribes.
+    ///
+    /// # Errors
+    ///
+    /// Returns initialization, validation, or transport failures.
+    pub async fn output_transcript_delta(
+        &self,
+        delta: impl Into<String>,
+    ) -> Result<(), crate::Error> {
+        let context = self.context()?;
+        let delta = delta.into();
+        let mut starts_at = Duration::ZERO;
+        if let Some(audio) = context.audio()
+            && let Some(format) = audio.format()
+        {
+            // Audio that was cleared or dropped never reaches the played stream it is cut against.
+            let stats = audio.playback_stats();
+            let end = stats
+                .written
+                .saturating_sub(stats.cleared)
+                .saturating_sub(stats.dropped);
+            starts_at = duration_of(&format, end)?;
+        }
+        mutex_lock(&self.output)
+            .deltas
+            .push((starts_at, delta.clone()));
+        catalog::ApplicationEvents::new(context)
+            .output_transcript_delta(catalog::OutputTranscriptDeltaEvent { delta })
+            .await
+    }
+
+    /// Emit `output.transcript.done` for the current agent turn and return its text.
+    ///
+    /// After [`Self::clear_audio_buffer`] cut the turn, the event carries the deltas whose audio
+    /// had started playing as `text`; otherwise `text` is absent and the deltas stand.
+    ///
+    /// # Errors
+    ///
+    /// Returns initialization, validation, or transport failures.
+    pub async fn output_transcript_done(&self) -> Result<String, crate::Error> {
+        let context = self.context()?;
+        let turn = std::mem::take(&mut *mutex_lock(&self.output));
+        let text = turn.heard.map(|heard| {
+            turn.deltas
+                .iter()
+                .filter(|(starts_at, _)| *starts_at < heard)
+                .map(|(_, delta)| delta.as_str())
+                .collect::<String>()
+        });
+        catalog::ApplicationEvents::new(context)
+            .output_transcript_done(catalog::OutputTranscriptDoneEvent { text: text.clone() })
+            .await?;
+        Ok(text.unwrap_or_else(|| turn.deltas.into_iter().map(|(_, delta)| delta).collect()))
+    }
+
+    /// Start a voice-side recording with optional tags and return its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns initialization, validation, remote, or transport failures.
+    pub async fn start_recording(&self, tags: Option<Vec<String>>) -> Result<String, crate::Error> {
+        let response = self
+            .peer()?
+            .recording_start(catalog::RecordingStartRequest { tags })
+            .await?;
+        Ok(response.id)
+    }
+
+    /// Stop a voice-side recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns initialization, validation, remote, or transport failures.
+    pub async fn stop_recording(
+        &self,
+        recording_id: impl Into<String>,
+    ) -> Result<(), crate::Error> {
+        self.peer()?
+            .recording_stop(catalog::RecordingStopRequest {
+                id: recording_id.into(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    fn context(&self) -> Result<HandlerContext, crate::Error> {
+        mutex_lock(&self.context)
+            .clone()
+            .ok_or_else(|| crate::Error::SessionFailed("session not initialized".to_owned()))
+    }
+
+    fn peer(&self) -> Result<catalog::VoicePeer<HandlerContext>, crate::Error> {
+        self.context().map(catalog::VoicePeer::new)
+    }
+
+    fn end_streams(&self) {
+        self.dtmf.end();
+        self.hangups.end();
+        self.session_updates.end();
+        self.audio_info.end();
+    }
+}
+
+#[async_trait]
+impl catalog::ApplicationHandler for ApplicationBridge {
+    async fn ping(
+        &self,
+        context: HandlerContext,
+        request: catalog::PingRequest,
+    ) -> Result<catalog::PingResponse, crate::Error> {
+        ping_response(&context, request)
+    }
+
+    async fn session_initialize(
+        &self,
+        context: HandlerContext,
+        request: catalog::SessionInitializeRequest,
+    ) -> Result<catalog::SessionInitializeResponse, crate::Error> {
+        let selected = select_format(&self.config.audio_formats, &request.audio_codec_offerings);
+        let (format, codec) = selected.ok_or_else(|| {
+            crate::Error::Handler(crate::WireError {
+                code: 415,
+                message: "no offered audio codec is supported".to_owned(),
+                data: None,
+            })
+        })?;
+        *mutex_lock(&self.format) = Some(format.clone());
+        match self.config.binding {
+            AudioBinding::Open => context.open_audio(format).await?,
+            AudioBinding::Accept => {
+                tokio::spawn(accept_selected_audio(context.clone(), format));
+            }
+        }
+        *mutex_lock(&self.context) = Some(context);
+        *mutex_lock(&self.initialization) = Some(request);
+        Ok(catalog::SessionInitializeResponse {
+            audio_codec: Some(codec),
+        })
+    }
+
+    async fn session_terminate(
+        &self,
+        _context: HandlerContext,
+        _request: catalog::SessionTerminateRequest,
+    ) -> Result<catalog::EmptyResponse, crate::Error> {
+        self.end_streams();
+        Ok(empty_response())
+    }
+}
+
+#[async_trait]
+impl catalog::ApplicationEventHandler for ApplicationBridge {
+    async fn audio_info(
+        &self,
+        _context: HandlerContext,
+        event: catalog::AudioInfoEvent,
+    ) -> Result<(), crate::Error> {
+        self.audio_info.send(&event);
+        Ok(())
+    }
+
+    async fn call_hangup(
+        &self,
+        _context: HandlerContext,
+        event: catalog::CallHangupEvent,
+    ) -> Result<(), crate::Error> {
+        self.hangups.send(&event);
+        Ok(())
+    }
+
+    async fn dtmf(
+        &self,
+        _context: HandlerContext,
+        event: catalog::DtmfEvent,
+    ) -> Result<(), crate::Error> {
+        self.dtmf.send(&event);
+        Ok(())
+    }
+
+    async fn session_updated(
+        &self,
+        _context: HandlerContext,
+        event: catalog::SessionUpdatedEvent,
+    ) -> Result<(), crate::Error> {
+        self.session_updates.send(&event);
+        Ok(())
+    }
+}
+
+/// Accept the voice peer's audio and close the session unless it carries the selected format.
+async fn accept_selected_audio(context: HandlerContext, format: MediaFormat) {
+    let accepted = context.accept_audio().await.and_then(|()| {
+        let audio = context.audio().ok_or(crate::Error::AudioUnavailable)?;
+        if audio.format().as_ref() == Some(&format) {
+            Ok(())
+        } else {
+            Err(crate::Error::AudioFormatConflict)
+        }
+    });
+    if accepted.is_err() {
+        let _ = context.close();
+    }
+}
+
+/// Events an [`EventStream`] may queue for a slow receiver before further ones are dropped.
+pub const EVENT_STREAM_CAPACITY: usize = 64;
+
+/// Live subscribers to one event type; `None` once the streams have ended.
+struct Subscribers<T> {
+    senders: Mutex<Option<Vec<Subscriber<T>>>>,
+}
+
+struct Subscriber<T> {
+    sender: mpsc::Sender<T>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<T> Default for Subscribers<T> {
+    fn default() -> Self {
+        Self {
+            senders: Mutex::new(Some(Vec::new())),
+        }
+    }
+}
+
+impl<T: Clone> Subscribers<T> {
+    fn subscribe(&self) -> EventStream<T> {
+        let (sender, receiver) = mpsc::channel(EVENT_STREAM_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        if let Some(senders) = mutex_lock(&self.senders).as_mut() {
+            senders.push(Subscriber {
+                sender,
+                dropped: Arc::clone(&dropped),
+            });
+        }
+        EventStream { receiver, dropped }
+    }
+
+    /// Queue `event` for every live stream, never waiting on a slow one.
+    fn send(&self, event: &T) {
+        if let Some(senders) = mutex_lock(&self.senders).as_mut() {
+            senders.retain(
+                |subscriber| match subscriber.sender.try_send(event.clone()) {
+                    Ok(()) => true,
+                    Err(mpsc::error::TrySendError::Full(_)) => {
+                        subscriber.dropped.fetch_add(1, Ordering::Relaxed);
+                        true
+                    }
+                    Err(mpsc::error::TrySendError::Closed(_)) => false,
+                },
+            );
+        }
+    }
+
+    fn end(&self) {
+        mutex_lock(&self.senders).take();
+    }
+}
+
+/// Typed peer events as a [`Stream`], ending when the session closes or fails.
+pub struct EventStream<T> {
+    receiver: mpsc::Receiver<T>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<T> EventStream<T> {
+    /// Receive the next event, or `None` once the stream has ended.
+    pub async fn recv(&mut self) -> Option<T> {
+        self.receiver.recv().await
+    }
+
+    /// Count events dropped because this stream was full.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Stream for EventStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().receiver.poll_recv(context)
+    }
+}
+
+static NEXT_RECORDING: AtomicU64 = AtomicU64::new(1);
+
+/// Local backend for [`TelephonyAdapter::recording_start`] and
+/// [`TelephonyAdapter::recording_stop`], writing session audio to WAV files in one directory.
+///
+/// Each recording is named by its ID: `<id>.wav` in stereo, or `<id>-caller.wav` and
+/// `<id>-agent.wav` in dual mono. IDs are `recording-<millis>-<n>`: the backend's creation time in
+/// Unix milliseconds and a counter shared by every backend in the process, so sessions recording
+/// into one directory do not reuse names. An existing file is never overwritten. Request tags
+/// become the files' keywords.
+pub struct LocalRecordings {
+    audio: Arc<AudioStream>,
+    directory: PathBuf,
+    dual_mono: bool,
+    prefix: String,
+    active: Mutex<HashMap<String, Recorder>>,
+}
+
+impl LocalRecordings {
+    /// Record `audio`, usually [`crate::Session::audio`], as stereo files in `directory`.
+    #[must_use]
+    pub fn new(audio: Arc<AudioStream>, directory: impl Into<PathBuf>) -> Self {
+        Self {
+            audio,
+            directory: directory.into(),
+            dual_mono: false,
+            prefix: format!(
+                "recording-{}",
+                epoch_millis(SystemTime::now()).unwrap_or_default()
+            ),
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Write one mono file per direction instead of one stereo file.
+    #[must_use]
+    pub fn dual_mono(mut self) -> Self {
+        self.dual_mono = true;
+        self
+    }
+
+    /// Start a recording and return its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Recorder::start`] failures.
+    pub fn start(
+        &self,
+        request: catalog::RecordingStartRequest,
+    ) -> Result<catalog::RecordingStartResponse, crate::Error> {
+        let id = format!(
+            "{}-{}",
+            self.prefix,
+            NEXT_RECORDING.fetch_add(1, Ordering::Relaxed)
+        );
+        let layout = if self.dual_mono {
+            Layout::DualMono {
+                caller: self.directory.join(format!("{id}-caller.wav")),
+                agent: self.directory.join(format!("{id}-agent.wav")),
+            }
+        } else {
+            Layout::Stereo(self.directory.join(format!("{id}.wav")))
+        };
+        let mut config = RecorderConfig::new(layout);
+        config.keywords = request.tags.unwrap_or_default();
+        let recorder = Recorder::start(&self.audio, &config)?;
+        mutex_lock(&self.active).insert(id.clone(), recorder);
+        Ok(catalog::RecordingStartResponse { id })
+    }
+
+    /// Pause an active recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error for an unknown recording ID.
+    pub fn pause(&self, recording_id: &str) -> Result<(), crate::Error> {
+        self.with_active(recording_id, Recorder::pause)
+    }
+
+    /// Resume a paused recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error for an unknown recording ID.
+    pub fn resume(&self, recording_id: &str) -> Result<(), crate::Error> {
+        self.with_active(recording_id, Recorder::resume)
+    }
+
+    /// Stop a recording and finalize its files.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error for an unknown recording ID, or the [`Recorder::stop`]
+    /// failures.
+    pub async fn stop(&self, recording_id: &str) -> Result<RecordingSummary, crate::Error> {
+        let recorder = mutex_lock(&self.active)
+            .remove(recording_id)
+            .ok_or_else(|| unknown_recording(recording_id))?;
+        recorder.stop().await
+    }
+
+    fn with_active(
+        &self,
+        recording_id: &str,
+        action: impl FnOnce(&Recorder),
+    ) -> Result<(), crate::Error> {
+        let active = mutex_lock(&self.active);
+        let recorder = active
+            .get(recording_id)
+            .ok_or_else(|| unknown_recording(recording_id))?;
+        action(recorder);
+        Ok(())
+    }
+}
+
+fn unknown_recording(recording_id: &str) -> crate::Error {
+    crate::Error::Configuration(format!("unknown recording {recording_id:?}"))
+}
+
 /// Default `L16/8000/1`, 20 ms media format.
 #[must_use]
 pub fn default_media_format() -> MediaFormat {
@@ -447,6 +1370,21 @@ pub fn media_format(
     Ok(format)
 }
 
+/// Return the first of `preferred` that `offerings` contain, with the matching offered codec.
+fn select_format(
+    preferred: &[MediaFormat],
+    offerings: &[catalog::AudioCodec],
+) -> Option<(MediaFormat, catalog::AudioCodec)> {
+    preferred.iter().find_map(|format| {
+        offerings
+            .iter()
+            .find(|codec| {
+                media_format(Some(codec), format.ptime).is_ok_and(|offered| offered == *format)
+            })
+            .map(|codec| (format.clone(), codec.clone()))
+    })
+}
+
 /// Create a catalog measurement ping using the current epoch time.
 ///
 /// # Errors
@@ -515,6 +1453,70 @@ fn audio_codec(format: &MediaFormat) -> catalog::AudioCodec {
     }
 }
 
+fn transport_info(stats: TransportStats) -> catalog::AudioTransportInfo {
+    let millis = |duration: Duration| i64::try_from(duration.as_millis()).unwrap_or(i64::MAX);
+    let count = |value: u64| i64::try_from(value).unwrap_or(i64::MAX);
+    catalog::AudioTransportInfo {
+        candidate_pair: stats.candidate_pair,
+        rtt: stats.rtt.map(millis),
+        jitter: stats.jitter.map(millis),
+        packets_received: stats.packets_received.map(count),
+        packets_lost: stats.packets_lost,
+        bytes_sent: stats.bytes_sent.map(count),
+        bytes_received: stats.bytes_received.map(count),
+        nacks_sent: stats.nacks_sent.map(count),
+        nacks_received: stats.nacks_received.map(count),
+        queued_bytes: stats.queued_bytes.map(count),
+    }
+}
+
+/// Number a digit from a transport or a [`DtmfMonitor`](crate::audio::dtmf::DtmfMonitor) as a
+/// catalog `dtmf` event with press and release times in epoch milliseconds.
+///
+/// # Errors
+///
+/// Returns a configuration error for a time before the Unix epoch.
+pub fn dtmf_event(digit: DtmfDigit, seq: i64) -> Result<catalog::DtmfEvent, crate::Error> {
+    Ok(catalog::DtmfEvent {
+        seq,
+        digit: digit.digit,
+        pressed_at: epoch_millis(digit.pressed_at)?,
+        released_at: epoch_millis(digit.released_at)?,
+    })
+}
+
+/// Start a [`VadMonitor`] that also emits `audio.speech.started` with origin `sender` through
+/// `notifier`, usually the Application session or its handler context, at every start.
+///
+/// Emission failures are ignored, as the session reports them itself.
+///
+/// # Errors
+///
+/// Returns the [`VadMonitor::start`] failures.
+pub fn start_vad_emitting<N>(
+    audio: &AudioStream,
+    config: VadConfig,
+    notifier: N,
+) -> Result<VadMonitor, crate::Error>
+where
+    N: Notifier + Clone + 'static,
+{
+    VadMonitor::start_with(
+        audio,
+        config,
+        Box::new(move || {
+            let events = catalog::ApplicationEvents::new(notifier.clone());
+            Box::pin(async move {
+                let _ = events
+                    .audio_speech_started(catalog::AudioSpeechStartedEvent {
+                        origin: SENDER.to_owned(),
+                    })
+                    .await;
+            })
+        }),
+    )
+}
+
 fn empty_response() -> catalog::EmptyResponse {
     catalog::EmptyResponse(Map::new())
 }
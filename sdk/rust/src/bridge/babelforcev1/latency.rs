@@ -0,0 +1,407 @@
+//! Periodic catalog pings with clock-offset estimates and latency statistics.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use super::{epoch_millis, mutex_lock, new_ping_request};
+use crate::Requester;
+use crate::catalog::babelforcev1 as catalog;
+
+/// The local role, which decides the peer operations a monitor uses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocalRole {
+    /// Ping the application; terminate with `session.terminate`.
+    Voice,
+    /// Ping the voice peer; terminate with `call.hangup`.
+    Application,
+}
+
+/// What a monitor does once latency stays at or above its threshold.
+#[derive(Clone)]
+pub enum LatencyAction {
+    /// Call back with the statistics and keep monitoring.
+    Notify(Arc<dyn Fn(LatencyStats) + Send + Sync>),
+    /// End the session with this reason and stop monitoring.
+    Terminate(String),
+}
+
+impl std::fmt::Debug for LatencyAction {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Notify(_) => formatter.write_str("Notify(..)"),
+            Self::Terminate(reason) => formatter.debug_tuple("Terminate").field(reason).finish(),
+        }
+    }
+}
+
+/// Round-trip time that triggers `action` after `breaches` consecutive pings reach it.
+///
+/// Timed-out and failed pings count as breaches.
+#[derive(Clone, Debug)]
+pub struct LatencyThreshold {
+    pub rtt: Duration,
+    pub breaches: u32,
+    pub action: LatencyAction,
+}
+
+/// Ping cadence, statistics window, and optional alarm.
+#[derive(Clone, Debug)]
+pub struct LatencyMonitorConfig {
+    pub interval: Duration,
+    /// How long one ping may take before it counts as timed out.
+    pub timeout: Duration,
+    /// Most recent round trips the statistics cover.
+    pub window: usize,
+    pub threshold: Option<LatencyThreshold>,
+}
+
+impl Default for LatencyMonitorConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(5),
+            window: 100,
+            threshold: None,
+        }
+    }
+}
+
+impl LatencyMonitorConfig {
+    fn validate(&self) -> Result<(), crate::Error> {
+        if self.interval.is_zero() || self.timeout.is_zero() || self.window == 0 {
+            return Err(crate::Error::Configuration(
+                "latency monitor interval, timeout, and window must be positive".to_owned(),
+            ));
+        }
+        if self
+            .threshold
+            .as_ref()
+            .is_some_and(|threshold| threshold.breaches == 0)
+        {
+            return Err(crate::Error::Configuration(
+                "latency threshold breaches must be positive".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// One ping measured from its `t0`, `t1`, and `t2` timestamps and the local receive time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencySample {
+    /// Round trip without the peer's processing time.
+    pub rtt: Duration,
+    /// Half the round trip.
+    pub one_way: Duration,
+    /// Peer clock minus local clock, in milliseconds.
+    pub clock_offset: i64,
+}
+
+impl LatencySample {
+    /// Estimate from the request's `t0`, the peer's `t1` and `t2`, and the local receive `t3`.
+    #[must_use]
+    pub fn estimate(t0: i64, t1: i64, t2: i64, t3: i64) -> Self {
+        let rtt = ((t3 - t0) - (t2 - t1)).max(0);
+        let rtt = Duration::from_millis(u64::try_from(rtt).unwrap_or(0));
+        Self {
+            rtt,
+            one_way: rtt / 2,
+            clock_offset: i64::midpoint(t1 - t0, t2 - t3),
+        }
+    }
+}
+
+/// Latency over the monitor's window, plus lifetime counters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Pings answered since the monitor started.
+    pub samples: u64,
+    /// Pings that timed out since the monitor started.
+    pub timeouts: u64,
+    /// Pings that failed since the monitor started, such as a peer without `ping`.
+    pub errors: u64,
+    pub last: Option<LatencySample>,
+    pub min_rtt: Option<Duration>,
+    pub avg_rtt: Option<Duration>,
+    pub p95_rtt: Option<Duration>,
+}
+
+#[derive(Default)]
+struct Window {
+    rtts: VecDeque<Duration>,
+    samples: u64,
+    timeouts: u64,
+    errors: u64,
+    last: Option<LatencySample>,
+}
+
+impl Window {
+    fn record(&mut self, sample: LatencySample, capacity: usize) {
+        if self.rtts.len() == capacity {
+            self.rtts.pop_front();
+        }
+        self.rtts.push_back(sample.rtt);
+        self.samples += 1;
+        self.last = Some(sample);
+    }
+
+    fn stats(&self) -> LatencyStats {
+        let mut sorted: Vec<_> = self.rtts.iter().copied().collect();
+        sorted.sort_unstable();
+        let count = u32::try_from(sorted.len()).unwrap_or(u32::MAX);
+        // Nearest-rank percentile.
+        let p95 = (sorted.len() * 95).div_ceil(100).saturating_sub(1);
+        LatencyStats {
+            samples: self.samples,
+            timeouts: self.timeouts,
+            errors: self.errors,
+            last: self.last,
+            min_rtt: sorted.first().copied(),
+            avg_rtt: (count > 0).then(|| sorted.iter().sum::<Duration>() / count),
+            p95_rtt: sorted.get(p95).copied(),
+        }
+    }
+}
+
+/// Pings the peer in the background until dropped, stopped, or the session ends.
+///
+/// Each ping carries the previous round trip in `rtt`.
+pub struct LatencyMonitor {
+    window: Arc<Mutex<Window>>,
+    task: JoinHandle<()>,
+}
+
+impl LatencyMonitor {
+    /// Start pinging through `requester`, usually the session or a handler context.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error for a zero interval, timeout, window, or breach count.
+    pub fn start<R>(
+        requester: R,
+        role: LocalRole,
+        config: LatencyMonitorConfig,
+    ) -> Result<Self, crate::Error>
+    where
+        R: Requester + Clone + 'static,
+    {
+        config.validate()?;
+        let window = Arc::new(Mutex::new(Window::default()));
+        let task = tokio::spawn(monitor(requester, role, config, Arc::clone(&window)));
+        Ok(Self { window, task })
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> LatencyStats {
+        mutex_lock(&self.window).stats()
+    }
+
+    /// Return whether the monitor has stopped, after a terminating alarm or once the session
+    /// closed or failed.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Stop pinging. Dropping the monitor does the same.
+    pub fn stop(self) {}
+}
+
+impl Drop for LatencyMonitor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn monitor<R>(
+    requester: R,
+    role: LocalRole,
+    config: LatencyMonitorConfig,
+    window: Arc<Mutex<Window>>,
+) where
+    R: Requester + Clone + 'static,
+{
+    let mut timer = tokio::time::interval(config.interval);
+    // A ping that waits out its timeout spans several ticks; resume the cadence from its end
+    // instead of firing the missed ticks back to back.
+    timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut last_rtt = None;
+    let mut breaches = 0;
+    loop {
+        timer.tick().await;
+        let rtt = match tokio::time::timeout(config.timeout, ping(&requester, role, last_rtt)).await
+        {
+            Ok(Ok(sample)) => {
+                mutex_lock(&window).record(sample, config.window);
+                last_rtt = i64::try_from(sample.rtt.as_millis()).ok();
+                Some(sample.rtt)
+            }
+            Ok(Err(
+                crate::Error::Closed | crate::Error::SessionClosed | crate::Error::SessionFailed(_),
+            )) => return,
+            Ok(Err(_)) => {
+                mutex_lock(&window).errors += 1;
+                None
+            }
+            Err(_) => {
+                mutex_lock(&window).timeouts += 1;
+                None
+            }
+        };
+        let Some(threshold) = &config.threshold else {
+            continue;
+        };
+        if rtt.is_some_and(|rtt| rtt < threshold.rtt) {
+            breaches = 0;
+            continue;
+        }
+        breaches += 1;
+        if breaches < threshold.breaches {
+            continue;
+        }
+        breaches = 0;
+        match &threshold.action {
+            LatencyAction::Notify(callback) => callback(mutex_lock(&window).stats()),
+            LatencyAction::Terminate(reason) => {
+                let _ = terminate(&requester, role, reason.clone()).await;
+                return;
+            }
+        }
+    }
+}
+
+async fn ping<R: Requester + Clone>(
+    requester: &R,
+    role: LocalRole,
+    last_rtt: Option<i64>,
+) -> Result<LatencySample, crate::Error> {
+    let mut request = new_ping_request()?;
+    request.rtt = last_rtt;
+    let response = match role {
+        LocalRole::Voice => {
+            catalog::ApplicationPeer::new(requester.clone())
+                .ping(request)
+                .await?
+        }
+        LocalRole::Application => {
+            catalog::VoicePeer::new(requester.clone())
+                .ping(request)
+                .await?
+        }
+    };
+    let t3 = epoch_millis(SystemTime::now())?;
+    Ok(LatencySample::estimate(
+        response.t0,
+        response.t1,
+        response.t2,
+        t3,
+    ))
+}
+
+async fn terminate<R: Requester + Clone>(
+    requester: &R,
+    role: LocalRole,
+    reason: String,
+) -> Result<(), crate::Error> {
+    match role {
+        LocalRole::Voice => {
+            catalog::ApplicationPeer::new(requester.clone())
+                .session_terminate(catalog::SessionTerminateRequest { reason })
+                .await?;
+        }
+        LocalRole::Application => {
+            catalog::VoicePeer::new(requester.clone())
+                .call_hangup(catalog::CallHangupRequest { reason })
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers every ping with a remote error until `closed` is set.
+    #[derive(Clone, Default)]
+    struct Unsupported {
+        closed: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    #[async_trait::async_trait]
+    impl Requester for Unsupported {
+        async fn request(
+            &self,
+            method: &'static str,
+            _payload: serde_json::Value,
+        ) -> Result<serde_json::Value, crate::Error> {
+            if self.closed.load(std::sync::atomic::Ordering::Acquire) {
+                return Err(crate::Error::SessionClosed);
+            }
+            Err(crate::Error::Remote(crate::WireError {
+                code: 501,
+                message: format!("unknown method: {method}"),
+                data: None,
+            }))
+        }
+    }
+
+    #[test]
+    fn estimates_exclude_peer_processing_and_split_clock_offset() {
+        // The peer clock runs 500 ms ahead; each leg takes 20 ms and the peer works 5 ms.
+        let sample = LatencySample::estimate(1_000, 1_520, 1_525, 1_045);
+        assert_eq!(sample.rtt, Duration::from_millis(40));
+        assert_eq!(sample.one_way, Duration::from_millis(20));
+        assert_eq!(sample.clock_offset, 500);
+
+        let mut window = Window::default();
+        for rtt in [30, 10, 20, 40, 50] {
+            window.record(LatencySample::estimate(0, 0, 0, rtt), 4);
+        }
+        let stats = window.stats();
+        assert_eq!(stats.samples, 5);
+        assert_eq!(stats.min_rtt, Some(Duration::from_millis(10)));
+        assert_eq!(stats.avg_rtt, Some(Duration::from_millis(30)));
+        assert_eq!(stats.p95_rtt, Some(Duration::from_millis(50)));
+    }
+
+    #[tokio::test]
+    async fn failed_pings_count_as_breaches_until_the_session_closes() {
+        let requester = Unsupported::default();
+        let (alarms, mut alarm) = tokio::sync::mpsc::unbounded_channel();
+        let monitor = LatencyMonitor::start(
+            requester.clone(),
+            LocalRole::Voice,
+            LatencyMonitorConfig {
+                interval: Duration::from_millis(5),
+                threshold: Some(LatencyThreshold {
+                    rtt: Duration::from_secs(1),
+                    breaches: 3,
+                    action: LatencyAction::Notify(Arc::new(move |stats| {
+                        let _ = alarms.send(stats);
+                    })),
+                }),
+                ..LatencyMonitorConfig::default()
+            },
+        )
+        .unwrap();
+        let stats = alarm.recv().await.unwrap();
+        assert_eq!((stats.samples, stats.timeouts, stats.errors), (0, 0, 3));
+        assert!(!monitor.is_finished());
+
+        requester
+            .closed
+            .store(true, std::sync::atomic::Ordering::Release);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !monitor.is_finished() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+}
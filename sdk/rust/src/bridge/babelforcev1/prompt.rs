@@ -0,0 +1,433 @@
+//! Recorded prompts streamed into an application session at real-time pace.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use super::EventStream;
+use crate::MediaFormat;
+use crate::audio::recorder::read_wav;
+use crate::audio::vad::{VadEvent, VadMonitor};
+use crate::audio::{AudioStream, duration_of};
+use crate::catalog::babelforcev1 as catalog;
+
+/// Frames written ahead of real time, enough to ride out scheduling jitter while keeping
+/// interruptions quick.
+const LEAD_FRAMES: usize = 2;
+
+/// Where a prompt's audio comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PromptSource {
+    /// A mono WAV file in the session's encoding and sample rate.
+    Wav(PathBuf),
+    /// A headerless file of samples in the session's format, such as raw L16.
+    Raw(PathBuf),
+    /// Samples in the session's format.
+    Memory(Vec<u8>),
+}
+
+/// Audio to play, and what may stop it early.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prompt {
+    source: PromptSource,
+    interrupt_on_speech: bool,
+    interrupt_on_dtmf: bool,
+}
+
+impl Prompt {
+    /// Play `source` to its end unless cancelled.
+    #[must_use]
+    pub fn new(source: PromptSource) -> Self {
+        Self {
+            source,
+            interrupt_on_speech: false,
+            interrupt_on_dtmf: false,
+        }
+    }
+
+    /// Stop the prompt when the player's speech monitor reports the caller speaking.
+    #[must_use]
+    pub fn interrupt_on_speech(mut self) -> Self {
+        self.interrupt_on_speech = true;
+        self
+    }
+
+    /// Stop the prompt when the caller presses a digit.
+    #[must_use]
+    pub fn interrupt_on_dtmf(mut self) -> Self {
+        self.interrupt_on_dtmf = true;
+        self
+    }
+}
+
+/// What stopped a prompt early.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Interruption {
+    Speech,
+    /// The digit that interrupted, which the application usually handles next.
+    Dtmf(catalog::DtmfEvent),
+}
+
+/// How a prompt ended.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PromptEnd {
+    Completed,
+    Interrupted(Interruption),
+    /// Cancelled by [`PromptPlayer::cancel`], or queued behind an interrupted prompt.
+    Cancelled,
+}
+
+/// How a prompt ended and how much of it played.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PromptOutcome {
+    pub end: PromptEnd,
+    /// Prompt audio the media pump took before the prompt ended.
+    pub position: Duration,
+    /// Length of the whole prompt.
+    pub duration: Duration,
+}
+
+/// Resolves when one queued prompt ends.
+pub struct PromptHandle {
+    outcome: oneshot::Receiver<Result<PromptOutcome, crate::Error>>,
+}
+
+impl PromptHandle {
+    /// Wait for the prompt to end.
+    ///
+    /// # Errors
+    ///
+    /// Returns audio write failures, or [`crate::Error::Closed`] once the player has stopped.
+    pub async fn finished(self) -> Result<PromptOutcome, crate::Error> {
+        self.outcome.await.unwrap_or(Err(crate::Error::Closed))
+    }
+}
+
+/// Plays queued prompts into a session's outbound audio, one at a time.
+///
+/// Audio is written a frame per packetization interval, so an interruption or cancellation
+/// silences the prompt within a few frames and the unsent rest is cleared from the outbound
+/// buffer. An interruption also cancels every prompt queued behind the interrupted one; input
+/// arriving while no prompt plays is ignored.
+pub struct PromptPlayer {
+    format: MediaFormat,
+    commands: mpsc::UnboundedSender<Command>,
+    task: JoinHandle<()>,
+}
+
+enum Command {
+    Play(Queued),
+    Cancel,
+}
+
+struct Queued {
+    samples: Vec<u8>,
+    interrupt_on_speech: bool,
+    interrupt_on_dtmf: bool,
+    outcome: oneshot::Sender<Result<PromptOutcome, crate::Error>>,
+}
+
+impl PromptPlayer {
+    /// Start a player writing to `audio`, interrupted by digits from `dtmf`, usually
+    /// [`super::ApplicationBridge::dtmf`], and by speech starts from `speech`, usually a
+    /// [`VadMonitor`] over the same stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::AudioUnavailable`] before a format is negotiated, or an
+    /// invalid-format error for encodings without fixed-size frames.
+    pub fn start(
+        audio: Arc<AudioStream>,
+        dtmf: Option<EventStream<catalog::DtmfEvent>>,
+        speech: Option<VadMonitor>,
+    ) -> Result<Self, crate::Error> {
+        let format = audio.format().ok_or(crate::Error::AudioUnavailable)?;
+        format.frame_bytes()?;
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let worker = Worker {
+            audio,
+            format: format.clone(),
+            commands: receiver,
+            queue: VecDeque::new(),
+            dtmf,
+            speech,
+        };
+        Ok(Self {
+            format,
+            commands,
+            task: tokio::spawn(worker.run()),
+        })
+    }
+
+    /// Load `prompt` on a blocking thread and queue it behind the prompts already queued.
+    ///
+    /// # Errors
+    ///
+    /// Returns read or format failures for the prompt's source, or [`crate::Error::Closed`]
+    /// after the player has stopped.
+    pub async fn play(&self, prompt: Prompt) -> Result<PromptHandle, crate::Error> {
+        let format = self.format.clone();
+        let samples = tokio::task::spawn_blocking(move || load(prompt.source, &format))
+            .await
+            .map_err(|error| crate::Error::Configuration(format!("prompt loader: {error}")))??;
+        let (sender, outcome) = oneshot::channel();
+        self.commands
+            .send(Command::Play(Queued {
+                samples,
+                interrupt_on_speech: prompt.interrupt_on_speech,
+                interrupt_on_dtmf: prompt.interrupt_on_dtmf,
+                outcome: sender,
+            }))
+            .map_err(|_| crate::Error::Closed)?;
+        Ok(PromptHandle { outcome })
+    }
+
+    /// End the playing prompt and every queued one as cancelled.
+    pub fn cancel(&self) {
+        let _ = self.commands.send(Command::Cancel);
+    }
+
+    /// Stop playing. Dropping the player does the same; pending prompts resolve as closed.
+    pub fn stop(self) {}
+}
+
+impl Drop for PromptPlayer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Read a prompt's samples, padding the last frame with silence.
+fn load(source: PromptSource, format: &MediaFormat) -> Result<Vec<u8>, crate::Error> {
+    let mut samples = match source {
+        PromptSource::Wav(path) => read_wav(&path, format)?,
+        PromptSource::Raw(path) => std::fs::read(&path).map_err(|error| {
+            crate::Error::Configuration(format!("cannot read {}: {error}", path.display()))
+        })?,
+        PromptSource::Memory(samples) => samples,
+    };
+    let partial = samples.len() % format.frame_bytes()?;
+    if partial > 0 {
+        samples.extend_from_slice(&format.silence()?[partial..]);
+    }
+    Ok(samples)
+}
+
+/// Something the worker reacts to.
+enum Signal {
+    Command(Option<Command>),
+    Dtmf(catalog::DtmfEvent),
+    Speech,
+}
+
+struct Worker {
+    audio: Arc<AudioStream>,
+    format: MediaFormat,
+    commands: mpsc::UnboundedReceiver<Command>,
+    queue: VecDeque<Queued>,
+    dtmf: Option<EventStream<catalog::DtmfEvent>>,
+    speech: Option<VadMonitor>,
+}
+
+impl Worker {
+    async fn run(mut self) {
+        loop {
+            let queued = match self.queue.pop_front() {
+                Some(queued) => queued,
+                None => match self.next_signal().await {
+                    Signal::Command(Some(Command::Play(queued))) => queued,
+                    Signal::Command(None) => return,
+                    _ => continue,
+                },
+            };
+            let result = self.play(&queued).await;
+            let cut = matches!(&result, Ok(outcome) if outcome.end != PromptEnd::Completed);
+            let _ = queued.outcome.send(result);
+            if cut {
+                self.cancel_queue();
+            }
+        }
+    }
+
+    async fn next_signal(&mut self) -> Signal {
+        tokio::select! {
+            command = self.commands.recv() => Signal::Command(command),
+            event = next_dtmf(&mut self.dtmf) => Signal::Dtmf(event),
+            () = next_speech(&mut self.speech) => Signal::Speech,
+        }
+    }
+
+    async fn play(&mut self, queued: &Queued) -> Result<PromptOutcome, crate::Error> {
+        let stats = self.audio.playback_stats();
+        // The prompt starts playing once the audio queued ahead of it has been taken.
+        let start = stats.played + stats.queued;
+        let total = byte_count(queued.samples.len());
+        let mut frames = queued.samples.chunks(self.format.frame_bytes()?);
+        let end = 'play: {
+            for frame in frames.by_ref().take(LEAD_FRAMES) {
+                if let Some(end) = self.write(frame, queued).await? {
+                    break 'play end;
+                }
+            }
+            let ptime = self.format.ptime;
+            let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + ptime, ptime);
+            let mut tail = 0;
+            loop {
+                let signal = tokio::select! {
+                    _ = ticks.tick() => None,
+                    signal = self.next_signal() => Some(signal),
+                };
+                let end = match signal {
+                    None => {
+                        if let Some(frame) = frames.next() {
+                            self.write(frame, queued).await?
+                        } else if tail < LEAD_FRAMES {
+                            // Let the lead written ahead of real time play out.
+                            tail += 1;
+                            None
+                        } else {
+                            let stats = self.audio.playback_stats();
+                            (stats.queued == 0 || stats.played >= start + total)
+                                .then_some(PromptEnd::Completed)
+                        }
+                    }
+                    Some(signal) => self.handle(signal, queued),
+                };
+                if let Some(end) = end {
+                    break 'play end;
+                }
+            }
+        };
+        let played = if end == PromptEnd::Completed {
+            total
+        } else {
+            let _ = self.audio.clear_write_buffer();
+            self.audio
+                .playback_stats()
+                .played
+                .saturating_sub(start)
+                .min(total)
+        };
+        Ok(PromptOutcome {
+            end,
+            position: duration_of(&self.format, played)?,
+            duration: duration_of(&self.format, total)?,
+        })
+    }
+
+    /// Write one frame, reacting to signals while a full outbound buffer holds it back.
+    ///
+    /// Returns how the prompt ended when a signal ends it before the frame is queued.
+    async fn write(
+        &mut self,
+        frame: &[u8],
+        queued: &Queued,
+    ) -> Result<Option<PromptEnd>, crate::Error> {
+        let audio = Arc::clone(&self.audio);
+        let write = audio.write(frame);
+        tokio::pin!(write);
+        loop {
+            tokio::select! {
+                written = &mut write => return written.map(|_| None),
+                signal = self.next_signal() => {
+                    if let Some(end) = self.handle(signal, queued) {
+                        return Ok(Some(end));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Queue a prompt or return how `signal` ends the playing one.
+    fn handle(&mut self, signal: Signal, queued: &Queued) -> Option<PromptEnd> {
+        match signal {
+            Signal::Command(Some(Command::Play(next))) => {
+                self.queue.push_back(next);
+                None
+            }
+            Signal::Command(Some(Command::Cancel) | None) => Some(PromptEnd::Cancelled),
+            Signal::Dtmf(event) if queued.interrupt_on_dtmf => {
+                Some(PromptEnd::Interrupted(Interruption::Dtmf(event)))
+            }
+            Signal::Speech if queued.interrupt_on_speech => {
+                Some(PromptEnd::Interrupted(Interruption::Speech))
+            }
+            Signal::Dtmf(_) | Signal::Speech => None,
+        }
+    }
+
+    fn cancel_queue(&mut self) {
+        for queued in self.queue.drain(..) {
+            let outcome =
+                duration_of(&self.format, byte_count(queued.samples.len())).map(|duration| {
+                    PromptOutcome {
+                        end: PromptEnd::Cancelled,
+                        position: Duration::ZERO,
+                        duration,
+                    }
+                });
+            let _ = queued.outcome.send(outcome);
+        }
+    }
+}
+
+/// Receive the next digit, waiting forever once the stream has ended or when there is none.
+async fn next_dtmf(dtmf: &mut Option<EventStream<catalog::DtmfEvent>>) -> catalog::DtmfEvent {
+    if let Some(stream) = dtmf {
+        if let Some(event) = stream.recv().await {
+            return event;
+        }
+        *dtmf = None;
+    }
+    std::future::pending().await
+}
+
+/// Wait for the next speech start, or forever once the monitor has stopped or when there is
+/// none.
+async fn next_speech(speech: &mut Option<VadMonitor>) {
+    if let Some(monitor) = speech {
+        while let Some(event) = monitor.recv().await {
+            if let VadEvent::SpeechStarted { .. } = event {
+                return;
+            }
+        }
+        *speech = None;
+    }
+    std::future::pending::<()>().await;
+}
+
+fn byte_count(count: usize) -> u64 {
+    u64::try_from(count).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompts_are_padded_to_whole_frames_of_silence() {
+        let format = MediaFormat {
+            encoding: "PCMU".to_owned(),
+            sample_rate: 8_000,
+            bit_depth: 8,
+            channels: 1,
+            ptime: Duration::from_millis(20),
+        };
+        let samples = load(PromptSource::Memory(vec![1; 200]), &format).unwrap();
+        assert_eq!(samples.len(), 320);
+        assert_eq!(&samples[..200], &[1; 200]);
+        let silence = format.silence().unwrap()[0];
+        assert!(samples[200..].iter().all(|byte| *byte == silence));
+        assert!(matches!(
+            load(
+                PromptSource::Raw(PathBuf::from("/nonexistent/prompt.raw")),
+                &format
+            ),
+            Err(crate::Error::Configuration(_))
+        ));
+    }
+}
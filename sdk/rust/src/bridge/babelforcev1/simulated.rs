@@ -0,0 +1,266 @@
+//! Scriptable in-memory telephony for exercising [`super::VoiceBridge`] in tests.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use super::{
+    DtmfCallback, HangupCallback, TelephonyAdapter, epoch_millis, mutex_lock, unknown_recording,
+};
+use crate::audio::AudioStream;
+use crate::audio::recorder::read_wav;
+use crate::catalog::babelforcev1 as catalog;
+
+/// How long a scripted digit is held, and the pause before the next one.
+const DIGIT_DURATION: Duration = Duration::from_millis(100);
+const DIGIT_GAP: Duration = Duration::from_millis(100);
+
+/// Something the simulated caller does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallerAction {
+    /// Press each digit in turn, reported through the DTMF callback on release.
+    Dtmf(String),
+    /// Speak the samples of a mono WAV file in the session's audio format at real-time pace.
+    Audio(PathBuf),
+    /// Hang up, reported through the hangup callback.
+    Hangup(Option<String>),
+}
+
+/// One caller action, timed from when the bridge registers its telephony callbacks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptStep {
+    pub at: Duration,
+    pub action: CallerAction,
+}
+
+/// A recording started through [`TelephonyAdapter::recording_start`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulatedRecording {
+    pub id: String,
+    pub tags: Vec<String>,
+    /// Whether the recording is still running.
+    pub active: bool,
+}
+
+/// [`TelephonyAdapter`] that keeps session variables and recordings in memory, records every
+/// `application.move` and `call.hangup`, and plays scripted caller behavior.
+#[derive(Default)]
+pub struct SimulatedTelephony {
+    variables: Mutex<Map<String, Value>>,
+    moves: Mutex<Vec<catalog::ApplicationMoveRequest>>,
+    hangups: Mutex<Vec<catalog::CallHangupRequest>>,
+    recordings: Mutex<Vec<SimulatedRecording>>,
+    next_recording: AtomicU64,
+    dtmf: Mutex<Option<DtmfCallback>>,
+    hangup: Mutex<Option<HangupCallback>>,
+    registered: Notify,
+}
+
+impl SimulatedTelephony {
+    #[must_use]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Return the current session variables.
+    #[must_use]
+    pub fn variables(&self) -> Map<String, Value> {
+        mutex_lock(&self.variables).clone()
+    }
+
+    /// Return every `application.move` request in arrival order.
+    #[must_use]
+    pub fn moves(&self) -> Vec<catalog::ApplicationMoveRequest> {
+        mutex_lock(&self.moves).clone()
+    }
+
+    /// Return every `call.hangup` request in arrival order.
+    #[must_use]
+    pub fn hangups(&self) -> Vec<catalog::CallHangupRequest> {
+        mutex_lock(&self.hangups).clone()
+    }
+
+    /// Return every recording started so far.
+    #[must_use]
+    pub fn recordings(&self) -> Vec<SimulatedRecording> {
+        mutex_lock(&self.recordings).clone()
+    }
+
+    /// Play `steps` against the voice session's `audio` once the bridge registers its
+    /// callbacks, usually when the session is initialized.
+    ///
+    /// Steps run one at a time in order of `at`; a step due while an earlier one still runs
+    /// starts as soon as it finishes. The task ends after the last step.
+    pub fn run_script(
+        self: &Arc<Self>,
+        audio: Arc<AudioStream>,
+        mut steps: Vec<ScriptStep>,
+    ) -> JoinHandle<Result<(), crate::Error>> {
+        steps.sort_by_key(|step| step.at);
+        let telephony = Arc::clone(self);
+        tokio::spawn(async move {
+            telephony.wait_registered().await;
+            let started = tokio::time::Instant::now();
+            for step in steps {
+                tokio::time::sleep_until(started + step.at).await;
+                telephony.perform(&audio, step.action).await?;
+            }
+            Ok(())
+        })
+    }
+
+    async fn wait_registered(&self) {
+        loop {
+            let registered = self.registered.notified();
+            if mutex_lock(&self.dtmf).is_some() && mutex_lock(&self.hangup).is_some() {
+                return;
+            }
+            registered.await;
+        }
+    }
+
+    async fn perform(&self, audio: &AudioStream, action: CallerAction) -> Result<(), crate::Error> {
+        match action {
+            CallerAction::Dtmf(digits) => {
+                for digit in digits.chars() {
+                    let pressed_at = SystemTime::now();
+                    tokio::time::sleep(DIGIT_DURATION).await;
+                    let callback = mutex_lock(&self.dtmf).clone();
+                    if let Some(callback) = callback {
+                        callback(catalog::DtmfEvent {
+                            seq: 0,
+                            pressed_at: epoch_millis(pressed_at)?,
+                            released_at: epoch_millis(SystemTime::now())?,
+                            digit: digit.to_string(),
+                        });
+                    }
+                    tokio::time::sleep(DIGIT_GAP).await;
+                }
+            }
+            CallerAction::Audio(path) => {
+                let format = audio.format().ok_or(crate::Error::AudioUnavailable)?;
+                let samples = {
+                    let format = format.clone();
+                    tokio::task::spawn_blocking(move || read_wav(&path, &format))
+                        .await
+                        .map_err(|error| {
+                            crate::Error::Configuration(format!("caller audio loader: {error}"))
+                        })??
+                };
+                // Speak in real time, one packetization frame per interval, like a real caller.
+                let mut ticks = tokio::time::interval(format.ptime);
+                for frame in samples.chunks(format.frame_bytes()?) {
+                    ticks.tick().await;
+                    audio.write(frame).await?;
+                }
+            }
+            CallerAction::Hangup(reason) => {
+                let callback = mutex_lock(&self.hangup).clone();
+                if let Some(callback) = callback {
+                    callback(catalog::CallHangupEvent { reason });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn register<T>(
+        &self,
+        slot: &Mutex<Option<T>>,
+        callback: T,
+        kind: &str,
+    ) -> Result<(), crate::Error> {
+        let mut slot = mutex_lock(slot);
+        if slot.is_some() {
+            return Err(crate::Error::Configuration(format!(
+                "{kind} callback already registered"
+            )));
+        }
+        *slot = Some(callback);
+        drop(slot);
+        self.registered.notify_waiters();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TelephonyAdapter for SimulatedTelephony {
+    async fn application_move(
+        &self,
+        request: catalog::ApplicationMoveRequest,
+    ) -> Result<catalog::ApplicationMoveResponse, crate::Error> {
+        let next_application_id = request.application_id.clone();
+        mutex_lock(&self.moves).push(request);
+        Ok(catalog::ApplicationMoveResponse {
+            next_application_id,
+        })
+    }
+
+    async fn hangup(&self, request: catalog::CallHangupRequest) -> Result<(), crate::Error> {
+        mutex_lock(&self.hangups).push(request);
+        Ok(())
+    }
+
+    async fn session_variables_set(
+        &self,
+        request: catalog::SessionSetRequest,
+    ) -> Result<(), crate::Error> {
+        mutex_lock(&self.variables).extend(request.data);
+        Ok(())
+    }
+
+    async fn session_variables_get(
+        &self,
+        request: catalog::SessionGetRequest,
+    ) -> Result<Map<String, Value>, crate::Error> {
+        let variables = mutex_lock(&self.variables);
+        if request.keys.is_empty() {
+            return Ok(variables.clone());
+        }
+        Ok(request
+            .keys
+            .into_iter()
+            .filter_map(|key| variables.get(&key).cloned().map(|value| (key, value)))
+            .collect())
+    }
+
+    async fn recording_start(
+        &self,
+        request: catalog::RecordingStartRequest,
+    ) -> Result<catalog::RecordingStartResponse, crate::Error> {
+        let id = format!(
+            "recording-{}",
+            self.next_recording.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        mutex_lock(&self.recordings).push(SimulatedRecording {
+            id: id.clone(),
+            tags: request.tags.unwrap_or_default(),
+            active: true,
+        });
+        Ok(catalog::RecordingStartResponse { id })
+    }
+
+    async fn recording_stop(&self, recording_id: String) -> Result<(), crate::Error> {
+        let mut recordings = mutex_lock(&self.recordings);
+        let recording = recordings
+            .iter_mut()
+            .find(|recording| recording.id == recording_id && recording.active)
+            .ok_or_else(|| unknown_recording(&recording_id))?;
+        recording.active = false;
+        Ok(())
+    }
+
+    fn on_dtmf(&self, callback: DtmfCallback) -> Result<(), crate::Error> {
+        self.register(&self.dtmf, callback, "DTMF")
+    }
+
+    fn on_hangup(&self, callback: HangupCallback) -> Result<(), crate::Error> {
+        self.register(&self.hangup, callback, "hangup")
+    }
+}
@@ -0,0 +1,159 @@
+//! How [`super::VoiceBridge`] ends a session after a caller hangup or an explicit terminate.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::HandlerContext;
+use crate::catalog::babelforcev1 as catalog;
+
+/// How the telephony hangup reason becomes the `session.terminate` reason.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HangupReason {
+    /// Always send this reason.
+    Fixed(String),
+    /// Send the telephony reason, or `fallback` when there is none.
+    Forward { fallback: String },
+    /// Translate telephony reasons; missing and unlisted ones become `fallback`.
+    Map {
+        reasons: HashMap<String, String>,
+        fallback: String,
+    },
+}
+
+impl HangupReason {
+    pub(super) fn resolve(&self, reason: Option<&str>) -> String {
+        match self {
+            Self::Fixed(fixed) => fixed.clone(),
+            Self::Forward { fallback } => reason.unwrap_or(fallback).to_owned(),
+            Self::Map { reasons, fallback } => reason
+                .and_then(|reason| reasons.get(reason))
+                .unwrap_or(fallback)
+                .clone(),
+        }
+    }
+}
+
+/// What the bridge does when the termination sequence fails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TerminationFailure {
+    /// Close the session anyway.
+    #[default]
+    Close,
+    /// Leave the session open for the application to end.
+    KeepOpen,
+}
+
+/// Timeouts, reasons, and closing behavior for ending a voice session.
+#[derive(Clone, Debug)]
+pub struct TerminationPolicy {
+    /// How long emitting `call.hangup` may take before the sequence moves on.
+    pub hangup_timeout: Duration,
+    /// How long to wait for the application to acknowledge `session.terminate`.
+    pub terminate_timeout: Duration,
+    pub hangup_reason: HangupReason,
+    /// Close after the application acknowledges `session.terminate`; otherwise close once the
+    /// request is written and the session's terminal close grace has passed.
+    pub await_acknowledgement: bool,
+    pub on_failure: TerminationFailure,
+}
+
+impl Default for TerminationPolicy {
+    fn default() -> Self {
+        Self {
+            hangup_timeout: Duration::from_secs(5),
+            terminate_timeout: Duration::from_secs(5),
+            hangup_reason: HangupReason::Fixed("hangup".to_owned()),
+            await_acknowledgement: true,
+            on_failure: TerminationFailure::Close,
+        }
+    }
+}
+
+impl TerminationPolicy {
+    pub(super) fn validate(&self) -> Result<(), crate::Error> {
+        if self.hangup_timeout.is_zero() || self.terminate_timeout.is_zero() {
+            return Err(crate::Error::Configuration(
+                "termination timeouts must be positive".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How a termination sequence ended, resolved by [`super::VoiceBridge::terminated`].
+///
+/// A session that ends without a sequence, for example after the application's
+/// `session.terminate`, resolves to an empty reason, with an error if the session failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TerminationOutcome {
+    /// Reason sent with `session.terminate`, empty when the bridge sent none.
+    pub reason: String,
+    /// Whether the application acknowledged `session.terminate` before the bridge closed.
+    pub acknowledged: bool,
+    /// First failure of the sequence, from `call.hangup` or `session.terminate`, or of the
+    /// session itself.
+    pub error: Option<String>,
+    /// Whether the session was closed rather than left open.
+    pub closed: bool,
+}
+
+/// Emit `call.hangup` within the policy's hangup timeout.
+pub(super) async fn emit_hangup(
+    context: HandlerContext,
+    policy: &TerminationPolicy,
+    event: catalog::CallHangupEvent,
+) -> Result<(), crate::Error> {
+    tokio::time::timeout(
+        policy.hangup_timeout,
+        catalog::VoiceEvents::new(context).call_hangup(event),
+    )
+    .await
+    .map_err(|_| crate::Error::Timeout)?
+}
+
+/// Send `session.terminate`, returning whether the application acknowledged it.
+pub(super) async fn send_terminate(
+    context: HandlerContext,
+    policy: &TerminationPolicy,
+    reason: String,
+) -> Result<bool, crate::Error> {
+    let request = catalog::SessionTerminateRequest { reason };
+    if !policy.await_acknowledgement {
+        context.request_then_close(request).await?;
+        return Ok(false);
+    }
+    tokio::time::timeout(
+        policy.terminate_timeout,
+        catalog::ApplicationPeer::new(context).session_terminate(request),
+    )
+    .await
+    .map_err(|_| crate::Error::Timeout)??;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hangup_reasons_resolve_with_fallbacks() {
+        assert_eq!(
+            TerminationPolicy::default()
+                .hangup_reason
+                .resolve(Some("busy")),
+            "hangup"
+        );
+        let forward = HangupReason::Forward {
+            fallback: "hangup".to_owned(),
+        };
+        assert_eq!(forward.resolve(Some("busy")), "busy");
+        assert_eq!(forward.resolve(None), "hangup");
+        let map = HangupReason::Map {
+            reasons: HashMap::from([("normal_clearing".to_owned(), "caller_left".to_owned())]),
+            fallback: "hangup".to_owned(),
+        };
+        assert_eq!(map.resolve(Some("normal_clearing")), "caller_left");
+        assert_eq!(map.resolve(Some("busy")), "hangup");
+        assert_eq!(map.resolve(None), "hangup");
+    }
+}
@@ -0,0 +1,132 @@
+//! Voice-side handlers for the tools an agent announces with `agent.tool.call`.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use super::{TelephonyAdapter, epoch_millis, mutex_lock, read_lock, write_lock};
+use crate::HandlerContext;
+use crate::catalog::babelforcev1 as catalog;
+
+type ToolFuture = Pin<Box<dyn Future<Output = Result<(), crate::Error>> + Send>>;
+type ToolHandler = dyn Fn(HandlerContext, catalog::AgentToolCallEvent) -> ToolFuture + Send + Sync;
+
+/// How a tool call ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ToolCallOutcome {
+    Completed,
+    /// The handler failed with this error message.
+    Failed(String),
+    /// No handler is registered for the tool.
+    Unhandled,
+}
+
+/// Timing and outcome of one `agent.tool.call`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToolCallRecord {
+    pub name: String,
+    /// Epoch milliseconds when the event arrived.
+    pub called_at: i64,
+    /// How long the handler ran; zero when unhandled.
+    pub duration: Duration,
+    pub outcome: ToolCallOutcome,
+}
+
+/// Handlers keyed by tool name, installed with [`super::VoiceBridge::set_tool_registry`].
+///
+/// Each call runs in its own task so slow tools do not hold up session dispatch. Its
+/// [`ToolCallRecord`] is kept in call order and reported to
+/// [`TelephonyAdapter::tool_call`], including calls without a handler and handlers that panic.
+///
+/// A registry is meant for one session: its records grow with every call for as long as it
+/// lives, so create a new one per session rather than sharing it across sessions.
+#[derive(Default)]
+pub struct ToolRegistry {
+    handlers: RwLock<HashMap<String, Arc<ToolHandler>>>,
+    /// One slot per call, reserved on arrival and filled once the call finishes.
+    records: Mutex<Vec<Option<ToolCallRecord>>>,
+}
+
+impl ToolRegistry {
+    #[must_use]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Run `handler` whenever the agent calls `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error when `name` already has a handler.
+    pub fn register<F, Fut>(&self, name: impl Into<String>, handler: F) -> Result<(), crate::Error>
+    where
+        F: Fn(HandlerContext, catalog::AgentToolCallEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), crate::Error>> + Send + 'static,
+    {
+        let name = name.into();
+        let mut handlers = write_lock(&self.handlers);
+        if handlers.contains_key(&name) {
+            return Err(crate::Error::Configuration(format!(
+                "tool {name} already has a handler"
+            )));
+        }
+        handlers.insert(
+            name,
+            Arc::new(move |context, event| Box::pin(handler(context, event))),
+        );
+        Ok(())
+    }
+
+    /// Return every finished tool call in the order the calls arrived.
+    #[must_use]
+    pub fn records(&self) -> Vec<ToolCallRecord> {
+        mutex_lock(&self.records)
+            .iter()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    pub(super) fn dispatch(
+        self: &Arc<Self>,
+        context: HandlerContext,
+        event: catalog::AgentToolCallEvent,
+        telephony: Arc<dyn TelephonyAdapter>,
+    ) -> Result<(), crate::Error> {
+        let called_at = epoch_millis(SystemTime::now())?;
+        let handler = read_lock(&self.handlers).get(&event.name).cloned();
+        let slot = {
+            let mut records = mutex_lock(&self.records);
+            records.push(None);
+            records.len() - 1
+        };
+        let registry = Arc::clone(self);
+        tokio::spawn(async move {
+            let name = event.name.clone();
+            let (outcome, duration) = match handler {
+                Some(handler) => {
+                    let started = tokio::time::Instant::now();
+                    // An inner task turns a panicking handler into a failure, not a lost record.
+                    let outcome = match tokio::spawn(handler(context, event)).await {
+                        Ok(Ok(())) => ToolCallOutcome::Completed,
+                        Ok(Err(error)) => ToolCallOutcome::Failed(error.to_string()),
+                        Err(error) => ToolCallOutcome::Failed(error.to_string()),
+                    };
+                    (outcome, started.elapsed())
+                }
+                None => (ToolCallOutcome::Unhandled, Duration::ZERO),
+            };
+            let record = ToolCallRecord {
+                name,
+                called_at,
+                duration,
+                outcome,
+            };
+            mutex_lock(&registry.records)[slot] = Some(record.clone());
+            let _ = telephony.tool_call(record).await;
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,385 @@
+//! Conversation records assembled from the babelforce.v1 transcript, tool, and DTMF events.
+
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ApplicationEventAdapter, SENDER, epoch_millis, mutex_lock};
+use crate::catalog::babelforcev1 as catalog;
+
+/// One agent utterance assembled from `output.transcript.delta` and `output.transcript.done`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentTurn {
+    pub text: String,
+    /// Epoch milliseconds of the first delta.
+    pub started_at: i64,
+    /// Epoch milliseconds of `output.transcript.done`; absent while the turn is open.
+    pub ended_at: Option<i64>,
+    /// Epoch milliseconds when caller speech barged in before the turn finished.
+    pub interrupted_at: Option<i64>,
+}
+
+/// One conversation entry; timestamps are epoch milliseconds.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptEntry {
+    Caller {
+        text: String,
+        at: i64,
+    },
+    Agent(AgentTurn),
+    ToolCall {
+        name: String,
+        at: i64,
+    },
+    Dtmf {
+        digit: String,
+        pressed_at: i64,
+        released_at: i64,
+    },
+}
+
+/// Conversation entries in the order they began.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    /// Export the entries as a JSON object with an `entries` array.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    transcript: Transcript,
+    /// Index of the agent turn still receiving deltas.
+    open: Option<usize>,
+}
+
+impl State {
+    fn open_turn(&mut self) -> Option<&mut AgentTurn> {
+        match self.transcript.entries.get_mut(self.open?) {
+            Some(TranscriptEntry::Agent(turn)) => Some(turn),
+            _ => None,
+        }
+    }
+}
+
+/// Assembles babelforce.v1 events into a [`Transcript`] on either side of a session.
+///
+/// On the voice side, pass it to [`super::VoiceBridge::set_event_adapter`]; on the application
+/// side, record each event as it is emitted. DTMF arrives outside the event adapter on both sides,
+/// so feed it through [`Self::on_dtmf`].
+#[derive(Default)]
+pub struct TranscriptAggregator {
+    state: Mutex<State>,
+}
+
+impl TranscriptAggregator {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a finalized caller utterance.
+    ///
+    /// # Errors
+    ///
+    /// Returns a system-clock range failure.
+    pub fn on_input_transcript(
+        &self,
+        event: &catalog::InputTranscriptEvent,
+    ) -> Result<(), crate::Error> {
+        let at = epoch_millis(SystemTime::now())?;
+        mutex_lock(&self.state)
+            .transcript
+            .entries
+            .push(TranscriptEntry::Caller {
+                text: event.delta.clone(),
+                at,
+            });
+        Ok(())
+    }
+
+    /// Append agent text, opening a turn when none is open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a system-clock range failure.
+    pub fn on_output_transcript_delta(
+        &self,
+        event: &catalog::OutputTranscriptDeltaEvent,
+    ) -> Result<(), crate::Error> {
+        let at = epoch_millis(SystemTime::now())?;
+        let mut state = mutex_lock(&self.state);
+        if let Some(turn) = state.open_turn() {
+            turn.text.push_str(&event.delta);
+            return Ok(());
+        }
+        state.open = Some(state.transcript.entries.len());
+        state
+            .transcript
+            .entries
+            .push(TranscriptEntry::Agent(AgentTurn {
+                text: event.delta.clone(),
+                started_at: at,
+                ended_at: None,
+                interrupted_at: None,
+            }));
+        Ok(())
+    }
+
+    /// Close the open agent turn. Present `text`, even empty, replaces the accumulated deltas;
+    /// absent `text` keeps them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a system-clock range failure.
+    pub fn on_output_transcript_done(
+        &self,
+        event: &catalog::OutputTranscriptDoneEvent,
+    ) -> Result<(), crate::Error> {
+        let at = epoch_millis(SystemTime::now())?;
+        let mut state = mutex_lock(&self.state);
+        if let Some(turn) = state.open_turn() {
+            if let Some(text) = &event.text {
+                text.clone_into(&mut turn.text);
+            }
+            turn.ended_at = Some(at);
+            state.open = None;
+        } else if let Some(text) = event.text.as_ref().filter(|text| !text.is_empty()) {
+            state
+                .transcript
+                .entries
+                .push(TranscriptEntry::Agent(AgentTurn {
+                    text: text.clone(),
+                    started_at: at,
+                    ended_at: Some(at),
+                    interrupted_at: None,
+                }));
+        }
+        Ok(())
+    }
+
+    /// Record a tool invocation by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a system-clock range failure.
+    pub fn on_agent_tool_call(
+        &self,
+        event: &catalog::AgentToolCallEvent,
+    ) -> Result<(), crate::Error> {
+        let at = epoch_millis(SystemTime::now())?;
+        mutex_lock(&self.state)
+            .transcript
+            .entries
+            .push(TranscriptEntry::ToolCall {
+                name: event.name.clone(),
+                at,
+            });
+        Ok(())
+    }
+
+    /// Mark the open agent turn as interrupted when the caller starts speaking.
+    ///
+    /// The turn stays open for its remaining deltas and `output.transcript.done`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a system-clock range failure.
+    pub fn on_audio_speech_started(
+        &self,
+        event: &catalog::AudioSpeechStartedEvent,
+    ) -> Result<(), crate::Error> {
+        if event.origin != SENDER {
+            return Ok(());
+        }
+        let at = epoch_millis(SystemTime::now())?;
+        if let Some(turn) = mutex_lock(&self.state).open_turn() {
+            turn.interrupted_at.get_or_insert(at);
+        }
+        Ok(())
+    }
+
+    /// Record a released DTMF key with its own press and release times.
+    pub fn on_dtmf(&self, event: &catalog::DtmfEvent) {
+        mutex_lock(&self.state)
+            .transcript
+            .entries
+            .push(TranscriptEntry::Dtmf {
+                digit: event.digit.clone(),
+                pressed_at: event.pressed_at,
+                released_at: event.released_at,
+            });
+    }
+
+    /// Return the conversation so far, including any open agent turn.
+    #[must_use]
+    pub fn transcript(&self) -> Transcript {
+        mutex_lock(&self.state).transcript.clone()
+    }
+
+    /// Return the conversation at session end, closing any open agent turn now.
+    ///
+    /// # Errors
+    ///
+    /// Returns a system-clock range failure.
+    pub fn finish(&self) -> Result<Transcript, crate::Error> {
+        let at = epoch_millis(SystemTime::now())?;
+        let mut state = mutex_lock(&self.state);
+        if let Some(turn) = state.open_turn() {
+            turn.ended_at = Some(at);
+        }
+        state.open = None;
+        Ok(state.transcript.clone())
+    }
+}
+
+#[async_trait]
+impl ApplicationEventAdapter for TranscriptAggregator {
+    async fn agent_tool_call(
+        &self,
+        event: catalog::AgentToolCallEvent,
+    ) -> Result<(), crate::Error> {
+        self.on_agent_tool_call(&event)
+    }
+
+    async fn audio_speech_started(
+        &self,
+        event: catalog::AudioSpeechStartedEvent,
+    ) -> Result<(), crate::Error> {
+        self.on_audio_speech_started(&event)
+    }
+
+    async fn input_transcript(
+        &self,
+        event: catalog::InputTranscriptEvent,
+    ) -> Result<(), crate::Error> {
+        self.on_input_transcript(&event)
+    }
+
+    async fn output_transcript_delta(
+        &self,
+        event: catalog::OutputTranscriptDeltaEvent,
+    ) -> Result<(), crate::Error> {
+        self.on_output_transcript_delta(&event)
+    }
+
+    async fn output_transcript_done(
+        &self,
+        event: catalog::OutputTranscriptDoneEvent,
+    ) -> Result<(), crate::Error> {
+        self.on_output_transcript_done(&event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(text: &str) -> catalog::OutputTranscriptDeltaEvent {
+        catalog::OutputTranscriptDeltaEvent {
+            delta: text.to_owned(),
+        }
+    }
+
+    fn done(text: Option<&str>) -> catalog::OutputTranscriptDoneEvent {
+        catalog::OutputTranscriptDoneEvent {
+            text: text.map(str::to_owned),
+        }
+    }
+
+    fn agent_texts(transcript: &Transcript) -> Vec<&str> {
+        transcript
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                TranscriptEntry::Agent(turn) => Some(turn.text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn done_text_presence_decides_between_deltas_and_replacement() {
+        let aggregator = TranscriptAggregator::new();
+        aggregator
+            .on_output_transcript_delta(&delta("Hel"))
+            .unwrap();
+        aggregator.on_output_transcript_delta(&delta("lo")).unwrap();
+        aggregator.on_output_transcript_done(&done(None)).unwrap();
+        aggregator
+            .on_output_transcript_delta(&delta("draft"))
+            .unwrap();
+        aggregator
+            .on_output_transcript_done(&done(Some("Final")))
+            .unwrap();
+        aggregator
+            .on_output_transcript_delta(&delta("unsaid"))
+            .unwrap();
+        aggregator
+            .on_output_transcript_done(&done(Some("")))
+            .unwrap();
+        // Without deltas, only non-empty text forms a turn.
+        aggregator
+            .on_output_transcript_done(&done(Some("Bye")))
+            .unwrap();
+        aggregator.on_output_transcript_done(&done(None)).unwrap();
+
+        let transcript = aggregator.finish().unwrap();
+        assert_eq!(agent_texts(&transcript), ["Hello", "Final", "", "Bye"]);
+        assert!(transcript.entries.iter().all(|entry| matches!(
+            entry,
+            TranscriptEntry::Agent(AgentTurn {
+                ended_at: Some(_),
+                interrupted_at: None,
+                ..
+            })
+        )));
+    }
+
+    #[test]
+    fn caller_speech_interrupts_only_an_open_turn() {
+        let aggregator = TranscriptAggregator::new();
+        let caller = catalog::AudioSpeechStartedEvent {
+            origin: SENDER.to_owned(),
+        };
+        aggregator.on_audio_speech_started(&caller).unwrap();
+        aggregator
+            .on_output_transcript_delta(&delta("Your balance"))
+            .unwrap();
+        aggregator
+            .on_audio_speech_started(&catalog::AudioSpeechStartedEvent {
+                origin: "receiver".to_owned(),
+            })
+            .unwrap();
+        assert!(matches!(
+            &aggregator.transcript().entries[..],
+            [TranscriptEntry::Agent(AgentTurn {
+                interrupted_at: None,
+                ended_at: None,
+                ..
+            })]
+        ));
+        aggregator.on_audio_speech_started(&caller).unwrap();
+        aggregator
+            .on_output_transcript_delta(&delta(" is"))
+            .unwrap();
+
+        let transcript = aggregator.finish().unwrap();
+        let [TranscriptEntry::Agent(turn)] = &transcript.entries[..] else {
+            panic!("{transcript:?}");
+        };
+        assert_eq!(turn.text, "Your balance is");
+        assert!(turn.interrupted_at.is_some() && turn.ended_at.is_some());
+        assert_eq!(transcript.to_json()["entries"][0]["type"], "agent");
+    }
+}
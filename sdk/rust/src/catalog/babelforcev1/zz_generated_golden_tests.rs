@@ -23,7 +23,7 @@ fn generated_golden_payloads_round_trip_exact_bytes() {
     }
     assert_round_trip::<AudioInfoEvent>("events/audio.info.json/round_trip", "{\"read\":{\"bytes\":0,\"bytes_per_second\":0,\"bytes_total\":0},\"write\":{\"bytes\":0,\"bytes_per_second\":0,\"bytes_total\":0}}");
     {
-        let constructed = AudioInfoEvent { read: AudioInfoItem { bytes: 0, bytes_per_second: 0.0, bytes_total: 0 }, write: AudioInfoItem { bytes: 0, bytes_per_second: 0.0, bytes_total: 0 } };
+        let constructed = AudioInfoEvent { read: AudioInfoItem { bytes: 0, bytes_per_second: 0.0, bytes_total: 0 }, write: AudioInfoItem { bytes: 0, bytes_per_second: 0.0, bytes_total: 0 }, transport: None };
         let actual = serde_json::to_string(&constructed).unwrap();
         assert_eq!(actual, "{\"read\":{\"bytes\":0,\"bytes_per_second\":0,\"bytes_total\":0},\"write\":{\"bytes\":0,\"bytes_per_second\":0,\"bytes_total\":0}}", "events/audio.info.json/construct");
     }
@@ -191,7 +191,7 @@ fn generated_golden_payloads_round_trip_exact_bytes() {
     }
     assert_round_trip::<AudioInfoEvent>("variants/events/audio.info-nonzero.json/round_trip", "{\"read\":{\"bytes\":1280,\"bytes_per_second\":12800,\"bytes_total\":6400},\"write\":{\"bytes\":32,\"bytes_per_second\":106.66666666666667,\"bytes_total\":96}}");
     {
-        let constructed = AudioInfoEvent { read: AudioInfoItem { bytes: 1280, bytes_per_second: 12800.0, bytes_total: 6400 }, write: AudioInfoItem { bytes: 32, bytes_per_second: 106.66666666666667, bytes_total: 96 } };
+        let constructed = AudioInfoEvent { read: AudioInfoItem { bytes: 1280, bytes_per_second: 12800.0, bytes_total: 6400 }, write: AudioInfoItem { bytes: 32, bytes_per_second: 106.66666666666667, bytes_total: 96 }, transport: None };
         let actual = serde_json::to_string(&constructed).unwrap();
         assert_eq!(actual, "{\"read\":{\"bytes\":1280,\"bytes_per_second\":12800,\"bytes_total\":6400},\"write\":{\"bytes\":32,\"bytes_per_second\":106.66666666666667,\"bytes_total\":96}}", "variants/events/audio.info-nonzero.json/construct");
     }
@@ -85,6 +85,9 @@ pub struct AudioInfoEvent {
     pub read: AudioInfoItem,
     /// Audio written to the telephony stream.
     pub write: AudioInfoItem,
+    /// Optional transport statistics sampled at the same time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transport: Option<AudioTransportInfo>,
 }
 
 impl crate::Validate for AudioInfoEvent {}
@@ -112,6 +115,43 @@ pub struct AudioSpeechStartedEvent {
 
 impl crate::Validate for AudioSpeechStartedEvent {}
 
+/// Transport health sampled with one `audio.info` report; unmeasured values are absent.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AudioTransportInfo {
+    /// Selected ICE candidate pair.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate_pair: Option<String>,
+    /// Latest transport round-trip time in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtt: Option<i64>,
+    /// Interarrival jitter of received media in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<i64>,
+    /// Media packets received.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packets_received: Option<i64>,
+    /// Received media packets missing from the sequence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packets_lost: Option<i64>,
+    /// Media bytes sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_sent: Option<i64>,
+    /// Media bytes received.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_received: Option<i64>,
+    /// Negative acknowledgements sent for received media.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nacks_sent: Option<i64>,
+    /// Negative acknowledgements received for sent media.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nacks_received: Option<i64>,
+    /// Bytes admitted for sending but not yet written.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queued_bytes: Option<i64>,
+}
+
+impl crate::Validate for AudioTransportInfo {}
+
 /// Data emitted by `call.hangup` when the voice peer reports call termination.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CallHangupEvent {
@@ -47,6 +47,8 @@ pub enum Error {
     AudioFormatConflict,
     #[error("audio transport is unavailable")]
     AudioUnavailable,
+    #[error("recording: {0}")]
+    Recording(String),
     #[error("configuration: {0}")]
     Configuration(String),
 }
@@ -26,6 +26,6 @@ pub use session::{
     SessionConfig, SessionState,
 };
 pub use transport::{
-    ControlChannel, KeepalivePolicy, MediaChannel, MediaFormat, MediaFrame, Received, Transport,
-    TransportFactory,
+    ControlChannel, DtmfChannel, DtmfDigit, KeepalivePolicy, MediaChannel, MediaFormat, MediaFrame,
+    Received, Transport, TransportFactory, TransportStats,
 };
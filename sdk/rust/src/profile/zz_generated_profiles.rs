@@ -23,6 +23,7 @@ pub const PROFILE_RTVBP_V1: &str = "rtvbp.v1";
 pub const PROFILE_RTVBP_DEMO_V1: &str = "rtvbp.demo.v1";
 pub const PROFILE_RTVBP_WEBRTC_V1: &str = "rtvbp.webrtc.v1";
 pub const SIGNALING_TRANSPORT_WEBRTC_OFFER: &str = "transport.webrtc.offer";
+pub const SIGNALING_TRANSPORT_WEBRTC_CANDIDATE: &str = "transport.webrtc.candidate";
 pub const DEFAULT: &str = PROFILE_RTVBP_V1;
 pub const HEADERLESS: &str = PROFILE_RTVBP_V1;
 
@@ -62,7 +63,7 @@ pub static PROFILES: &[Descriptor] = &[
         transport: "webrtcws.v1",
         envelope: "classic.v1",
         catalog: "babelforce.v1",
-        signaling: &["transport.webrtc.offer"],
+        signaling: &["transport.webrtc.offer", "transport.webrtc.candidate"],
         media: &[Media {
             channel: "audio",
             carrier: "webrtc-rtp",
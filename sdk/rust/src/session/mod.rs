@@ -13,15 +13,19 @@ use serde_json::Value;
 use tokio::sync::{Notify, mpsc, oneshot};
 use tokio::task::JoinHandle;
 
-use crate::audio::AudioStream;
+use crate::audio::{
+    AudioStream, BufferLimit, JitterBuffer, JitterBufferConfig, JitterBufferStats, Pacer,
+    PacerConfig, PacerCounters, PacerStats, Resampler, ResamplerConfig,
+};
 use crate::{
-    ControlFrame, Envelope, EventRegistration, FrameKind, HandlerReply, MediaChannel, MediaFormat,
-    NamedEvent, NamedRequest, Notifier, RequestRegistration, Requester, Transport,
-    TransportFactory, WireError,
+    ControlFrame, DtmfChannel, Envelope, EventRegistration, FrameKind, HandlerReply, MediaChannel,
+    MediaFormat, NamedEvent, NamedRequest, Notifier, RequestRegistration, Requester, Transport,
+    TransportFactory, TransportStats, WireError,
 };
 
 type HookFuture<T> = Pin<Box<dyn Future<Output = Result<T, crate::Error>> + Send>>;
 type BeginHook = dyn Fn(HandlerContext) -> HookFuture<()> + Send + Sync;
+type EndHook = dyn Fn(SessionState) + Send + Sync;
 type RequestHook = dyn Fn(HandlerContext, InboundRequest) -> HookFuture<()> + Send + Sync;
 type EventHook = dyn Fn(HandlerContext, InboundEvent) -> HookFuture<()> + Send + Sync;
 type PendingSender = oneshot::Sender<Result<Option<Value>, crate::Error>>;
@@ -50,6 +54,7 @@ pub struct Handler {
     requests: HashMap<&'static str, RequestRegistration>,
     events: HashMap<&'static str, EventRegistration>,
     on_begin: Arc<BeginHook>,
+    on_end: Option<Arc<EndHook>>,
     middleware: Vec<Arc<RequestHook>>,
     on_unknown_request: Option<Arc<RequestHook>>,
     on_unknown_event: Option<Arc<EventHook>>,
@@ -91,6 +96,7 @@ impl Handler {
             requests: request_map,
             events: event_map,
             on_begin: Arc::new(|_| Box::pin(async { Ok(()) })),
+            on_end: None,
             middleware: Vec::new(),
             on_unknown_request: None,
             on_unknown_event: None,
@@ -108,6 +114,17 @@ impl Handler {
         self
     }
 
+    /// Use a lifecycle callback once the session reaches [`SessionState::Closed`] or
+    /// [`SessionState::Failed`], however it ended.
+    #[must_use]
+    pub fn with_on_end<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(SessionState) + Send + Sync + 'static,
+    {
+        self.on_end = Some(Arc::new(callback));
+        self
+    }
+
     /// Add raw request middleware. Middleware runs in insertion order before typed decoding.
     #[must_use]
     pub fn with_request_middleware<F, Fut>(mut self, callback: F) -> Self
@@ -171,6 +188,17 @@ pub struct SessionConfig {
     /// control queue dispatches the immediately preceding response.
     pub terminal_close_grace: Duration,
     pub audio_buffer_size: usize,
+    /// Cap inbound audio waiting for the application, in time, with an overflow policy.
+    pub inbound_buffer: Option<BufferLimit>,
+    /// Cap outbound audio waiting for the transport, in time, with an overflow policy.
+    pub outbound_buffer: Option<BufferLimit>,
+    /// Reorder inbound audio by PTS and conceal gaps before it reaches [`Session::audio`].
+    pub jitter_buffer: Option<JitterBufferConfig>,
+    /// Release outbound audio at one frame per packetization interval instead of as written.
+    pub pacer: Option<PacerConfig>,
+    /// Expose audio at the application's own sample rate and channel count, converting to and
+    /// from the L16 channel format.
+    pub resampler: Option<ResamplerConfig>,
     pub keepalive: crate::KeepalivePolicy,
     pub transport_factory: Arc<dyn TransportFactory>,
     pub id_generator: Arc<dyn Fn() -> String + Send + Sync>,
@@ -190,6 +218,11 @@ impl SessionConfig {
             close_timeout: Duration::from_secs(5),
             terminal_close_grace: Duration::from_millis(100),
             audio_buffer_size: 1024 * 1024,
+            inbound_buffer: None,
+            outbound_buffer: None,
+            jitter_buffer: None,
+            pacer: None,
+            resampler: None,
             keepalive: crate::KeepalivePolicy::default(),
             transport_factory,
             id_generator: Arc::new(move || {
@@ -205,6 +238,23 @@ impl SessionConfig {
     pub fn with_transport(transport: Arc<dyn Transport>) -> Self {
         Self::new(Arc::new(FixedTransportFactory(transport)))
     }
+
+    fn validate(&self) -> Result<(), crate::Error> {
+        self.keepalive.validate()?;
+        if let Some(jitter_buffer) = &self.jitter_buffer {
+            jitter_buffer.validate()?;
+        }
+        if let Some(resampler) = self.resampler {
+            resampler.validate()?;
+        }
+        for limit in [self.inbound_buffer, self.outbound_buffer]
+            .into_iter()
+            .flatten()
+        {
+            limit.validate()?;
+        }
+        Ok(())
+    }
 }
 
 struct FixedTransportFactory(Arc<dyn Transport>);
@@ -241,6 +291,8 @@ struct SessionInner {
     audio: Arc<AudioStream>,
     media: Mutex<MediaBinding>,
     media_tasks: Mutex<Vec<JoinHandle<()>>>,
+    jitter: Mutex<Option<Arc<JitterBuffer>>>,
+    pacer: Mutex<Option<Arc<PacerCounters>>>,
 }
 
 #[derive(Default)]
@@ -262,7 +314,10 @@ impl Session {
             inner: Arc::new(SessionInner {
                 envelope,
                 handler,
-                audio: Arc::new(AudioStream::new(config.audio_buffer_size)),
+                audio: Arc::new(
+                    AudioStream::new(config.audio_buffer_size)
+                        .with_limits(config.inbound_buffer, config.outbound_buffer),
+                ),
                 config,
                 state: RwLock::new(SessionState::Inactive),
                 run_started: AtomicBool::new(false),
@@ -275,6 +330,8 @@ impl Session {
                 pending: Mutex::new(HashMap::new()),
                 media: Mutex::new(MediaBinding::Unbound),
                 media_tasks: Mutex::new(Vec::new()),
+                jitter: Mutex::new(None),
+                pacer: Mutex::new(None),
             }),
         }
     }
@@ -294,6 +351,36 @@ impl Session {
         Arc::clone(&self.inner.audio)
     }
 
+    /// Sample the constructed transport's statistics, or `None` before construction.
+    pub async fn stats(&self) -> Option<TransportStats> {
+        let transport = read_lock(&self.inner.transport).clone()?;
+        Some(transport.stats().await)
+    }
+
+    /// Return jitter-buffer counters once audio is bound with [`SessionConfig::jitter_buffer`].
+    #[must_use]
+    pub fn jitter_stats(&self) -> Option<JitterBufferStats> {
+        mutex_lock(&self.inner.jitter)
+            .as_ref()
+            .map(|jitter| jitter.stats())
+    }
+
+    /// Return outbound pacing counters once audio is bound with [`SessionConfig::pacer`].
+    ///
+    /// [`PacerStats::ahead`] reports how far ahead of real time the application is writing.
+    #[must_use]
+    pub fn pacer_stats(&self) -> Option<PacerStats> {
+        mutex_lock(&self.inner.pacer)
+            .as_ref()
+            .map(|pacer| pacer.stats(&self.inner.audio))
+    }
+
+    /// Return the constructed transport's in-band DTMF channel, when it has one.
+    #[must_use]
+    pub fn dtmf(&self) -> Option<Arc<dyn DtmfChannel>> {
+        read_lock(&self.inner.transport).as_ref()?.dtmf()
+    }
+
     /// Own the transport and session workers until terminal shutdown.
     ///
     /// # Errors
@@ -309,7 +396,7 @@ impl Session {
             return Err(crate::Error::SessionAlreadyRun);
         }
         self.set_state(SessionState::Connecting);
-        if let Err(error) = self.inner.config.keepalive.validate() {
+        if let Err(error) = self.inner.config.validate() {
             self.request_failure(error.to_string());
             return self.finish_without_transport();
         }
@@ -405,8 +492,7 @@ impl Session {
             .is_ok()
         {
             self.inner.closing.store(true, Ordering::Release);
-            self.set_state(SessionState::Closed);
-            self.inner.done_notify.notify_waiters();
+            self.finish(SessionState::Closed);
             return Ok(());
         }
         self.request_close();
@@ -485,18 +571,24 @@ impl Session {
     fn finish_terminal(&self) -> Result<(), crate::Error> {
         let failures = mutex_lock(&self.inner.stop).failures.clone();
         if failures.is_empty() {
-            self.set_state(SessionState::Closed);
-            self.inner.done_notify.notify_waiters();
+            self.finish(SessionState::Closed);
             Ok(())
         } else {
             let message = failures.join("; ");
             *mutex_lock(&self.inner.final_error) = Some(message.clone());
-            self.set_state(SessionState::Failed);
-            self.inner.done_notify.notify_waiters();
+            self.finish(SessionState::Failed);
             Err(crate::Error::SessionFailed(message))
         }
     }
 
+    fn finish(&self, state: SessionState) {
+        self.set_state(state);
+        self.inner.done_notify.notify_waiters();
+        if let Some(on_end) = &self.inner.handler.on_end {
+            on_end(state);
+        }
+    }
+
     fn set_state(&self, state: SessionState) {
         *write_lock(&self.inner.state) = state;
     }
@@ -740,6 +832,17 @@ impl Session {
         }
     }
 
+    async fn send_request(&self, method: &'static str, payload: Value) -> Result<(), crate::Error> {
+        let id = (self.inner.config.id_generator)();
+        if id.is_empty() {
+            return Err(crate::Error::RequestFailed(
+                "id generator returned an empty id".to_owned(),
+            ));
+        }
+        self.send_frame(ControlFrame::request(id, method, Some(payload)))
+            .await
+    }
+
     fn cancel_pending(&self, id: &str) -> bool {
         mutex_lock(&self.inner.pending).remove(id).is_some()
     }
@@ -845,60 +948,142 @@ impl Session {
             self.abort_audio_bind();
             return Err(crate::Error::AudioFormatConflict);
         }
-        self.inner.audio.set_format(channel.format().clone())?;
+        let sdk_format = match &self.inner.config.resampler {
+            Some(config) => config.format(channel.format())?,
+            None => channel.format().clone(),
+        };
+        let jitter = match self.inner.config.jitter_buffer {
+            Some(config) => Some(Arc::new(JitterBuffer::new(config, channel.format())?)),
+            None => None,
+        };
+        let pacer = match self.inner.config.pacer {
+            Some(config) => Some(Pacer::new(config, &sdk_format)?),
+            None => None,
+        };
+        let (inbound, outbound) = match &self.inner.config.resampler {
+            Some(config) => (
+                Some(Resampler::new(
+                    channel.format(),
+                    &sdk_format,
+                    config.quality,
+                )?),
+                Some(Resampler::new(
+                    &sdk_format,
+                    channel.format(),
+                    config.quality,
+                )?),
+            ),
+            None => (None, None),
+        };
+        self.inner.audio.set_format(sdk_format)?;
         *mutex_lock(&self.inner.media) = MediaBinding::Bound(Arc::clone(&channel));
-        self.spawn_audio_pumps(channel);
+        self.spawn_inbound_pump(Arc::clone(&channel), jitter, inbound);
+        self.spawn_outbound_pump(channel, pacer, outbound);
         Ok(())
     }
 
-    fn spawn_audio_pumps(&self, channel: Arc<dyn MediaChannel>) {
-        let inbound_session = self.clone();
-        let inbound_channel = Arc::clone(&channel);
-        let inbound = tokio::spawn(async move {
-            loop {
-                match inbound_channel.read_frame().await {
-                    Ok(frame) => {
-                        if inbound_session
-                            .inner
-                            .audio
-                            .push_inbound_frame(frame)
-                            .await
-                            .is_err()
-                        {
+    /// Move channel frames into the audio stream, through the jitter buffer and resampler.
+    fn spawn_inbound_pump(
+        &self,
+        channel: Arc<dyn MediaChannel>,
+        jitter: Option<Arc<JitterBuffer>>,
+        mut resampler: Option<Resampler>,
+    ) {
+        let session = self.clone();
+        let mut convert = move |frame: crate::MediaFrame| match resampler.as_mut() {
+            Some(resampler) => resampler.convert(&frame),
+            None => vec![frame],
+        };
+        let Some(jitter) = jitter else {
+            let inbound = tokio::spawn(async move {
+                loop {
+                    match channel.read_frame().await {
+                        Ok(frame) => {
+                            for frame in convert(frame) {
+                                if session.inner.audio.push_inbound_frame(frame).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Err(crate::Error::Closed) => return,
+                        Err(error) => {
+                            session.request_failure(format!("audio read: {error}"));
                             return;
                         }
                     }
-                    Err(crate::Error::Closed) => return,
+                }
+            });
+            mutex_lock(&self.inner.media_tasks).push(inbound);
+            return;
+        };
+        *mutex_lock(&self.inner.jitter) = Some(Arc::clone(&jitter));
+        let admitted = Arc::clone(&jitter);
+        let inbound = tokio::spawn(async move {
+            loop {
+                match channel.read_frame().await {
+                    Ok(frame) => admitted.insert(frame),
+                    Err(crate::Error::Closed) => break,
                     Err(error) => {
-                        inbound_session.request_failure(format!("audio read: {error}"));
-                        return;
+                        session.request_failure(format!("audio read: {error}"));
+                        break;
                     }
                 }
             }
+            admitted.close();
+        });
+        let audio = Arc::clone(&self.inner.audio);
+        let playout = tokio::spawn(async move {
+            let _ = jitter.play(&audio, convert).await;
+            jitter.close();
         });
-        let outbound_session = self.clone();
+        mutex_lock(&self.inner.media_tasks).extend([inbound, playout]);
+    }
+
+    /// Move application audio to the channel, paced and resampled when configured.
+    fn spawn_outbound_pump(
+        &self,
+        channel: Arc<dyn MediaChannel>,
+        mut pacer: Option<Pacer>,
+        mut resampler: Option<Resampler>,
+    ) {
+        if let Some(pacer) = &pacer {
+            *mutex_lock(&self.inner.pacer) = Some(pacer.counters());
+        }
+        let session = self.clone();
         let outbound = tokio::spawn(async move {
+            let audio = &session.inner.audio;
             loop {
-                match outbound_session.inner.audio.read_outbound_frame().await {
-                    Ok(data) => {
-                        if let Err(error) =
-                            channel.write_frame(crate::MediaFrame::untimed(data)).await
-                        {
-                            if !matches!(error, crate::Error::Closed) {
-                                outbound_session.request_failure(format!("audio write: {error}"));
-                            }
-                            return;
+                let next = match pacer.as_mut() {
+                    Some(pacer) => pacer.next_frame(audio).await,
+                    None => audio.read_outbound_frame().await.map(Some),
+                };
+                let frames = match next {
+                    Ok(None) => continue,
+                    Ok(Some(data)) => {
+                        audio.tap_outbound_frame(&data);
+                        match resampler.as_mut() {
+                            Some(resampler) => resampler.convert_frames(&data),
+                            None => vec![data],
                         }
                     }
                     Err(crate::Error::Closed) => return,
                     Err(error) => {
-                        outbound_session.request_failure(format!("audio buffer: {error}"));
+                        session.request_failure(format!("audio buffer: {error}"));
+                        return;
+                    }
+                };
+                for data in frames {
+                    if let Err(error) = channel.write_frame(crate::MediaFrame::untimed(data)).await
+                    {
+                        if !matches!(error, crate::Error::Closed) {
+                            session.request_failure(format!("audio write: {error}"));
+                        }
                         return;
                     }
                 }
             }
         });
-        mutex_lock(&self.inner.media_tasks).extend([inbound, outbound]);
+        mutex_lock(&self.inner.media_tasks).push(outbound);
     }
 }
 
@@ -1027,6 +1212,30 @@ impl HandlerContext {
             .map(|session| Arc::clone(&session.audio))
     }
 
+    /// Sample the session transport's statistics, like [`Session::stats`].
+    pub async fn stats(&self) -> Option<TransportStats> {
+        let (session, _) = self.attached().ok()?;
+        session.stats().await
+    }
+
+    /// Return the session's jitter-buffer counters, like [`Session::jitter_stats`].
+    #[must_use]
+    pub fn jitter_stats(&self) -> Option<JitterBufferStats> {
+        self.attached().ok()?.0.jitter_stats()
+    }
+
+    /// Return the session's outbound pacing counters, like [`Session::pacer_stats`].
+    #[must_use]
+    pub fn pacer_stats(&self) -> Option<PacerStats> {
+        self.attached().ok()?.0.pacer_stats()
+    }
+
+    /// Return the session transport's in-band DTMF channel, like [`Session::dtmf`].
+    #[must_use]
+    pub fn dtmf(&self) -> Option<Arc<dyn DtmfChannel>> {
+        self.attached().ok()?.0.dtmf()
+    }
+
     /// Issue a generated typed nested request.
     ///
     /// # Errors
@@ -1039,6 +1248,25 @@ impl HandlerContext {
         crate::request_peer(self, request).await
     }
 
+    /// Write a generated typed request without awaiting its response, then request graceful
+    /// shutdown after [`SessionConfig::terminal_close_grace`].
+    ///
+    /// # Errors
+    ///
+    /// Returns detached-context, validation, encoding, or transport failures.
+    pub async fn request_then_close<Q: NamedRequest>(
+        &self,
+        request: Q,
+    ) -> Result<(), crate::Error> {
+        request.validate()?;
+        let payload = serde_json::to_value(request).map_err(crate::Error::envelope)?;
+        let session = self.attached()?.0;
+        session.send_request(Q::METHOD, payload).await?;
+        tokio::time::sleep(session.inner.config.terminal_close_grace).await;
+        session.request_close();
+        Ok(())
+    }
+
     /// Emit a generated typed event.
     ///
     /// # Errors
@@ -73,24 +73,29 @@ impl MediaFormat {
     ///
     /// # Errors
     ///
-    /// Returns [`crate::Error::InvalidMediaFormat`] unless the format is fixed-width L16 with a
-    /// positive, integral sample count and a representable byte length.
+    /// Returns [`crate::Error::InvalidMediaFormat`] unless the format is 16-bit L16 or 8-bit
+    /// G.711 (`PCMU` or `PCMA`) with a positive, integral sample count and a representable byte
+    /// length.
     pub fn frame_bytes(&self) -> Result<usize, crate::Error> {
-        if self.encoding != "L16" {
-            return Err(crate::Error::InvalidMediaFormat(format!(
-                "unsupported byte-audio encoding {:?}",
-                self.encoding
-            )));
-        }
+        let bit_depth = match self.encoding.as_str() {
+            "L16" => 16,
+            "PCMU" | "PCMA" => 8,
+            _ => {
+                return Err(crate::Error::InvalidMediaFormat(format!(
+                    "unsupported byte-audio encoding {:?}",
+                    self.encoding
+                )));
+            }
+        };
         if self.sample_rate == 0 {
             return Err(crate::Error::InvalidMediaFormat(
                 "sample rate must be positive".to_owned(),
             ));
         }
-        if self.bit_depth != 16 {
+        if self.bit_depth != bit_depth {
             return Err(crate::Error::InvalidMediaFormat(format!(
-                "L16 bit depth must be 16, got {}",
-                self.bit_depth
+                "{} bit depth must be {bit_depth}, got {}",
+                self.encoding, self.bit_depth
             )));
         }
         if self.channels == 0 {
@@ -126,6 +131,22 @@ impl MediaFormat {
             })?;
         Ok(bytes)
     }
+
+    /// Return one packetization interval of silence in this encoding.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`MediaFormat::frame_bytes`].
+    pub fn silence(&self) -> Result<Vec<u8>, crate::Error> {
+        let size = self.frame_bytes()?;
+        let byte = match self.encoding.as_str() {
+            // G.711 encodes zero amplitude as a non-zero code.
+            "PCMU" => 0xff,
+            "PCMA" => 0xd5,
+            _ => 0,
+        };
+        Ok(vec![byte; size])
+    }
 }
 
 /// One transport media frame. `pts` is absent when the transport has no media clock.
@@ -142,6 +163,26 @@ impl MediaFrame {
     }
 }
 
+/// One point-in-time sample of transport health. Transports leave unmeasured values `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransportStats {
+    /// Selected ICE candidate pair, as reported by the WebRTC stack.
+    pub candidate_pair: Option<String>,
+    /// Latest round-trip time: ICE connectivity checks on WebRTC, keepalive Ping/Pong on WebSocket.
+    pub rtt: Option<Duration>,
+    /// Interarrival jitter of received media, per RFC 3550.
+    pub jitter: Option<Duration>,
+    pub packets_received: Option<u64>,
+    /// Received media packets missing from the sequence; negative after duplicates.
+    pub packets_lost: Option<i64>,
+    pub bytes_sent: Option<u64>,
+    pub bytes_received: Option<u64>,
+    pub nacks_sent: Option<u64>,
+    pub nacks_received: Option<u64>,
+    /// Bytes admitted for sending but not yet written to the socket.
+    pub queued_bytes: Option<u64>,
+}
+
 /// Opaque envelope-byte channel.
 #[async_trait]
 pub trait ControlChannel: Send + Sync {
@@ -188,6 +229,33 @@ pub trait MediaChannel: Send + Sync {
     async fn close(&self) -> Result<(), crate::Error>;
 }
 
+/// One DTMF digit the peer pressed and released, timed on the local wall clock.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DtmfDigit {
+    /// `0`-`9`, `*`, `#`, or `A`-`D`.
+    pub digit: String,
+    pub pressed_at: SystemTime,
+    pub released_at: SystemTime,
+}
+
+/// Telephone-event digits carried in-band beside a transport's audio, such as RFC 4733 RTP events.
+#[async_trait]
+pub trait DtmfChannel: Send + Sync {
+    /// Receive the next digit the peer released.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::Closed`] after the audio stream ends.
+    async fn read_dtmf(&self) -> Result<DtmfDigit, crate::Error>;
+
+    /// Send one digit (`0`-`9`, `*`, `#`, or `A`-`D`) held for `duration`.
+    ///
+    /// # Errors
+    ///
+    /// Returns invalid-digit, unnegotiated-event, channel-closure, or transport failures.
+    async fn send_dtmf(&self, digit: &str, duration: Duration) -> Result<(), crate::Error>;
+}
+
 /// One control channel plus zero or more media channels.
 #[async_trait]
 pub trait Transport: Send + Sync {
@@ -233,6 +301,16 @@ pub trait Transport: Send + Sync {
             "transport does not support keepalive".to_owned(),
         ))
     }
+
+    /// Sample current transport statistics. The default measures nothing.
+    async fn stats(&self) -> TransportStats {
+        TransportStats::default()
+    }
+
+    /// Return the in-band DTMF channel, when this transport's media carries telephone events.
+    fn dtmf(&self) -> Option<Arc<dyn DtmfChannel>> {
+        None
+    }
 }
 
 /// Async constructor for one transport instance.
@@ -304,6 +382,10 @@ mod tests {
             },
             MediaFormat {
                 ptime: Duration::from_nanos(1),
+                ..valid.clone()
+            },
+            MediaFormat {
+                encoding: "PCMU".to_owned(),
                 ..valid
             },
         ];
@@ -313,4 +395,22 @@ mod tests {
                 .all(|format| format.frame_bytes().is_err())
         );
     }
+
+    #[test]
+    fn g711_frames_carry_one_byte_per_sample_and_encode_silence() {
+        let mu_law = MediaFormat {
+            encoding: "PCMU".to_owned(),
+            sample_rate: 8_000,
+            bit_depth: 8,
+            channels: 1,
+            ptime: Duration::from_millis(20),
+        };
+        assert_eq!(mu_law.frame_bytes().unwrap(), 160);
+        assert_eq!(mu_law.silence().unwrap(), vec![0xff; 160]);
+        let a_law = MediaFormat {
+            encoding: "PCMA".to_owned(),
+            ..mu_law
+        };
+        assert_eq!(a_law.silence().unwrap(), vec![0xd5; 160]);
+    }
 }
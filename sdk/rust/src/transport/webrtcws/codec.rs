@@ -1,3 +1,11 @@
+use opus_rs::{Application, OpusDecoder, OpusEncoder};
+
+use crate::MediaFormat;
+
+/// The largest Opus packet duration, 120 ms, in samples per channel at 48 kHz.
+const MAX_OPUS_FRAME: usize = 5_760;
+const MAX_OPUS_PACKET: usize = 1_275;
+
 pub(super) fn encode_pcmu(pcm: &[u8]) -> Vec<u8> {
     pcm.chunks_exact(2)
         .map(|sample| linear_to_mu_law(i16::from_le_bytes([sample[0], sample[1]])))
@@ -11,6 +19,55 @@ pub(super) fn decode_pcmu(encoded: &[u8]) -> Vec<u8> {
         .collect()
 }
 
+pub(super) fn encode_pcma(pcm: &[u8]) -> Vec<u8> {
+    pcm.chunks_exact(2)
+        .map(|sample| linear_to_a_law(i16::from_le_bytes([sample[0], sample[1]])))
+        .collect()
+}
+
+pub(super) fn decode_pcma(encoded: &[u8]) -> Vec<u8> {
+    encoded
+        .iter()
+        .flat_map(|sample| a_law_to_linear(*sample).to_le_bytes())
+        .collect()
+}
+
+/// Sample encoding of the SDK-side audio, transcoded through L16 to the negotiated RTP codec.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum SampleEncoding {
+    L16,
+    Pcmu,
+    Pcma,
+}
+
+impl SampleEncoding {
+    pub(super) fn of(format: &MediaFormat) -> Self {
+        match format.encoding.as_str() {
+            "PCMU" => Self::Pcmu,
+            "PCMA" => Self::Pcma,
+            _ => Self::L16,
+        }
+    }
+
+    /// Convert SDK-side audio to L16.
+    pub(super) fn decode(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::L16 => data.to_vec(),
+            Self::Pcmu => decode_pcmu(data),
+            Self::Pcma => decode_pcma(data),
+        }
+    }
+
+    /// Convert L16 to SDK-side audio.
+    pub(super) fn encode(self, pcm: Vec<u8>) -> Vec<u8> {
+        match self {
+            Self::L16 => pcm,
+            Self::Pcmu => encode_pcmu(&pcm),
+            Self::Pcma => encode_pcma(&pcm),
+        }
+    }
+}
+
 fn linear_to_mu_law(sample: i16) -> u8 {
     const BIAS: i32 = 0x84;
     const CLIP: i32 = 32_635;
@@ -45,6 +102,122 @@ fn mu_law_to_linear(encoded: u8) -> i16 {
     i16::try_from(linear).unwrap_or(if linear < 0 { i16::MIN } else { i16::MAX })
 }
 
+fn linear_to_a_law(sample: i16) -> u8 {
+    // G.711 A-law quantizes the 13 most significant bits.
+    let mut value = i32::from(sample) >> 3;
+    let mask = if value >= 0 {
+        0xd5
+    } else {
+        value = -value - 1;
+        0x55
+    };
+    let Some(segment) = (0..8_u8).find(|segment| value < (0x20 << segment)) else {
+        return 0x7f ^ mask;
+    };
+    let shift = if segment < 2 { 1 } else { segment };
+    let mantissa = u8::try_from((value >> shift) & 0x0f).unwrap_or(0);
+    ((segment << 4) | mantissa) ^ mask
+}
+
+fn a_law_to_linear(encoded: u8) -> i16 {
+    let value = encoded ^ 0x55;
+    let mut magnitude = i32::from(value & 0x0f) << 4;
+    let segment = (value & 0x70) >> 4;
+    magnitude += if segment == 0 { 8 } else { 0x108 };
+    if segment > 1 {
+        magnitude <<= segment - 1;
+    }
+    let linear = if value & 0x80 != 0 {
+        magnitude
+    } else {
+        -magnitude
+    };
+    saturate(linear)
+}
+
+pub(super) fn opus_encoder(sample_rate: u32) -> Result<OpusEncoder, crate::Error> {
+    let rate = i32::try_from(sample_rate)
+        .map_err(|_| crate::Error::InvalidMediaFormat(format!("Opus rate {sample_rate}")))?;
+    OpusEncoder::new(rate, 1, Application::Voip).map_err(opus_error)
+}
+
+pub(super) fn encode_opus(encoder: &mut OpusEncoder, pcm: &[u8]) -> Result<Vec<u8>, crate::Error> {
+    let samples: Vec<_> = samples(pcm).collect();
+    let mut packet = vec![0; MAX_OPUS_PACKET];
+    let length = encoder
+        .encode_i16(&samples, samples.len(), &mut packet)
+        .map_err(opus_error)?;
+    packet.truncate(length);
+    Ok(packet)
+}
+
+/// Decode Opus for the mono SDK boundary, creating the decoder for the first packet's channels.
+pub(super) struct OpusReceiver {
+    sample_rate: u32,
+    decoder: Option<(Box<OpusDecoder>, usize)>,
+}
+
+impl OpusReceiver {
+    pub(super) const fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            decoder: None,
+        }
+    }
+
+    pub(super) fn decode(&mut self, packet: &[u8]) -> Result<Vec<u8>, crate::Error> {
+        let Some(toc) = packet.first() else {
+            return Ok(Vec::new());
+        };
+        let channels = if toc & 0x04 == 0 { 1 } else { 2 };
+        if self
+            .decoder
+            .as_ref()
+            .is_none_or(|(_, existing)| *existing != channels)
+        {
+            let rate = i32::try_from(self.sample_rate).map_err(|_| {
+                crate::Error::InvalidMediaFormat(format!("Opus rate {}", self.sample_rate))
+            })?;
+            self.decoder = Some((
+                Box::new(OpusDecoder::new(rate, channels).map_err(opus_error)?),
+                channels,
+            ));
+        }
+        let Some((decoder, channels)) = self.decoder.as_mut() else {
+            return Ok(Vec::new());
+        };
+        let capacity = MAX_OPUS_FRAME * usize::try_from(self.sample_rate).unwrap_or(0) / 48_000;
+        let mut pcm = vec![0.0; capacity * *channels];
+        let count = decoder
+            .decode(packet, capacity, &mut pcm)
+            .map_err(opus_error)?;
+        let scale = if *channels == 2 { 0.5 } else { 1.0 };
+        Ok(pcm[..count * *channels]
+            .chunks_exact(*channels)
+            .flat_map(|frame| float_to_linear(frame.iter().sum::<f32>() * scale).to_le_bytes())
+            .collect())
+    }
+}
+
+fn samples(pcm: &[u8]) -> impl Iterator<Item = i16> + '_ {
+    pcm.chunks_exact(2)
+        .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+}
+
+fn saturate(sample: i32) -> i16 {
+    i16::try_from(sample).unwrap_or(if sample < 0 { i16::MIN } else { i16::MAX })
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn float_to_linear(sample: f32) -> i16 {
+    // The clamp keeps the float inside the i16 range before the saturating cast.
+    (sample * 32_768.0).round().clamp(-32_768.0, 32_767.0) as i16
+}
+
+fn opus_error(error: &str) -> crate::Error {
+    crate::Error::Transport(format!("Opus: {error}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,4 +238,49 @@ mod tests {
             .collect();
         assert_eq!(round_trip, [-32_124, -9_852, -988, 0, 988, 9_852, 32_124]);
     }
+
+    #[test]
+    fn a_law_matches_g711_reference_points() {
+        let linear = [i16::MIN, -10_000, -1_000, 0, 1_000, 10_000, i16::MAX];
+        let pcm: Vec<_> = linear
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        let encoded = encode_pcma(&pcm);
+        assert_eq!(encoded, [42, 54, 122, 213, 250, 182, 170]);
+        let round_trip: Vec<_> = samples(&decode_pcma(&encoded)).collect();
+        assert_eq!(
+            round_trip,
+            [-32_256, -9_984, -1_008, 8, 1_008, 9_984, 32_256]
+        );
+
+        // Transcoding between the G.711 laws goes through L16.
+        let mu_law = SampleEncoding::Pcmu.encode(SampleEncoding::Pcma.decode(&encoded));
+        assert_eq!(mu_law, encode_pcmu(&decode_pcma(&encoded)));
+        assert_eq!(SampleEncoding::L16.decode(&pcm), pcm);
+    }
+
+    #[test]
+    fn opus_round_trips_a_wideband_tone_at_the_sdk_rate() {
+        let mut encoder = opus_encoder(16_000).unwrap();
+        let mut receiver = OpusReceiver::new(16_000);
+        let mut energy = 0_i64;
+        for frame in 0_u16..10 {
+            let pcm: Vec<_> = (0_u16..320)
+                .flat_map(|index| {
+                    let time = f32::from(frame * 320 + index) / 16_000.0;
+                    float_to_linear((time * 440.0 * std::f32::consts::TAU).sin() * 0.25)
+                        .to_le_bytes()
+                })
+                .collect();
+            let packet = encode_opus(&mut encoder, &pcm).unwrap();
+            assert!(packet.len() < pcm.len());
+            let decoded = receiver.decode(&packet).unwrap();
+            assert_eq!(decoded.len(), 640);
+            energy += samples(&decoded)
+                .map(|sample| i64::from(sample).abs())
+                .sum::<i64>();
+        }
+        assert!(energy > 0);
+    }
 }
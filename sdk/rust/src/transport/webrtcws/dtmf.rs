@@ -0,0 +1,210 @@
+//! RFC 4733 telephone-event payloads carrying DTMF digits.
+
+use std::time::{Duration, SystemTime};
+
+use crate::DtmfDigit;
+
+/// Digits indexed by their RFC 4733 event code.
+const DIGITS: &[u8; 16] = b"0123456789*#ABCD";
+/// Volume sent with every event, in -dBm0; RFC 4733 leaves the choice to the sender.
+pub(super) const VOLUME: u8 = 10;
+
+/// Return the event code for one DTMF digit.
+pub(super) fn event_code(digit: &str) -> Option<u8> {
+    let [digit] = digit.as_bytes() else {
+        return None;
+    };
+    DIGITS
+        .iter()
+        .position(|candidate| candidate.eq_ignore_ascii_case(digit))
+        .and_then(|code| u8::try_from(code).ok())
+}
+
+fn digit(code: u8) -> Option<String> {
+    DIGITS
+        .get(usize::from(code))
+        .map(|digit| char::from(*digit).to_string())
+}
+
+/// One telephone-event payload: event code, end flag, volume, and duration in RTP clock units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct EventPayload {
+    pub(super) code: u8,
+    pub(super) end: bool,
+    pub(super) volume: u8,
+    pub(super) duration: u16,
+}
+
+impl EventPayload {
+    pub(super) fn marshal(self) -> [u8; 4] {
+        let [high, low] = self.duration.to_be_bytes();
+        let end = if self.end { 0x80 } else { 0 };
+        [self.code, end | (self.volume & 0x3f), high, low]
+    }
+
+    fn parse(payload: &[u8]) -> Option<Self> {
+        let [code, flags, high, low, ..] = *payload else {
+            return None;
+        };
+        Some(Self {
+            code,
+            end: flags & 0x80 != 0,
+            volume: flags & 0x3f,
+            duration: u16::from_be_bytes([high, low]),
+        })
+    }
+}
+
+/// Collapse the update and end packets of each received event into one released digit.
+#[derive(Default)]
+pub(super) struct EventReceiver {
+    current: Option<PendingEvent>,
+    ended: Option<u32>,
+}
+
+struct PendingEvent {
+    timestamp: u32,
+    code: u8,
+    pressed_at: SystemTime,
+    duration: u16,
+    clock_rate: u32,
+}
+
+impl EventReceiver {
+    /// Account for one telephone-event packet and return the digits it completes.
+    ///
+    /// A new event timestamp completes an event whose end packets were all lost.
+    pub(super) fn receive(
+        &mut self,
+        timestamp: u32,
+        payload: &[u8],
+        clock_rate: u32,
+    ) -> Vec<DtmfDigit> {
+        let Some(payload) = EventPayload::parse(payload) else {
+            return Vec::new();
+        };
+        if self.ended == Some(timestamp) {
+            // Retransmitted end packet of a released digit.
+            return Vec::new();
+        }
+        let mut released = Vec::new();
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|current| current.timestamp != timestamp)
+        {
+            released.extend(self.release());
+        }
+        let current = self.current.get_or_insert_with(|| PendingEvent {
+            timestamp,
+            code: payload.code,
+            // The first packet to arrive may already report part of the press.
+            pressed_at: SystemTime::now() - held(payload.duration, clock_rate),
+            duration: 0,
+            clock_rate,
+        });
+        current.duration = current.duration.max(payload.duration);
+        if payload.end {
+            self.ended = Some(timestamp);
+            released.extend(self.release());
+        }
+        released
+    }
+
+    fn release(&mut self) -> Option<DtmfDigit> {
+        let event = self.current.take()?;
+        Some(DtmfDigit {
+            digit: digit(event.code)?,
+            pressed_at: event.pressed_at,
+            released_at: event.pressed_at + held(event.duration, event.clock_rate),
+        })
+    }
+}
+
+/// Convert a hold time to RTP clock units, or `None` when one event cannot carry it.
+pub(super) fn duration_ticks(duration: Duration, clock_rate: u32) -> Option<u16> {
+    let ticks = duration.as_nanos() * u128::from(clock_rate) / 1_000_000_000;
+    u16::try_from(ticks).ok().filter(|ticks| *ticks > 0)
+}
+
+fn held(duration: u16, clock_rate: u32) -> Duration {
+    Duration::from_nanos(u64::from(duration) * 1_000_000_000 / u64::from(clock_rate.max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(code: u8, end: bool, duration: u16) -> [u8; 4] {
+        EventPayload {
+            code,
+            end,
+            volume: VOLUME,
+            duration,
+        }
+        .marshal()
+    }
+
+    #[test]
+    fn digits_map_to_rfc_4733_event_codes() {
+        assert_eq!(event_code("0"), Some(0));
+        assert_eq!(event_code("9"), Some(9));
+        assert_eq!(event_code("*"), Some(10));
+        assert_eq!(event_code("#"), Some(11));
+        assert_eq!(event_code("d"), Some(15));
+        assert_eq!(event_code("E"), None);
+        assert_eq!(event_code("12"), None);
+        assert_eq!(
+            EventPayload::parse(&packet(11, true, 800)),
+            Some(EventPayload {
+                code: 11,
+                end: true,
+                volume: VOLUME,
+                duration: 800,
+            })
+        );
+        assert_eq!(duration_ticks(Duration::from_millis(100), 8_000), Some(800));
+        assert_eq!(duration_ticks(Duration::from_secs(2), 48_000), None);
+    }
+
+    #[test]
+    fn updates_and_repeated_ends_release_each_digit_once() {
+        let mut receiver = EventReceiver::default();
+        assert!(
+            receiver
+                .receive(1_000, &packet(5, false, 160), 8_000)
+                .is_empty()
+        );
+        assert!(
+            receiver
+                .receive(1_000, &packet(5, false, 320), 8_000)
+                .is_empty()
+        );
+        let released = receiver.receive(1_000, &packet(5, true, 800), 8_000);
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].digit, "5");
+        assert_eq!(
+            released[0]
+                .released_at
+                .duration_since(released[0].pressed_at)
+                .unwrap(),
+            Duration::from_millis(100)
+        );
+        assert!(
+            receiver
+                .receive(1_000, &packet(5, true, 800), 8_000)
+                .is_empty()
+        );
+
+        // A lost end is recovered when the next event starts.
+        assert!(
+            receiver
+                .receive(9_000, &packet(11, false, 160), 8_000)
+                .is_empty()
+        );
+        let released = receiver.receive(12_000, &packet(10, true, 480), 8_000);
+        let digits: Vec<_> = released.iter().map(|event| event.digit.as_str()).collect();
+        assert_eq!(digits, ["#", "*"]);
+        assert!(receiver.receive(12_000, &[10], 8_000).is_empty());
+    }
+}
@@ -1,17 +1,21 @@
 use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use opus_rs::OpusEncoder;
 use tokio::sync::Notify;
-use webrtc::media::Sample;
+use webrtc::api::media_engine::MIME_TYPE_TELEPHONE_EVENT;
 use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
-use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
 use webrtc::track::track_remote::TrackRemote;
 
-use super::{PCMU_CLOCK_RATE, codec};
-use crate::{MediaChannel, MediaFormat, MediaFrame};
+use super::codec::{self, OpusReceiver, SampleEncoding};
+use super::dtmf::{self, EventReceiver};
+use super::track::NegotiatedTrack;
+use super::{Codec, PCMU_CLOCK_RATE, SDK_PTIME};
+use crate::audio::{Resampler, ResamplerQuality};
+use crate::{DtmfChannel, DtmfDigit, MediaChannel, MediaFormat, MediaFrame};
 
 #[derive(Clone, Debug)]
 enum Terminal {
@@ -20,22 +24,39 @@ enum Terminal {
 }
 
 pub(super) struct WebRtcMedia {
-    track: Arc<TrackLocalStaticSample>,
+    track: Arc<NegotiatedTrack>,
+    codecs: Vec<Codec>,
     format: OnceLock<MediaFormat>,
-    incoming: FrameInbox,
+    encoder: Mutex<Option<OpusEncoder>>,
+    /// Sent audio resampled to the PCMU clock, with filter state carried across frames.
+    downsampler: Mutex<Option<Resampler>>,
+    reception: Mutex<Reception>,
+    incoming: Inbox<MediaFrame>,
+    digits: Inbox<DtmfDigit>,
+    suspended: AtomicBool,
     closed: AtomicBool,
 }
 
 impl WebRtcMedia {
-    pub(super) fn new(track: Arc<TrackLocalStaticSample>, format: Option<MediaFormat>) -> Self {
+    pub(super) fn new(
+        track: Arc<NegotiatedTrack>,
+        codecs: Vec<Codec>,
+        format: Option<MediaFormat>,
+    ) -> Self {
         let selected = OnceLock::new();
         if let Some(format) = format {
             let _ = selected.set(format);
         }
         Self {
             track,
+            codecs,
             format: selected,
-            incoming: FrameInbox::new(),
+            encoder: Mutex::new(None),
+            downsampler: Mutex::new(None),
+            reception: Mutex::new(Reception::default()),
+            incoming: Inbox::new(),
+            digits: Inbox::new(),
+            suspended: AtomicBool::new(false),
             closed: AtomicBool::new(false),
         }
     }
@@ -59,14 +80,38 @@ impl WebRtcMedia {
         self.format.get().cloned()
     }
 
+    pub(super) fn codec(&self) -> Option<Codec> {
+        self.track.codec()
+    }
+
+    /// Return received packets, sequence losses, and RFC 3550 jitter since the first packet.
+    pub(super) fn reception(&self) -> Option<(u64, i64, Duration)> {
+        self.reception
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .summary()
+    }
+
+    /// Drop outbound frames, like lost packets, while ICE recovers.
+    pub(super) fn suspend(&self) {
+        self.suspended.store(true, Ordering::Release);
+    }
+
+    pub(super) fn resume(&self) {
+        self.suspended.store(false, Ordering::Release);
+    }
+
     pub(super) fn fail(&self, message: impl Into<String>) {
         if !self.closed.swap(true, Ordering::AcqRel) {
-            self.incoming.close(Terminal::Failed(message.into()));
+            let terminal = Terminal::Failed(message.into());
+            self.digits.close(terminal.clone());
+            self.incoming.close(terminal);
         }
     }
 
     pub(super) fn finish_orderly(&self) {
         if !self.closed.swap(true, Ordering::AcqRel) {
+            self.digits.close(Terminal::Orderly);
             self.incoming.close(Terminal::Orderly);
         }
     }
@@ -77,41 +122,56 @@ impl WebRtcMedia {
             return;
         }
         let mut first_timestamp = None;
-        let mut codec_checked = false;
+        let mut decoder = None;
+        let mut events = EventReceiver::default();
         loop {
             match track.read_rtp().await {
                 Ok((packet, _)) => {
-                    if !codec_checked {
-                        let capability = track.codec().capability;
-                        if packet.header.payload_type != 0
-                            || (!capability.mime_type.is_empty()
-                                && (!capability.mime_type.eq_ignore_ascii_case("audio/PCMU")
-                                    || capability.clock_rate != PCMU_CLOCK_RATE
-                                    || capability.channels != 1))
-                        {
-                            self.fail(format!(
-                                "unexpected WebRTC codec payload={} {}/{}",
-                                packet.header.payload_type,
-                                capability.mime_type,
-                                capability.clock_rate
-                            ));
-                            return;
+                    // The remote track follows each packet's payload type.
+                    let capability = track.codec().capability;
+                    if capability
+                        .mime_type
+                        .eq_ignore_ascii_case(MIME_TYPE_TELEPHONE_EVENT)
+                    {
+                        self.record(&packet.header, None);
+                        for digit in events.receive(
+                            packet.header.timestamp,
+                            &packet.payload,
+                            capability.clock_rate,
+                        ) {
+                            if self.digits.push(digit).is_err() {
+                                return;
+                            }
                         }
-                        codec_checked = true;
+                        continue;
                     }
+                    let (codec, decoder) = match &mut decoder {
+                        Some(decoder) => decoder,
+                        None => match self.remote_codec(&track, packet.header.payload_type) {
+                            Ok(codec) => decoder.insert(codec),
+                            Err(message) => {
+                                self.fail(message);
+                                return;
+                            }
+                        },
+                    };
+                    self.record(&packet.header, Some(codec.clock_rate()));
                     let first = *first_timestamp.get_or_insert(packet.header.timestamp);
-                    let ticks = packet.header.timestamp.wrapping_sub(first);
-                    let nanos =
-                        u64::from(ticks).saturating_mul(1_000_000_000) / u64::from(PCMU_CLOCK_RATE);
-                    if self
-                        .incoming
-                        .push(MediaFrame {
-                            data: codec::decode_pcmu(&packet.payload),
-                            pts: Some(Duration::from_nanos(nanos)),
-                        })
-                        .is_err()
-                    {
-                        return;
+                    let Some(pts) = media_time(first, packet.header.timestamp, codec.clock_rate())
+                    else {
+                        continue;
+                    };
+                    let frames = match decoder.decode(&packet.payload, pts) {
+                        Ok(frames) => frames,
+                        Err(error) => {
+                            self.fail(format!("decode WebRTC {codec:?} audio: {error}"));
+                            return;
+                        }
+                    };
+                    for frame in frames {
+                        if self.incoming.push(frame).is_err() {
+                            return;
+                        }
                     }
                 }
                 Err(error) => {
@@ -125,6 +185,240 @@ impl WebRtcMedia {
             }
         }
     }
+
+    /// Count one received packet; only audio packets carry a media clock for jitter.
+    fn record(&self, header: &webrtc::rtp::header::Header, clock_rate: Option<u32>) {
+        self.reception
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .record(
+                header.sequence_number,
+                clock_rate.map(|clock_rate| (header.timestamp, clock_rate)),
+            );
+    }
+
+    /// Match the first packet to a configured codec and prepare its decoder.
+    fn remote_codec(
+        &self,
+        track: &TrackRemote,
+        payload_type: u8,
+    ) -> Result<(Codec, Decoder), String> {
+        let capability = track.codec().capability;
+        let codec = if capability.mime_type.is_empty() {
+            self.codecs
+                .iter()
+                .copied()
+                .find(|codec| codec.payload_type() == payload_type)
+        } else {
+            Codec::from_mime(&capability.mime_type)
+                .filter(|codec| capability.clock_rate == codec.clock_rate())
+        };
+        let Some(codec) = codec.filter(|codec| self.codecs.contains(codec)) else {
+            return Err(format!(
+                "unexpected WebRTC codec payload={payload_type} {}/{}",
+                capability.mime_type, capability.clock_rate
+            ));
+        };
+        let format = self.selected_format();
+        let sample_rate = format
+            .as_ref()
+            .map_or(PCMU_CLOCK_RATE, |format| format.sample_rate);
+        let ptime = format.as_ref().map_or(SDK_PTIME, |format| format.ptime);
+        let encoding = format
+            .as_ref()
+            .map_or(SampleEncoding::L16, SampleEncoding::of);
+        let decoder = match codec {
+            Codec::Opus => Decoder::Opus(OpusReceiver::new(sample_rate), encoding),
+            Codec::Pcmu => Decoder::Pcmu {
+                upsampler: (sample_rate != PCMU_CLOCK_RATE)
+                    .then(|| resampler(PCMU_CLOCK_RATE, sample_rate, ptime))
+                    .transpose()
+                    .map_err(|error| error.to_string())?,
+                encoding,
+            },
+        };
+        Ok((codec, decoder))
+    }
+
+    /// Encode one SDK frame into the packets it completes for the negotiated codec, transcoding
+    /// through L16 when they differ. Resampling to PCMU holds back the filter's lookahead, so the
+    /// first frame may complete no packet.
+    fn encode(&self, format: &MediaFormat, data: &[u8]) -> Result<Vec<Vec<u8>>, crate::Error> {
+        let encoding = SampleEncoding::of(format);
+        let codec = self.track.codec();
+        if encoding == SampleEncoding::Pcmu && codec != Some(Codec::Opus) {
+            return Ok(vec![data.to_vec()]);
+        }
+        let pcm = encoding.decode(data);
+        match codec {
+            Some(Codec::Opus) => {
+                let mut encoder = self
+                    .encoder
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                let encoder = match encoder.as_mut() {
+                    Some(encoder) => encoder,
+                    None => encoder.insert(codec::opus_encoder(format.sample_rate)?),
+                };
+                Ok(vec![codec::encode_opus(encoder, &pcm)?])
+            }
+            Some(Codec::Pcmu) | None if format.sample_rate == PCMU_CLOCK_RATE => {
+                Ok(vec![codec::encode_pcmu(&pcm)])
+            }
+            Some(Codec::Pcmu) | None => {
+                let mut downsampler = self
+                    .downsampler
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                let downsampler = match downsampler.as_mut() {
+                    Some(downsampler) => downsampler,
+                    None => downsampler.insert(resampler(
+                        format.sample_rate,
+                        PCMU_CLOCK_RATE,
+                        format.ptime,
+                    )?),
+                };
+                Ok(downsampler
+                    .convert_frames(&pcm)
+                    .iter()
+                    .map(|pcm| codec::encode_pcmu(pcm))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Decode received RTP audio into the selected SDK encoding.
+enum Decoder {
+    Opus(OpusReceiver, SampleEncoding),
+    Pcmu {
+        /// Raises 8 kHz audio to a wideband SDK rate, with filter state carried across packets.
+        upsampler: Option<Resampler>,
+        encoding: SampleEncoding,
+    },
+}
+
+impl Decoder {
+    /// Decode one packet timed at `pts` into the SDK frames it completes.
+    fn decode(&mut self, payload: &[u8], pts: Duration) -> Result<Vec<MediaFrame>, crate::Error> {
+        let data = match self {
+            Self::Opus(receiver, encoding) => encoding.encode(receiver.decode(payload)?),
+            Self::Pcmu {
+                encoding: SampleEncoding::Pcmu,
+                ..
+            } => payload.to_vec(),
+            Self::Pcmu {
+                upsampler: Some(upsampler),
+                encoding,
+            } => {
+                let frame = MediaFrame {
+                    data: codec::decode_pcmu(payload),
+                    pts: Some(pts),
+                };
+                return Ok(upsampler
+                    .convert(&frame)
+                    .into_iter()
+                    .map(|frame| MediaFrame {
+                        data: encoding.encode(frame.data),
+                        pts: frame.pts,
+                    })
+                    .collect());
+            }
+            Self::Pcmu {
+                upsampler: None,
+                encoding,
+            } => encoding.encode(codec::decode_pcmu(payload)),
+        };
+        Ok(vec![MediaFrame {
+            data,
+            pts: Some(pts),
+        }])
+    }
+}
+
+/// RFC 3550 receiver accounting with the integer jitter estimator from its appendix A.8.
+#[derive(Default)]
+struct Reception {
+    started: Option<Instant>,
+    clock_rate: u32,
+    received: u64,
+    base_sequence: i64,
+    highest_sequence: i64,
+    /// Arrival time in RTP timestamp units and RTP timestamp of the last audio packet.
+    last_timing: Option<(i64, u32)>,
+    /// Jitter in RTP timestamp units, scaled by 16.
+    jitter: i64,
+}
+
+impl Reception {
+    /// Account for one packet; `timing` is its RTP timestamp and clock rate when it carries audio.
+    fn record(&mut self, sequence: u16, timing: Option<(u32, u32)>) {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let sequence = i64::from(sequence);
+        if self.received == 0 {
+            self.base_sequence = sequence;
+            self.highest_sequence = sequence;
+        } else {
+            // Extend the 16-bit sequence around the highest one seen so far.
+            let cycle = self.highest_sequence & !0xffff;
+            let mut extended = cycle | sequence;
+            if extended < self.highest_sequence - 0x8000 {
+                extended += 0x1_0000;
+            } else if extended > self.highest_sequence + 0x8000 {
+                extended -= 0x1_0000;
+            }
+            self.highest_sequence = self.highest_sequence.max(extended);
+        }
+        self.received += 1;
+        let Some((timestamp, clock_rate)) = timing else {
+            return;
+        };
+        self.clock_rate = clock_rate;
+        let arrival = started.elapsed().as_nanos() * u128::from(clock_rate) / 1_000_000_000;
+        let arrival = i64::try_from(arrival).unwrap_or(i64::MAX);
+        if let Some((last_arrival, last_timestamp)) = self.last_timing.replace((arrival, timestamp))
+        {
+            // The transit delta spaces timestamps wrap-aware, as their 32 bits roll over mid-call.
+            let spaced = i64::from(timestamp.wrapping_sub(last_timestamp).cast_signed());
+            let delta = (arrival - last_arrival - spaced).abs();
+            self.jitter += delta - ((self.jitter + 8) >> 4);
+        }
+    }
+
+    fn summary(&self) -> Option<(u64, i64, Duration)> {
+        if self.received == 0 {
+            return None;
+        }
+        let expected = self.highest_sequence - self.base_sequence + 1;
+        let lost = expected - i64::try_from(self.received).unwrap_or(i64::MAX);
+        let ticks = u64::try_from(self.jitter >> 4).unwrap_or(0);
+        let jitter = Duration::from_nanos(
+            ticks.saturating_mul(1_000_000_000) / u64::from(self.clock_rate.max(1)),
+        );
+        Some((self.received, lost, jitter))
+    }
+}
+
+/// Return the media time of `timestamp` since the first packet's, or `None` for a packet sent
+/// before the first one arrived, which is already late.
+fn media_time(first: u32, timestamp: u32, clock_rate: u32) -> Option<Duration> {
+    // A signed difference keeps a reordered packet from wrapping to nearly 2^32 ticks ahead.
+    let ticks = u64::try_from(timestamp.wrapping_sub(first).cast_signed()).ok()?;
+    let nanos = ticks.saturating_mul(1_000_000_000) / u64::from(clock_rate);
+    Some(Duration::from_nanos(nanos))
+}
+
+/// Build a streaming mono L16 resampler between two rates, grouping its output into frames of
+/// `ptime`.
+fn resampler(from: u32, to: u32, ptime: Duration) -> Result<Resampler, crate::Error> {
+    let format = |sample_rate| MediaFormat {
+        encoding: "L16".to_owned(),
+        sample_rate,
+        bit_depth: 16,
+        channels: 1,
+        ptime,
+    };
+    Resampler::new(&format(from), &format(to), ResamplerQuality::default())
 }
 
 #[async_trait]
@@ -149,18 +443,22 @@ impl MediaChannel for WebRtcMedia {
         let wanted = format.frame_bytes()?;
         if frame.data.len() != wanted {
             return Err(crate::Error::InvalidMediaFormat(format!(
-                "L16 frame has {} bytes, want {wanted}",
+                "{} frame has {} bytes, want {wanted}",
+                format.encoding,
                 frame.data.len()
             )));
         }
-        self.track
-            .write_sample(&Sample {
-                data: codec::encode_pcmu(&frame.data).into(),
-                duration: format.ptime,
-                ..Default::default()
-            })
-            .await
-            .map_err(|error| crate::Error::Transport(error.to_string()))
+        if self.suspended.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        for packet in self.encode(&format, &frame.data)? {
+            match self.track.write_audio(packet, format.ptime).await {
+                Ok(()) => {}
+                Err(_) if self.suspended.load(Ordering::Acquire) => return Ok(()),
+                Err(error) => return Err(crate::Error::Transport(error.to_string())),
+            }
+        }
+        Ok(())
     }
 
     async fn read_frame(&self) -> Result<MediaFrame, crate::Error> {
@@ -173,20 +471,40 @@ impl MediaChannel for WebRtcMedia {
     }
 }
 
-struct FrameInbox {
-    state: Mutex<FrameState>,
+#[async_trait]
+impl DtmfChannel for WebRtcMedia {
+    async fn read_dtmf(&self) -> Result<DtmfDigit, crate::Error> {
+        self.digits.pop().await
+    }
+
+    async fn send_dtmf(&self, digit: &str, duration: Duration) -> Result<(), crate::Error> {
+        let code = dtmf::event_code(digit).ok_or_else(|| {
+            crate::ValidationError::new(format!("unsupported DTMF digit {digit:?}"))
+        })?;
+        if self.closed.load(Ordering::Acquire) {
+            return Err(crate::Error::Closed);
+        }
+        let ptime = self
+            .selected_format()
+            .map_or(SDK_PTIME, |format| format.ptime);
+        self.track.write_event(code, duration, ptime).await
+    }
+}
+
+struct Inbox<T> {
+    state: Mutex<InboxState<T>>,
     ready: Notify,
 }
 
-struct FrameState {
-    frames: VecDeque<MediaFrame>,
+struct InboxState<T> {
+    frames: VecDeque<T>,
     terminal: Option<Terminal>,
 }
 
-impl FrameInbox {
+impl<T> Inbox<T> {
     fn new() -> Self {
         Self {
-            state: Mutex::new(FrameState {
+            state: Mutex::new(InboxState {
                 frames: VecDeque::new(),
                 terminal: None,
             }),
@@ -194,7 +512,7 @@ impl FrameInbox {
         }
     }
 
-    fn push(&self, frame: MediaFrame) -> Result<(), crate::Error> {
+    fn push(&self, frame: T) -> Result<(), crate::Error> {
         let mut state = self
             .state
             .lock()
@@ -208,7 +526,7 @@ impl FrameInbox {
         Ok(())
     }
 
-    async fn pop(&self) -> Result<MediaFrame, crate::Error> {
+    async fn pop(&self) -> Result<T, crate::Error> {
         loop {
             let notified = self.ready.notified();
             {
@@ -242,3 +560,79 @@ impl FrameInbox {
         self.ready.notify_waiters();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_packet_sent_before_the_first_one_is_late_not_far_ahead() {
+        // The first two packets arrive swapped around a timestamp wrap.
+        let first = u32::MAX - 79;
+        assert_eq!(
+            media_time(first, first.wrapping_sub(160), PCMU_CLOCK_RATE),
+            None
+        );
+        assert_eq!(
+            media_time(first, first.wrapping_add(160), PCMU_CLOCK_RATE),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            media_time(first, first, PCMU_CLOCK_RATE),
+            Some(Duration::ZERO)
+        );
+    }
+
+    /// Sample `index` of a 400 Hz tone at `sample_rate`.
+    fn tone(index: u32, sample_rate: u32) -> f64 {
+        let time = f64::from(index) / f64::from(sample_rate);
+        (time * 400.0 * std::f64::consts::TAU).sin() * 8_000.0
+    }
+
+    #[test]
+    fn narrowband_audio_is_resampled_continuously_across_packets() {
+        let mut decoder = Decoder::Pcmu {
+            upsampler: Some(resampler(PCMU_CLOCK_RATE, 16_000, SDK_PTIME).unwrap()),
+            encoding: SampleEncoding::L16,
+        };
+        let mut frames = Vec::new();
+        for packet in 0_u32..4 {
+            let pcm: Vec<_> = (0..160)
+                .flat_map(|index| {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let sample = tone(packet * 160 + index, PCMU_CLOCK_RATE) as i16;
+                    sample.to_le_bytes()
+                })
+                .collect();
+            let payload = codec::encode_pcmu(&pcm);
+            frames.extend(decoder.decode(&payload, SDK_PTIME * packet).unwrap());
+        }
+        // The filter's lookahead delays output by one frame, each timed at its source audio.
+        assert_eq!(frames.len(), 3);
+        for (frame, index) in frames.iter().zip(0_u32..) {
+            assert_eq!(frame.data.len(), 640);
+            assert_eq!(frame.pts, Some(SDK_PTIME * index));
+        }
+        // Each output sample follows the source tone at its own time, across packet boundaries.
+        let error = frames
+            .iter()
+            .flat_map(|frame| frame.data.chunks_exact(2))
+            .map(|sample| f64::from(i16::from_le_bytes([sample[0], sample[1]])))
+            .zip(0_u32..)
+            .skip(64)
+            .map(|(sample, index)| (sample - tone(index, 16_000)).abs())
+            .fold(0.0, f64::max);
+        assert!(error < 400.0, "error {error}");
+    }
+
+    #[test]
+    fn jitter_ignores_a_timestamp_wrap() {
+        let mut reception = Reception::default();
+        reception.record(1, Some((u32::MAX - 79, PCMU_CLOCK_RATE)));
+        reception.record(2, Some((80, PCMU_CLOCK_RATE)));
+        let (received, lost, jitter) = reception.summary().unwrap();
+        assert_eq!((received, lost), (2, 0));
+        // Only the real arrival spacing against 20 ms counts, never the 2^32-tick wrap.
+        assert!(jitter < Duration::from_millis(10));
+    }
+}
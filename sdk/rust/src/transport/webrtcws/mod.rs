@@ -1,6 +1,6 @@
-//! `rtvbp.webrtc.v1`: WebSocket control plus one duplex PCMU WebRTC audio stream.
+//! `rtvbp.webrtc.v1`: WebSocket control plus one duplex Opus or PCMU WebRTC audio stream.
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
@@ -8,7 +8,10 @@ use async_trait::async_trait;
 use tokio::sync::Notify;
 use webrtc::api::APIBuilder;
 use webrtc::api::interceptor_registry::register_default_interceptors;
-use webrtc::api::media_engine::{MIME_TYPE_PCMU, MediaEngine};
+use webrtc::api::media_engine::{
+    MIME_TYPE_OPUS, MIME_TYPE_PCMU, MIME_TYPE_TELEPHONE_EVENT, MediaEngine,
+};
+use webrtc::ice::candidate::CandidatePairState;
 use webrtc::interceptor::registry::Registry;
 use webrtc::peer_connection::RTCPeerConnection;
 use webrtc::peer_connection::configuration::RTCConfiguration;
@@ -18,25 +21,89 @@ use webrtc::rtp_transceiver::rtp_codec::{
     RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType,
 };
 use webrtc::rtp_transceiver::rtp_transceiver_direction::RTCRtpTransceiverDirection;
+use webrtc::stats::StatsReportType;
 use webrtc::track::track_local::TrackLocal;
-use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
 
 use super::ws;
 use crate::{
-    ControlChannel, Envelope, KeepalivePolicy, MediaChannel, MediaFormat, Transport,
-    TransportFactory,
+    ControlChannel, DtmfChannel, Envelope, KeepalivePolicy, MediaChannel, MediaFormat, Transport,
+    TransportFactory, TransportStats,
 };
 
 mod codec;
+mod dtmf;
 mod media;
 mod signaling;
+mod track;
 
 use media::WebRtcMedia;
+use signaling::Signaling;
+use track::NegotiatedTrack;
 
 /// WebSocket profile token for WebRTC media plus RTVBP control.
 pub const SUBPROTOCOL: &str = crate::profile::PROFILE_RTVBP_WEBRTC_V1;
 const PCMU_CLOCK_RATE: u32 = 8_000;
-const PCMU_PTIME: Duration = Duration::from_millis(20);
+const OPUS_CLOCK_RATE: u32 = 48_000;
+const SDK_PTIME: Duration = Duration::from_millis(20);
+const SDK_SAMPLE_RATES: [u32; 3] = [8_000, 16_000, 48_000];
+
+/// RTP audio codec negotiated in SDP and transcoded to the SDK format at the media-channel
+/// boundary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    /// Opus at 48 kHz RTP clock, coded directly at the SDK sample rate.
+    Opus,
+    /// G.711 μ-law at 8 kHz, resampled to the SDK sample rate.
+    Pcmu,
+}
+
+impl Codec {
+    fn capability(self) -> RTCRtpCodecCapability {
+        match self {
+            Self::Opus => RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_OPUS.to_owned(),
+                clock_rate: OPUS_CLOCK_RATE,
+                channels: 2,
+                sdp_fmtp_line: "minptime=10;useinbandfec=1".to_owned(),
+                ..Default::default()
+            },
+            Self::Pcmu => RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_PCMU.to_owned(),
+                clock_rate: PCMU_CLOCK_RATE,
+                channels: 1,
+                ..Default::default()
+            },
+        }
+    }
+
+    const fn payload_type(self) -> u8 {
+        match self {
+            Self::Opus => 111,
+            Self::Pcmu => 0,
+        }
+    }
+
+    /// Telephone-event payload type registered at this codec's clock rate.
+    const fn event_payload_type(self) -> u8 {
+        match self {
+            Self::Opus => 110,
+            Self::Pcmu => 101,
+        }
+    }
+
+    const fn clock_rate(self) -> u32 {
+        match self {
+            Self::Opus => OPUS_CLOCK_RATE,
+            Self::Pcmu => PCMU_CLOCK_RATE,
+        }
+    }
+
+    fn from_mime(mime_type: &str) -> Option<Self> {
+        [Self::Opus, Self::Pcmu]
+            .into_iter()
+            .find(|codec| codec.capability().mime_type.eq_ignore_ascii_case(mime_type))
+    }
+}
 
 /// WebRTC peer and SDK-boundary audio configuration.
 #[derive(Clone)]
@@ -44,6 +111,28 @@ pub struct Config {
     pub peer_connection: RTCConfiguration,
     pub audio_format: Option<MediaFormat>,
     pub negotiation_timeout: Duration,
+    /// Send the SDP before ICE gathering completes and stream local candidates as
+    /// `transport.webrtc.candidate` events.
+    ///
+    /// Remote candidate events are always accepted. Leave this disabled when the peer may be an
+    /// SDK that expects complete non-trickle SDP, such as the published Go SDK.
+    pub trickle_ice: bool,
+    /// ICE losses tolerated after the first connection before the transport fails.
+    ///
+    /// The offering peer spends one per restart offer sent after `Disconnected` or `Failed`;
+    /// the answering peer spends one per `Failed` transition while it waits for that offer.
+    /// Zero keeps the published Go SDK behavior: ICE failure ends media permanently.
+    pub ice_restarts: u32,
+    /// Codecs offered or accepted in SDP, most preferred first.
+    ///
+    /// Both peers send the offerer's first codec that the answerer also lists. The default
+    /// PCMU-only list matches the published Go SDK; Opus keeps 16 or 48 kHz SDK audio wideband.
+    pub codecs: Vec<Codec>,
+    /// Negotiate RFC 4733 `telephone-event` and expose DTMF through [`Transport::dtmf`].
+    ///
+    /// Events are registered at 8 kHz, plus 48 kHz when Opus is listed, and sent only at the
+    /// negotiated audio codec's clock rate.
+    pub telephone_events: bool,
 }
 
 impl Default for Config {
@@ -52,6 +141,10 @@ impl Default for Config {
             peer_connection: RTCConfiguration::default(),
             audio_format: None,
             negotiation_timeout: Duration::from_secs(10),
+            trickle_ice: false,
+            ice_restarts: 0,
+            codecs: vec![Codec::Pcmu],
+            telephone_events: false,
         }
     }
 }
@@ -61,6 +154,21 @@ impl Config {
         if let Some(format) = &self.audio_format {
             validate_format(format)?;
         }
+        if self.codecs.is_empty() {
+            return Err(crate::Error::Configuration(
+                "WebRTC codec list must not be empty".to_owned(),
+            ));
+        }
+        if self
+            .codecs
+            .iter()
+            .enumerate()
+            .any(|(index, codec)| self.codecs[..index].contains(codec))
+        {
+            return Err(crate::Error::Configuration(
+                "WebRTC codec list must not repeat a codec".to_owned(),
+            ));
+        }
         if self.negotiation_timeout.is_zero() {
             return Err(crate::Error::Configuration(
                 "WebRTC negotiation timeout must be positive".to_owned(),
@@ -89,43 +197,56 @@ impl TransportFactory for ClientFactory {
         &self,
         envelope: Arc<dyn Envelope>,
     ) -> Result<Arc<dyn Transport>, crate::Error> {
-        let mut websocket = self.websocket.clone();
-        if websocket.subprotocols.is_none() {
-            websocket.subprotocols = Some(vec![SUBPROTOCOL.to_owned()]);
-        }
-        let mut peer_config = self.config.clone();
-        if peer_config.audio_format.is_none() {
-            peer_config.audio_format = Some(websocket.audio_format.clone());
-        }
-        let base = ws::connect(websocket).await?;
-        let mut construction = ConstructionGuard::new(Arc::clone(&base) as Arc<dyn Transport>);
-        if base.wire_subprotocol() != SUBPROTOCOL {
-            let selected = base.wire_subprotocol().to_owned();
-            let _ = base.close().await;
+        connect(self.websocket.clone(), envelope, self.config.clone())
+            .await
+            .map(|transport| transport as Arc<dyn Transport>)
+    }
+}
+
+/// Dial a WebSocket offering `rtvbp.webrtc.v1` and negotiate WebRTC as the offering peer.
+///
+/// # Errors
+///
+/// Returns profile, configuration, connection, construction, timeout, or SDP negotiation failures.
+pub async fn connect(
+    mut websocket: ws::ClientConfig,
+    envelope: Arc<dyn Envelope>,
+    mut config: Config,
+) -> Result<Arc<WebRtcTransport>, crate::Error> {
+    if websocket.subprotocols.is_none() {
+        websocket.subprotocols = Some(vec![SUBPROTOCOL.to_owned()]);
+    }
+    if config.audio_format.is_none() {
+        config.audio_format = Some(websocket.audio_format.clone());
+    }
+    let base = ws::connect(websocket).await?;
+    let mut construction = ConstructionGuard::new(Arc::clone(&base) as Arc<dyn Transport>);
+    if base.wire_subprotocol() != SUBPROTOCOL {
+        let selected = base.wire_subprotocol().to_owned();
+        let _ = base.close().await;
+        construction.disarm();
+        return Err(crate::Error::UnsupportedSubprotocol(format!(
+            "selected {selected:?}, want {SUBPROTOCOL:?}"
+        )));
+    }
+    let timeout = config.negotiation_timeout;
+    let transport = WebRtcTransport::new(base, envelope, config, true).await?;
+    construction.replace(Arc::clone(&transport) as Arc<dyn Transport>);
+    let negotiation = transport.signaling.negotiate_offer();
+    match tokio::time::timeout(timeout, negotiation).await {
+        Ok(Ok(())) => {
             construction.disarm();
-            return Err(crate::Error::UnsupportedSubprotocol(format!(
-                "selected {selected:?}, want {SUBPROTOCOL:?}"
-            )));
-        }
-        let transport = WebRtcTransport::new(base, peer_config).await?;
-        construction.replace(Arc::clone(&transport) as Arc<dyn Transport>);
-        let negotiation =
-            signaling::negotiate_offer(transport.control(), envelope, transport.peer.as_ref());
-        match tokio::time::timeout(self.config.negotiation_timeout, negotiation).await {
-            Ok(Ok(())) => {
-                construction.disarm();
-                Ok(transport as Arc<dyn Transport>)
-            }
-            Ok(Err(error)) => {
-                let _ = transport.close().await;
-                construction.disarm();
-                Err(error)
-            }
-            Err(_) => {
-                let _ = transport.close().await;
-                construction.disarm();
-                Err(crate::Error::Timeout)
-            }
+            Ok(transport)
+        }
+        Ok(Err(error)) => {
+            let _ = transport.close().await;
+            construction.disarm();
+            Err(error)
+        }
+        Err(_) => {
+            let _ = transport.close().await;
+            construction.disarm();
+            Err(crate::Error::Timeout)
         }
     }
 }
@@ -148,10 +269,9 @@ pub async fn accept(
         )));
     }
     let timeout = config.negotiation_timeout;
-    let transport = WebRtcTransport::new(base, config).await?;
+    let transport = WebRtcTransport::new(base, envelope, config, false).await?;
     construction.replace(Arc::clone(&transport) as Arc<dyn Transport>);
-    let negotiation =
-        signaling::negotiate_answer(transport.control(), envelope, transport.peer.as_ref());
+    let negotiation = transport.signaling.negotiate_answer();
     match tokio::time::timeout(timeout, negotiation).await {
         Ok(Ok(())) => {
             construction.disarm();
@@ -186,27 +306,28 @@ pub fn add_to_server(mut config: ws::ServerConfig) -> ws::ServerConfig {
 pub struct WebRtcTransport {
     base: Arc<ws::WsTransport>,
     peer: Arc<RTCPeerConnection>,
+    signaling: Arc<Signaling>,
     media: Arc<WebRtcMedia>,
     connection: Mutex<Option<Result<(), String>>>,
     connection_changed: Notify,
+    offerer: bool,
+    negotiation_timeout: Duration,
+    restarts: AtomicU32,
+    restarting: AtomicBool,
+    telephone_events: bool,
     claimed: AtomicBool,
     closed: AtomicBool,
 }
 
 impl WebRtcTransport {
-    async fn new(base: Arc<ws::WsTransport>, config: Config) -> Result<Arc<Self>, crate::Error> {
+    async fn new(
+        base: Arc<ws::WsTransport>,
+        envelope: Arc<dyn Envelope>,
+        config: Config,
+        offerer: bool,
+    ) -> Result<Arc<Self>, crate::Error> {
         config.validate()?;
-        let mut media_engine = MediaEngine::default();
-        media_engine
-            .register_codec(
-                RTCRtpCodecParameters {
-                    capability: pcmu_capability(),
-                    payload_type: 0,
-                    ..Default::default()
-                },
-                RTPCodecType::Audio,
-            )
-            .map_err(transport_error)?;
+        let mut media_engine = register_codecs(&config)?;
         let registry = register_default_interceptors(Registry::new(), &mut media_engine)
             .map_err(transport_error)?;
         let api = APIBuilder::new()
@@ -218,10 +339,9 @@ impl WebRtcTransport {
                 .await
                 .map_err(transport_error)?,
         );
-        let track = Arc::new(TrackLocalStaticSample::new(
-            pcmu_capability(),
-            "audio".to_owned(),
-            "rtvbp".to_owned(),
+        let track = Arc::new(NegotiatedTrack::new(
+            config.codecs.clone(),
+            config.telephone_events,
         ));
         let transceiver = peer
             .add_transceiver_from_track(
@@ -236,13 +356,25 @@ impl WebRtcTransport {
         let sender = transceiver.sender().await;
         tokio::spawn(async move { while sender.read_rtcp().await.is_ok() {} });
 
-        let media = Arc::new(WebRtcMedia::new(track, config.audio_format));
+        let signaling = Signaling::start(
+            base.control(),
+            envelope,
+            Arc::clone(&peer),
+            config.trickle_ice,
+        );
+        let media = Arc::new(WebRtcMedia::new(track, config.codecs, config.audio_format));
         let transport = Arc::new(Self {
             base,
             peer: Arc::clone(&peer),
+            signaling,
             media: Arc::clone(&media),
             connection: Mutex::new(None),
             connection_changed: Notify::new(),
+            offerer,
+            negotiation_timeout: config.negotiation_timeout,
+            restarts: AtomicU32::new(config.ice_restarts),
+            restarting: AtomicBool::new(false),
+            telephone_events: config.telephone_events,
             claimed: AtomicBool::new(false),
             closed: AtomicBool::new(false),
         });
@@ -290,13 +422,53 @@ impl WebRtcTransport {
             .map(|description| description.sdp)
     }
 
-    fn handle_connection_state(&self, state: RTCPeerConnectionState) {
+    /// Return the RTP codec bound for outbound audio, when SDP negotiation completed.
+    #[must_use]
+    pub fn codec(&self) -> Option<Codec> {
+        self.media.codec()
+    }
+
+    /// Restart ICE with fresh credentials over the control channel, keeping the media channel.
+    ///
+    /// Use this after a known network change. Only the offering peer restarts, and manual
+    /// restarts do not spend [`Config::ice_restarts`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error on the answering peer or while a restart is in progress,
+    /// [`crate::Error::Closed`] after close, and timeout or signaling failures.
+    pub async fn restart_ice(&self) -> Result<(), crate::Error> {
+        if !self.offerer {
+            return Err(crate::Error::Configuration(
+                "only the offering WebRTC peer restarts ICE".to_owned(),
+            ));
+        }
+        if self.closed.load(Ordering::Acquire) {
+            return Err(crate::Error::Closed);
+        }
+        if self.restarting.swap(true, Ordering::AcqRel) {
+            return Err(crate::Error::Configuration(
+                "WebRTC ICE restart is already in progress".to_owned(),
+            ));
+        }
+        let result = self.negotiate_restart().await;
+        self.restarting.store(false, Ordering::Release);
+        result
+    }
+
+    fn handle_connection_state(self: &Arc<Self>, state: RTCPeerConnectionState) {
         match state {
-            RTCPeerConnectionState::Connected => self.set_connection(Ok(())),
-            RTCPeerConnectionState::Failed => {
-                self.set_connection(Err("WebRTC peer connection failed".to_owned()));
-                self.media.fail("WebRTC peer connection failed");
+            RTCPeerConnectionState::Connected => {
+                self.set_connection(Ok(()));
+                self.media.resume();
+            }
+            RTCPeerConnectionState::Disconnected if self.is_connected() => {
+                self.media.suspend();
+                if self.offerer {
+                    self.start_restart();
+                }
             }
+            RTCPeerConnectionState::Failed if !self.recover_failure() => self.fail_connection(),
             RTCPeerConnectionState::Closed => {
                 self.set_connection(Err("WebRTC peer connection closed".to_owned()));
                 self.media.finish_orderly();
@@ -305,6 +477,84 @@ impl WebRtcTransport {
         }
     }
 
+    /// Keep media suspended across a failure while the restart budget allows it.
+    fn recover_failure(self: &Arc<Self>) -> bool {
+        if !self.is_connected() || self.closed.load(Ordering::Acquire) {
+            return false;
+        }
+        self.media.suspend();
+        if self.offerer {
+            self.restarting.load(Ordering::Acquire) || self.start_restart()
+        } else if self.take_restart() {
+            self.await_restart_offer();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Fail the answering peer unless the offerer's restart offer arrives in time.
+    fn await_restart_offer(self: &Arc<Self>) {
+        let answered = self.signaling.restarts_answered();
+        let transport = Arc::clone(self);
+        tokio::spawn(async move {
+            tokio::time::sleep(transport.negotiation_timeout).await;
+            if transport.signaling.restarts_answered() == answered
+                && !transport.closed.load(Ordering::Acquire)
+            {
+                transport.fail_connection();
+            }
+        });
+    }
+
+    fn fail_connection(&self) {
+        self.set_connection(Err("WebRTC peer connection failed".to_owned()));
+        self.media.fail("WebRTC peer connection failed");
+    }
+
+    fn start_restart(self: &Arc<Self>) -> bool {
+        if self.restarting.swap(true, Ordering::AcqRel) {
+            return true;
+        }
+        if !self.take_restart() {
+            self.restarting.store(false, Ordering::Release);
+            return false;
+        }
+        let transport = Arc::clone(self);
+        tokio::spawn(async move {
+            let result = transport.negotiate_restart().await;
+            transport.restarting.store(false, Ordering::Release);
+            if result.is_err() && !transport.closed.load(Ordering::Acquire) {
+                // Retry, wait, or fail according to where ICE stands now.
+                let state = transport.peer.connection_state();
+                transport.handle_connection_state(state);
+            }
+        });
+        true
+    }
+
+    async fn negotiate_restart(&self) -> Result<(), crate::Error> {
+        self.signaling.restart_offer(self.negotiation_timeout).await
+    }
+
+    fn take_restart(&self) -> bool {
+        self.restarts
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+                remaining.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    fn is_connected(&self) -> bool {
+        matches!(
+            *self
+                .connection
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+            Some(Ok(()))
+        )
+    }
+
     fn set_connection(&self, result: Result<(), String>) {
         let mut state = self
             .connection
@@ -347,7 +597,7 @@ impl WebRtcTransport {
 #[async_trait]
 impl Transport for WebRtcTransport {
     fn control(&self) -> Arc<dyn ControlChannel> {
-        self.base.control()
+        Arc::clone(&self.signaling) as Arc<dyn ControlChannel>
     }
 
     async fn accept_media(&self) -> Result<Arc<dyn MediaChannel>, crate::Error> {
@@ -406,26 +656,118 @@ impl Transport for WebRtcTransport {
     async fn monitor_keepalive(&self, policy: KeepalivePolicy) -> Result<(), crate::Error> {
         self.base.monitor_keepalive(policy).await
     }
+
+    /// Sample the nominated ICE pair and its check RTT, RTP audio counters, and local reception.
+    async fn stats(&self) -> TransportStats {
+        let mut stats = TransportStats::default();
+        let report = self.peer.get_stats().await;
+        // After an ICE restart the previous pair may linger; the live one received last.
+        let selected = report
+            .reports
+            .values()
+            .filter_map(|entry| match entry {
+                StatsReportType::CandidatePair(pair)
+                    if pair.nominated && pair.state == CandidatePairState::Succeeded =>
+                {
+                    Some(pair)
+                }
+                _ => None,
+            })
+            .max_by_key(|pair| pair.last_packet_received_timestamp);
+        if let Some(pair) = selected {
+            stats.rtt = Duration::try_from_secs_f64(pair.current_round_trip_time)
+                .ok()
+                .filter(|rtt| !rtt.is_zero());
+            let candidate = |id: &str| match report.reports.get(id) {
+                Some(
+                    StatsReportType::LocalCandidate(candidate)
+                    | StatsReportType::RemoteCandidate(candidate),
+                ) => Some(format!(
+                    "{} {}:{}",
+                    candidate.candidate_type, candidate.ip, candidate.port
+                )),
+                _ => None,
+            };
+            if let (Some(local), Some(remote)) = (
+                candidate(&pair.local_candidate_id),
+                candidate(&pair.remote_candidate_id),
+            ) {
+                stats.candidate_pair = Some(format!("{local} <-> {remote}"));
+            }
+        }
+        for entry in report.reports.values() {
+            match entry {
+                StatsReportType::InboundRTP(inbound) if inbound.kind == "audio" => {
+                    stats.bytes_received = Some(inbound.bytes_received);
+                    stats.nacks_sent = Some(inbound.nack_count);
+                }
+                StatsReportType::OutboundRTP(outbound) if outbound.kind == "audio" => {
+                    stats.bytes_sent = Some(outbound.bytes_sent);
+                    stats.nacks_received = Some(outbound.nack_count);
+                }
+                _ => {}
+            }
+        }
+        if let Some((received, lost, jitter)) = self.media.reception() {
+            stats.packets_received = Some(received);
+            stats.packets_lost = Some(lost);
+            stats.jitter = Some(jitter);
+        }
+        stats
+    }
+
+    fn dtmf(&self) -> Option<Arc<dyn DtmfChannel>> {
+        self.telephone_events
+            .then(|| Arc::clone(&self.media) as Arc<dyn DtmfChannel>)
+    }
 }
 
-fn pcmu_capability() -> RTCRtpCodecCapability {
-    RTCRtpCodecCapability {
-        mime_type: MIME_TYPE_PCMU.to_owned(),
-        clock_rate: PCMU_CLOCK_RATE,
-        channels: 1,
-        ..Default::default()
+/// Register the configured codecs in preference order, each with its telephone-event when enabled.
+fn register_codecs(config: &Config) -> Result<MediaEngine, crate::Error> {
+    let mut media_engine = MediaEngine::default();
+    for codec in &config.codecs {
+        media_engine
+            .register_codec(
+                RTCRtpCodecParameters {
+                    capability: codec.capability(),
+                    payload_type: codec.payload_type(),
+                    ..Default::default()
+                },
+                RTPCodecType::Audio,
+            )
+            .map_err(transport_error)?;
+        if config.telephone_events {
+            media_engine
+                .register_codec(
+                    RTCRtpCodecParameters {
+                        capability: RTCRtpCodecCapability {
+                            mime_type: MIME_TYPE_TELEPHONE_EVENT.to_owned(),
+                            clock_rate: codec.clock_rate(),
+                            channels: 1,
+                            sdp_fmtp_line: "0-15".to_owned(),
+                            ..Default::default()
+                        },
+                        payload_type: codec.event_payload_type(),
+                        ..Default::default()
+                    },
+                    RTPCodecType::Audio,
+                )
+                .map_err(transport_error)?;
+        }
     }
+    Ok(media_engine)
 }
 
 fn validate_format(format: &MediaFormat) -> Result<(), crate::Error> {
-    if format.encoding != "L16"
-        || format.sample_rate != PCMU_CLOCK_RATE
-        || format.bit_depth != 16
-        || format.channels != 1
-        || format.ptime != PCMU_PTIME
-    {
+    let rate = match format.encoding.as_str() {
+        "L16" => SDK_SAMPLE_RATES.contains(&format.sample_rate),
+        "PCMU" | "PCMA" => format.sample_rate == PCMU_CLOCK_RATE,
+        _ => false,
+    };
+    if !rate || format.channels != 1 || format.ptime != SDK_PTIME {
         return Err(crate::Error::InvalidMediaFormat(format!(
-            "unsupported WebRTC audio format {format:?}; want L16/8000/16-bit/mono/20ms"
+            "unsupported WebRTC audio format {format:?}; want mono 20ms L16 at 8000, 16000, \
+             or 48000 Hz, or PCMU or PCMA at 8000 Hz"
         )));
     }
     format.frame_bytes().map(|_| ())
@@ -473,7 +815,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn only_the_frozen_sdk_media_format_is_accepted() {
+    fn only_mono_l16_and_narrowband_g711_at_supported_rates_are_accepted() {
         let valid = MediaFormat {
             encoding: "L16".to_owned(),
             sample_rate: 8_000,
@@ -482,8 +824,40 @@ mod tests {
             ptime: Duration::from_millis(20),
         };
         assert!(validate_format(&valid).is_ok());
-        let mut changed = valid;
-        changed.sample_rate = 16_000;
+        for rate in [16_000, 48_000] {
+            let mut wideband = valid.clone();
+            wideband.sample_rate = rate;
+            assert!(validate_format(&wideband).is_ok());
+        }
+        let mut changed = valid.clone();
+        changed.sample_rate = 24_000;
+        assert!(validate_format(&changed).is_err());
+        changed = valid.clone();
+        changed.channels = 2;
         assert!(validate_format(&changed).is_err());
+        for encoding in ["PCMU", "PCMA"] {
+            let mut g711 = valid.clone();
+            g711.encoding = encoding.to_owned();
+            g711.bit_depth = 8;
+            assert!(validate_format(&g711).is_ok());
+            g711.sample_rate = 16_000;
+            assert!(validate_format(&g711).is_err());
+        }
+    }
+
+    #[test]
+    fn codec_lists_must_be_non_empty_and_distinct() {
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+        config.codecs = vec![Codec::Opus, Codec::Pcmu, Codec::Opus];
+        assert!(matches!(
+            config.validate(),
+            Err(crate::Error::Configuration(_))
+        ));
+        config.codecs.clear();
+        assert!(matches!(
+            config.validate(),
+            Err(crate::Error::Configuration(_))
+        ));
     }
 }
@@ -1,13 +1,20 @@
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use tokio::sync::{Notify, oneshot};
+use webrtc::ice_transport::ice_candidate::{RTCIceCandidate, RTCIceCandidateInit};
 use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::peer_connection::offer_answer_options::RTCOfferOptions;
 use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
 
-use crate::{ControlChannel, ControlFrame, Envelope, FrameKind};
+use crate::{ControlChannel, ControlFrame, Envelope, FrameKind, Received, WireError};
 
 pub(super) const OFFER_METHOD: &str = crate::profile::SIGNALING_TRANSPORT_WEBRTC_OFFER;
+pub(super) const CANDIDATE_METHOD: &str = crate::profile::SIGNALING_TRANSPORT_WEBRTC_CANDIDATE;
 const MAX_SIGNAL_FRAME_LEN: usize = 1 << 20;
 const MAX_SDP_LEN: usize = 512 << 10;
 static SIGNAL_IDS: AtomicU64 = AtomicU64::new(1);
@@ -17,83 +24,386 @@ struct Description {
     sdp: String,
 }
 
-pub(super) async fn negotiate_offer(
-    control: Arc<dyn ControlChannel>,
+/// Reserved WebRTC signaling on the semantic control channel.
+///
+/// A reader task owns the base control channel: it applies `transport.webrtc.candidate` events,
+/// answers ICE restart offers, and routes restart answers, then delivers every other frame, in
+/// order, through [`ControlChannel::recv`].
+pub(super) struct Signaling {
+    base: Arc<dyn ControlChannel>,
     envelope: Arc<dyn Envelope>,
-    peer: &RTCPeerConnection,
-) -> Result<(), crate::Error> {
-    let offer = peer.create_offer(None).await.map_err(transport_error)?;
-    let mut gathering = peer.gathering_complete_promise().await;
-    peer.set_local_description(offer)
-        .await
-        .map_err(transport_error)?;
-    // The promise completes by either sending or dropping its one-shot sender.
-    let _ = gathering.recv().await;
-    let local = peer
-        .local_description()
-        .await
-        .ok_or_else(|| crate::Error::Transport("local WebRTC offer is missing".to_owned()))?;
-    let payload = encode_description(&local.sdp)?;
-    let id = format!(
-        "webrtc-offer-{}",
-        SIGNAL_IDS.fetch_add(1, Ordering::Relaxed)
-    );
-    let encoded = envelope.encode(&ControlFrame::request(
-        id.clone(),
-        OFFER_METHOD,
-        Some(payload),
-    ))?;
-    validate_signal(&encoded)?;
-    control.send(encoded).await?;
-
-    let received = control.recv().await?;
-    let frame = decode_signal(envelope.as_ref(), &received.data)?;
-    if frame.kind != FrameKind::Response || frame.correlation_id != id {
-        return Err(crate::Error::Transport(format!(
-            "unexpected WebRTC answer kind={:?} correlation={:?}",
-            frame.kind, frame.correlation_id
-        )));
-    }
-    if let Some(error) = frame.error {
-        return Err(crate::Error::Remote(error));
-    }
-    let answer = decode_description(frame.payload)?;
-    peer.set_remote_description(RTCSessionDescription::answer(answer.sdp).map_err(transport_error)?)
-        .await
-        .map_err(transport_error)
+    peer: Arc<RTCPeerConnection>,
+    trickle: bool,
+    incoming: ControlInbox,
+    remote: Mutex<Candidates>,
+    local: tokio::sync::Mutex<Candidates>,
+    negotiated: AtomicBool,
+    answer: Mutex<Option<PendingAnswer>>,
+    /// Restart offers answered successfully, which the answering peer's failure timer watches.
+    restarts_answered: AtomicU64,
 }
 
-pub(super) async fn negotiate_answer(
-    control: Arc<dyn ControlChannel>,
-    envelope: Arc<dyn Envelope>,
-    peer: &RTCPeerConnection,
-) -> Result<(), crate::Error> {
-    let received = control.recv().await?;
-    let frame = decode_signal(envelope.as_ref(), &received.data)?;
-    if frame.kind != FrameKind::Request || frame.method != OFFER_METHOD || frame.id.is_empty() {
-        return Err(crate::Error::Transport(format!(
-            "unexpected WebRTC offer kind={:?} method={:?} id={:?}",
-            frame.kind, frame.method, frame.id
-        )));
-    }
-    let offer = decode_description(frame.payload)?;
-    peer.set_remote_description(RTCSessionDescription::offer(offer.sdp).map_err(transport_error)?)
-        .await
-        .map_err(transport_error)?;
-    let answer = peer.create_answer(None).await.map_err(transport_error)?;
-    let mut gathering = peer.gathering_complete_promise().await;
-    peer.set_local_description(answer)
-        .await
-        .map_err(transport_error)?;
-    let _ = gathering.recv().await;
-    let local = peer
-        .local_description()
+enum Reserved {
+    Candidate(RTCIceCandidateInit),
+    Offer(ControlFrame),
+    Answer(ControlFrame),
+    Invalid,
+}
+
+struct PendingAnswer {
+    id: String,
+    sender: oneshot::Sender<ControlFrame>,
+}
+
+/// Candidates held until the matching session description has been applied or sent.
+#[derive(Default)]
+struct Candidates {
+    described: bool,
+    pending: Vec<RTCIceCandidateInit>,
+}
+
+impl Signaling {
+    pub(super) fn start(
+        base: Arc<dyn ControlChannel>,
+        envelope: Arc<dyn Envelope>,
+        peer: Arc<RTCPeerConnection>,
+        trickle: bool,
+    ) -> Arc<Self> {
+        let signaling = Arc::new(Self {
+            base,
+            envelope,
+            peer,
+            trickle,
+            incoming: ControlInbox::new(),
+            remote: Mutex::new(Candidates::default()),
+            local: tokio::sync::Mutex::new(Candidates::default()),
+            negotiated: AtomicBool::new(false),
+            answer: Mutex::new(None),
+            restarts_answered: AtomicU64::new(0),
+        });
+        if trickle {
+            let weak = Arc::downgrade(&signaling);
+            signaling.peer.on_ice_candidate(Box::new(move |candidate| {
+                let weak = weak.clone();
+                Box::pin(async move {
+                    if let Some(signaling) = weak.upgrade() {
+                        signaling.local_candidate(candidate).await;
+                    }
+                })
+            }));
+        }
+        tokio::spawn(Arc::clone(&signaling).read_control());
+        signaling
+    }
+
+    pub(super) async fn negotiate_offer(&self) -> Result<(), crate::Error> {
+        let id = offer_id();
+        self.send_offer(id.clone(), false).await?;
+        let received = self.recv().await?;
+        let frame = decode_signal(self.envelope.as_ref(), &received.data)?;
+        if frame.kind != FrameKind::Response || frame.correlation_id != id {
+            return Err(crate::Error::Transport(format!(
+                "unexpected WebRTC answer kind={:?} correlation={:?}",
+                frame.kind, frame.correlation_id
+            )));
+        }
+        self.apply_answer(frame).await?;
+        self.negotiated.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Offer fresh ICE credentials mid-session and apply the correlated answer within `timeout`.
+    ///
+    /// The answer is routed by the control reader, so catalog traffic keeps flowing meanwhile.
+    pub(super) async fn restart_offer(&self, timeout: Duration) -> Result<(), crate::Error> {
+        mutex_lock(&self.remote).described = false;
+        let (sender, answered) = oneshot::channel();
+        let id = offer_id();
+        *mutex_lock(&self.answer) = Some(PendingAnswer {
+            id: id.clone(),
+            sender,
+        });
+        // The timeout stays inside so the cleanup below runs however the exchange ends.
+        let result = tokio::time::timeout(timeout, async {
+            self.send_offer(id, true).await?;
+            let frame = answered.await.map_err(|_| crate::Error::Closed)?;
+            self.apply_answer(frame).await
+        })
         .await
-        .ok_or_else(|| crate::Error::Transport("local WebRTC answer is missing".to_owned()))?;
-    let payload = encode_description(&local.sdp)?;
-    let encoded = envelope.encode(&ControlFrame::response(frame.id, Some(payload), None))?;
-    validate_signal(&encoded)?;
-    control.send(encoded).await
+        .unwrap_or(Err(crate::Error::Timeout));
+        mutex_lock(&self.answer).take();
+        if result.is_err() {
+            // The previous remote description stays in effect.
+            self.remote_described().await;
+        }
+        result
+    }
+
+    pub(super) async fn negotiate_answer(&self) -> Result<(), crate::Error> {
+        let received = self.recv().await?;
+        let frame = decode_signal(self.envelope.as_ref(), &received.data)?;
+        if frame.kind != FrameKind::Request || frame.method != OFFER_METHOD || frame.id.is_empty() {
+            return Err(crate::Error::Transport(format!(
+                "unexpected WebRTC offer kind={:?} method={:?} id={:?}",
+                frame.kind, frame.method, frame.id
+            )));
+        }
+        self.answer(frame).await?;
+        self.negotiated.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    async fn send_offer(&self, id: String, ice_restart: bool) -> Result<(), crate::Error> {
+        let offer = self
+            .peer
+            .create_offer(Some(RTCOfferOptions {
+                ice_restart,
+                ..Default::default()
+            }))
+            .await
+            .map_err(transport_error)?;
+        let local = self.set_local_description(offer).await?;
+        let payload = encode_description(&local.sdp)?;
+        self.send(&ControlFrame::request(id, OFFER_METHOD, Some(payload)))
+            .await?;
+        self.local_described().await
+    }
+
+    async fn apply_answer(&self, frame: ControlFrame) -> Result<(), crate::Error> {
+        if let Some(error) = frame.error {
+            return Err(crate::Error::Remote(error));
+        }
+        let answer = decode_description(frame.payload)?;
+        self.peer
+            .set_remote_description(
+                RTCSessionDescription::answer(answer.sdp).map_err(transport_error)?,
+            )
+            .await
+            .map_err(transport_error)?;
+        self.remote_described().await;
+        Ok(())
+    }
+
+    async fn answer(&self, frame: ControlFrame) -> Result<(), crate::Error> {
+        let offer = decode_description(frame.payload)?;
+        self.peer
+            .set_remote_description(
+                RTCSessionDescription::offer(offer.sdp).map_err(transport_error)?,
+            )
+            .await
+            .map_err(transport_error)?;
+        self.remote_described().await;
+        let answer = self
+            .peer
+            .create_answer(None)
+            .await
+            .map_err(transport_error)?;
+        let local = self.set_local_description(answer).await?;
+        let payload = encode_description(&local.sdp)?;
+        self.send(&ControlFrame::response(frame.id, Some(payload), None))
+            .await?;
+        self.local_described().await
+    }
+
+    /// Answer a restart offer received after the initial negotiation.
+    async fn answer_restart(self: Arc<Self>, frame: ControlFrame) {
+        let id = frame.id.clone();
+        match self.answer(frame).await {
+            Ok(()) => {
+                self.restarts_answered.fetch_add(1, Ordering::AcqRel);
+            }
+            Err(error) => {
+                let _ = self
+                    .send(&ControlFrame::response(
+                        id,
+                        None,
+                        Some(WireError {
+                            code: 500,
+                            message: format!("WebRTC ICE restart failed: {error}"),
+                            data: None,
+                        }),
+                    ))
+                    .await;
+            }
+        }
+    }
+
+    /// Return how many restart offers this peer has answered.
+    pub(super) fn restarts_answered(&self) -> u64 {
+        self.restarts_answered.load(Ordering::Acquire)
+    }
+
+    /// Apply a local description and return the SDP to signal.
+    ///
+    /// Without trickle ICE this waits until the description carries every candidate. With it, the
+    /// description is signaled as created and candidates follow as events.
+    async fn set_local_description(
+        &self,
+        description: RTCSessionDescription,
+    ) -> Result<RTCSessionDescription, crate::Error> {
+        self.local.lock().await.described = false;
+        if self.trickle {
+            self.peer
+                .set_local_description(description.clone())
+                .await
+                .map_err(transport_error)?;
+            return Ok(description);
+        }
+        let mut gathering = self.peer.gathering_complete_promise().await;
+        self.peer
+            .set_local_description(description)
+            .await
+            .map_err(transport_error)?;
+        // The promise completes by either sending or dropping its one-shot sender.
+        let _ = gathering.recv().await;
+        self.peer.local_description().await.ok_or_else(|| {
+            crate::Error::Transport("local WebRTC description is missing".to_owned())
+        })
+    }
+
+    async fn send(&self, frame: &ControlFrame) -> Result<(), crate::Error> {
+        let encoded = self.envelope.encode(frame)?;
+        validate_signal(&encoded)?;
+        self.base.send(encoded).await
+    }
+
+    async fn read_control(self: Arc<Self>) {
+        loop {
+            match self.base.recv().await {
+                Ok(received) => match self.reserved(&received.data) {
+                    Some(Reserved::Candidate(candidate)) => self.remote_candidate(candidate).await,
+                    Some(Reserved::Offer(frame)) => {
+                        mutex_lock(&self.remote).described = false;
+                        tokio::spawn(Arc::clone(&self).answer_restart(frame));
+                    }
+                    Some(Reserved::Answer(frame)) => {
+                        if let Some(pending) = mutex_lock(&self.answer).take() {
+                            let _ = pending.sender.send(frame);
+                        }
+                    }
+                    // A malformed reserved event is consumed and never reaches catalog dispatch.
+                    Some(Reserved::Invalid) => {}
+                    None => {
+                        if self.incoming.push(received).is_err() {
+                            return;
+                        }
+                    }
+                },
+                Err(error) => {
+                    mutex_lock(&self.answer).take();
+                    self.incoming.close(error);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Classify a frame the transport consumes instead of delivering it to the session.
+    fn reserved(&self, encoded: &[u8]) -> Option<Reserved> {
+        let negotiated = self.negotiated.load(Ordering::Acquire);
+        let answer_id = mutex_lock(&self.answer)
+            .as_ref()
+            .map(|pending| pending.id.clone());
+        let mentions = |needle: &str| {
+            let needle = needle.as_bytes();
+            encoded.windows(needle.len()).any(|window| window == needle)
+        };
+        let candidate = mentions(CANDIDATE_METHOD);
+        let offer = negotiated && mentions(OFFER_METHOD);
+        let answer = answer_id.as_deref().is_some_and(mentions);
+        if !(candidate || offer || answer) {
+            return None;
+        }
+        let frame = decode_signal(self.envelope.as_ref(), encoded).ok()?;
+        match frame.kind {
+            FrameKind::Event if frame.method == CANDIDATE_METHOD => {
+                Some(decode_candidate(frame.payload).map_or(Reserved::Invalid, Reserved::Candidate))
+            }
+            FrameKind::Request if negotiated && frame.method == OFFER_METHOD => {
+                Some(Reserved::Offer(frame))
+            }
+            FrameKind::Response if answer_id.as_deref() == Some(frame.correlation_id.as_str()) => {
+                Some(Reserved::Answer(frame))
+            }
+            _ => None,
+        }
+    }
+
+    async fn remote_candidate(&self, candidate: RTCIceCandidateInit) {
+        {
+            let mut remote = mutex_lock(&self.remote);
+            if !remote.described {
+                remote.pending.push(candidate);
+                return;
+            }
+        }
+        // Unusable remote candidates are skipped exactly like unreachable ones.
+        let _ = self.peer.add_ice_candidate(candidate).await;
+    }
+
+    async fn remote_described(&self) {
+        let pending = {
+            let mut remote = mutex_lock(&self.remote);
+            remote.described = true;
+            std::mem::take(&mut remote.pending)
+        };
+        for candidate in pending {
+            let _ = self.peer.add_ice_candidate(candidate).await;
+        }
+    }
+
+    async fn local_candidate(&self, candidate: Option<RTCIceCandidate>) {
+        // End-of-candidates is signaled with an empty candidate, as in the W3C API.
+        let init = match candidate.map(|candidate| candidate.to_json()) {
+            Some(Ok(init)) => init,
+            Some(Err(_)) => return,
+            None => RTCIceCandidateInit::default(),
+        };
+        let mut local = self.local.lock().await;
+        if local.described {
+            // A failed send means control is closing, which ends the transport anyway.
+            let _ = self.send_candidate(init).await;
+        } else {
+            local.pending.push(init);
+        }
+    }
+
+    async fn local_described(&self) -> Result<(), crate::Error> {
+        let mut local = self.local.lock().await;
+        for candidate in std::mem::take(&mut local.pending) {
+            self.send_candidate(candidate).await?;
+        }
+        local.described = true;
+        Ok(())
+    }
+
+    async fn send_candidate(&self, candidate: RTCIceCandidateInit) -> Result<(), crate::Error> {
+        let payload = serde_json::to_value(candidate).map_err(crate::Error::envelope)?;
+        let id = format!(
+            "webrtc-candidate-{}",
+            SIGNAL_IDS.fetch_add(1, Ordering::Relaxed)
+        );
+        self.send(&ControlFrame::event(id, CANDIDATE_METHOD, Some(payload)))
+            .await
+    }
+}
+
+#[async_trait]
+impl ControlChannel for Signaling {
+    async fn send(&self, data: Vec<u8>) -> Result<(), crate::Error> {
+        self.base.send(data).await
+    }
+
+    async fn recv(&self) -> Result<Received, crate::Error> {
+        self.incoming.pop().await
+    }
+}
+
+fn offer_id() -> String {
+    format!(
+        "webrtc-offer-{}",
+        SIGNAL_IDS.fetch_add(1, Ordering::Relaxed)
+    )
 }
 
 fn encode_description(sdp: &str) -> Result<serde_json::Value, crate::Error> {
@@ -114,6 +424,17 @@ fn decode_description(payload: Option<serde_json::Value>) -> Result<Description,
     Ok(description)
 }
 
+fn decode_candidate(
+    payload: Option<serde_json::Value>,
+) -> Result<RTCIceCandidateInit, crate::Error> {
+    serde_json::from_value(
+        payload.ok_or_else(|| {
+            crate::Error::Transport("WebRTC candidate payload is missing".to_owned())
+        })?,
+    )
+    .map_err(crate::Error::envelope)
+}
+
 fn decode_signal(envelope: &dyn Envelope, encoded: &[u8]) -> Result<ControlFrame, crate::Error> {
     validate_signal(encoded)?;
     envelope.decode(encoded)
@@ -143,9 +464,80 @@ fn transport_error(error: impl std::fmt::Display) -> crate::Error {
     crate::Error::Transport(error.to_string())
 }
 
+fn mutex_lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+struct ControlInbox {
+    state: Mutex<ControlState>,
+    ready: Notify,
+}
+
+struct ControlState {
+    frames: VecDeque<Received>,
+    terminal: Option<Result<(), String>>,
+}
+
+impl ControlInbox {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(ControlState {
+                frames: VecDeque::new(),
+                terminal: None,
+            }),
+            ready: Notify::new(),
+        }
+    }
+
+    fn push(&self, frame: Received) -> Result<(), crate::Error> {
+        let mut state = mutex_lock(&self.state);
+        if state.terminal.is_some() {
+            return Err(crate::Error::Closed);
+        }
+        state.frames.push_back(frame);
+        drop(state);
+        self.ready.notify_one();
+        Ok(())
+    }
+
+    async fn pop(&self) -> Result<Received, crate::Error> {
+        loop {
+            let notified = self.ready.notified();
+            {
+                let mut state = mutex_lock(&self.state);
+                if let Some(frame) = state.frames.pop_front() {
+                    return Ok(frame);
+                }
+                match &state.terminal {
+                    Some(Ok(())) => return Err(crate::Error::Closed),
+                    Some(Err(message)) => return Err(crate::Error::Transport(message.clone())),
+                    None => {}
+                }
+            }
+            notified.await;
+        }
+    }
+
+    fn close(&self, error: crate::Error) {
+        let mut state = mutex_lock(&self.state);
+        if state.terminal.is_none() {
+            state.terminal = Some(match error {
+                crate::Error::Closed => Ok(()),
+                crate::Error::Transport(message) => Err(message),
+                other => Err(other.to_string()),
+            });
+        }
+        drop(state);
+        self.ready.notify_waiters();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use webrtc::peer_connection::configuration::RTCConfiguration;
 
     #[test]
     fn signal_and_sdp_bounds_are_fail_closed() {
@@ -155,5 +547,44 @@ mod tests {
         assert!(validate_sdp(&"x".repeat(MAX_SDP_LEN + 1)).is_err());
         assert!(decode_description(Some(serde_json::json!({"sdp": ""}))).is_err());
         assert!(decode_description(Some(serde_json::json!({"other": "x"}))).is_err());
+        assert!(decode_candidate(None).is_err());
+        assert!(decode_candidate(Some(serde_json::json!({"sdpMid": "0"}))).is_err());
+        let end = decode_candidate(Some(serde_json::json!({"candidate": ""}))).unwrap();
+        assert!(end.candidate.is_empty());
+    }
+
+    #[tokio::test]
+    async fn an_unanswered_restart_times_out_and_restores_candidate_handling() {
+        let (local, _remote) =
+            crate::transport::memory::MemoryTransport::pair(crate::transport::memory::Config {
+                media: false,
+            });
+        let peer = Arc::new(
+            webrtc::api::APIBuilder::new()
+                .build()
+                .new_peer_connection(RTCConfiguration::default())
+                .await
+                .unwrap(),
+        );
+        let offer = peer.create_offer(None).await.unwrap();
+        peer.set_local_description(offer).await.unwrap();
+        let signaling = Signaling::start(
+            crate::Transport::control(local.as_ref()),
+            Arc::new(crate::envelope::v1classic::Envelope),
+            peer,
+            false,
+        );
+        assert!(matches!(
+            signaling
+                .restart_offer(std::time::Duration::from_millis(200))
+                .await,
+            Err(crate::Error::Timeout)
+        ));
+        assert!(mutex_lock(&signaling.answer).is_none());
+        signaling
+            .remote_candidate(RTCIceCandidateInit::default())
+            .await;
+        let remote = mutex_lock(&signaling.remote);
+        assert!(remote.described && remote.pending.is_empty());
     }
 }
@@ -0,0 +1,264 @@
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use webrtc::api::media_engine::MIME_TYPE_TELEPHONE_EVENT;
+use webrtc::rtp::header::{Extension, Header};
+use webrtc::rtp::packet::Packet;
+use webrtc::rtp_transceiver::rtp_codec::{RTCRtpCodecParameters, RTPCodecType};
+use webrtc::sdp::extmap::SDES_MID_URI;
+use webrtc::track::track_local::{TrackLocal, TrackLocalContext, TrackLocalWriter};
+
+use super::Codec;
+use super::dtmf::{self, EventPayload};
+
+/// End packets sent per telephone event, as RFC 4733 recommends.
+const EVENT_END_REPEATS: usize = 3;
+
+/// Local audio track that binds whichever configured codec the SDP negotiated.
+///
+/// The negotiated list follows the offer, so both peers pick the offerer's first shared codec.
+/// The track packetizes RTP itself so telephone events share the audio SSRC and sequence space.
+pub(super) struct NegotiatedTrack {
+    codecs: Vec<Codec>,
+    telephone_events: bool,
+    bound: Mutex<Option<Binding>>,
+    rtp: tokio::sync::Mutex<RtpClock>,
+}
+
+#[derive(Clone)]
+struct Binding {
+    codec: Codec,
+    payload_type: u8,
+    /// Telephone-event payload type negotiated at the audio clock rate.
+    events: Option<u8>,
+    ssrc: u32,
+    mid: Option<Extension>,
+    writer: Arc<dyn TrackLocalWriter + Send + Sync>,
+}
+
+/// Next outbound sequence number and media timestamp.
+struct RtpClock {
+    sequence: u16,
+    timestamp: u32,
+}
+
+impl NegotiatedTrack {
+    pub(super) fn new(codecs: Vec<Codec>, telephone_events: bool) -> Self {
+        // RFC 3550 asks for unpredictable initial values; clock noise is enough here.
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.subsec_nanos());
+        Self {
+            codecs,
+            telephone_events,
+            bound: Mutex::new(None),
+            rtp: tokio::sync::Mutex::new(RtpClock {
+                sequence: u16::try_from(seed & 0x7fff).unwrap_or(0),
+                timestamp: seed.rotate_left(13),
+            }),
+        }
+    }
+
+    /// Return the codec bound by the sender, once negotiation completed.
+    pub(super) fn codec(&self) -> Option<Codec> {
+        mutex_lock(&self.bound)
+            .as_ref()
+            .map(|binding| binding.codec)
+    }
+
+    /// Send one encoded audio frame lasting `duration`.
+    pub(super) async fn write_audio(
+        &self,
+        payload: Vec<u8>,
+        duration: Duration,
+    ) -> webrtc::error::Result<()> {
+        let Some(binding) = self.binding() else {
+            return Ok(());
+        };
+        let ticks = clock_ticks(duration, binding.codec.clock_rate());
+        let mut clock = self.rtp.lock().await;
+        let timestamp = clock.timestamp;
+        clock.timestamp = timestamp.wrapping_add(ticks);
+        binding
+            .write(&mut clock, binding.payload_type, false, timestamp, payload)
+            .await
+    }
+
+    /// Send one DTMF event as RFC 4733 updates every `ptime`, then its repeated end packet.
+    pub(super) async fn write_event(
+        &self,
+        code: u8,
+        duration: Duration,
+        ptime: Duration,
+    ) -> Result<(), crate::Error> {
+        let binding = self
+            .binding()
+            .ok_or_else(|| crate::Error::Transport("WebRTC audio is not negotiated".to_owned()))?;
+        let payload_type = binding.events.ok_or_else(|| {
+            crate::Error::Transport("peer did not negotiate telephone-event".to_owned())
+        })?;
+        let clock_rate = binding.codec.clock_rate();
+        let total = dtmf::duration_ticks(duration, clock_rate).ok_or_else(|| {
+            crate::Error::Configuration(format!(
+                "DTMF duration {duration:?} does not fit one telephone event"
+            ))
+        })?;
+        let step = dtmf::duration_ticks(ptime, clock_rate).unwrap_or(total);
+        // The event starts at the next audio timestamp and keeps it for every packet.
+        let timestamp = self.rtp.lock().await.timestamp;
+        let mut elapsed = 0_u16;
+        let mut marker = true;
+        let mut timer = tokio::time::interval(ptime);
+        while elapsed < total {
+            timer.tick().await;
+            elapsed = elapsed.saturating_add(step).min(total);
+            let end = elapsed == total;
+            let repeats = if end { EVENT_END_REPEATS } else { 1 };
+            for _ in 0..repeats {
+                let payload = EventPayload {
+                    code,
+                    end,
+                    volume: dtmf::VOLUME,
+                    duration: if end { total } else { elapsed },
+                };
+                let mut clock = self.rtp.lock().await;
+                binding
+                    .write(
+                        &mut clock,
+                        payload_type,
+                        std::mem::take(&mut marker),
+                        timestamp,
+                        payload.marshal().to_vec(),
+                    )
+                    .await
+                    .map_err(|error| crate::Error::Transport(error.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn binding(&self) -> Option<Binding> {
+        mutex_lock(&self.bound).clone()
+    }
+}
+
+impl Binding {
+    async fn write(
+        &self,
+        clock: &mut RtpClock,
+        payload_type: u8,
+        marker: bool,
+        timestamp: u32,
+        payload: Vec<u8>,
+    ) -> webrtc::error::Result<()> {
+        let sequence_number = clock.sequence;
+        clock.sequence = sequence_number.wrapping_add(1);
+        let mut header = Header {
+            version: 2,
+            marker,
+            payload_type,
+            sequence_number,
+            timestamp,
+            ssrc: self.ssrc,
+            ..Default::default()
+        };
+        if let Some(mid) = &self.mid {
+            header.set_extension(mid.id, mid.payload.clone())?;
+        }
+        self.writer
+            .write_rtp(&Packet {
+                header,
+                payload: payload.into(),
+            })
+            .await
+            .map(|_| ())
+    }
+}
+
+#[async_trait]
+impl TrackLocal for NegotiatedTrack {
+    async fn bind(
+        &self,
+        context: &TrackLocalContext,
+    ) -> webrtc::error::Result<RTCRtpCodecParameters> {
+        let parameters = context
+            .codec_parameters()
+            .iter()
+            .find(|parameters| {
+                Codec::from_mime(&parameters.capability.mime_type)
+                    .is_some_and(|codec| self.codecs.contains(&codec))
+            })
+            .cloned()
+            .ok_or(webrtc::Error::ErrUnsupportedCodec)?;
+        let codec = Codec::from_mime(&parameters.capability.mime_type)
+            .ok_or(webrtc::Error::ErrUnsupportedCodec)?;
+        let events = context
+            .codec_parameters()
+            .iter()
+            .find(|events| {
+                self.telephone_events
+                    && events
+                        .capability
+                        .mime_type
+                        .eq_ignore_ascii_case(MIME_TYPE_TELEPHONE_EVENT)
+                    && events.capability.clock_rate == codec.clock_rate()
+            })
+            .map(|events| events.payload_type);
+        let mid = context
+            .header_extensions()
+            .iter()
+            .find(|extension| extension.uri == SDES_MID_URI)
+            .and_then(|extension| u8::try_from(extension.id).ok())
+            .zip(context.mid())
+            .map(|(id, mid)| Extension {
+                id,
+                payload: mid.as_bytes().to_vec().into(),
+            });
+        *mutex_lock(&self.bound) = Some(Binding {
+            codec,
+            payload_type: parameters.payload_type,
+            events,
+            ssrc: context.ssrc(),
+            mid,
+            writer: context.write_stream(),
+        });
+        Ok(parameters)
+    }
+
+    async fn unbind(&self, _context: &TrackLocalContext) -> webrtc::error::Result<()> {
+        *mutex_lock(&self.bound) = None;
+        Ok(())
+    }
+
+    fn id(&self) -> &'static str {
+        "audio"
+    }
+
+    fn rid(&self) -> Option<&str> {
+        None
+    }
+
+    fn stream_id(&self) -> &'static str {
+        "rtvbp"
+    }
+
+    fn kind(&self) -> RTPCodecType {
+        RTPCodecType::Audio
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn clock_ticks(duration: Duration, clock_rate: u32) -> u32 {
+    u32::try_from(duration.as_nanos() * u128::from(clock_rate) / 1_000_000_000).unwrap_or(u32::MAX)
+}
+
+fn mutex_lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
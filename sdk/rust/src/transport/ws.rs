@@ -4,7 +4,7 @@ use std::collections::{HashMap, VecDeque};
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard, Weak};
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 
 use async_trait::async_trait;
 use futures_util::{Sink, SinkExt, Stream, StreamExt};
@@ -20,7 +20,7 @@ use tokio_tungstenite::{WebSocketStream, accept_hdr_async, connect_async};
 
 use super::{
     ControlChannel, KeepalivePolicy, MediaChannel, MediaFormat, MediaFrame, Received, Transport,
-    TransportFactory,
+    TransportFactory, TransportStats,
 };
 
 /// The deployed classic WebSocket/envelope/catalog profile.
@@ -284,6 +284,8 @@ pub struct WsTransport {
     pongs_tx: mpsc::UnboundedSender<Vec<u8>>,
     pongs_rx: tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<Vec<u8>>>>,
     ping_serial: AtomicU64,
+    keepalive_rtt: Mutex<Option<Duration>>,
+    queued_bytes: AtomicU64,
     media_claimed: AtomicBool,
 }
 
@@ -341,6 +343,8 @@ impl WsTransport {
             pongs_tx,
             pongs_rx: tokio::sync::Mutex::new(Some(pongs_rx)),
             ping_serial: AtomicU64::new(0),
+            keepalive_rtt: Mutex::new(None),
+            queued_bytes: AtomicU64::new(0),
             media_claimed: AtomicBool::new(false),
         });
         let (writer, reader) = stream.split();
@@ -387,7 +391,13 @@ impl WsTransport {
         let Some(sender) = &outgoing.sender else {
             return Err(self.closed_error());
         };
-        sender.send(command).map_err(|_| self.closed_error())
+        // Count before sending so the write pump never settles bytes that are not yet counted.
+        let length = message_bytes(&command.message);
+        self.queued_bytes.fetch_add(length, Ordering::Relaxed);
+        sender.send(command).map_err(|_| {
+            self.queued_bytes.fetch_sub(length, Ordering::Relaxed);
+            self.closed_error()
+        })
     }
 
     async fn enqueue_acknowledged(&self, message: Message) -> Result<(), crate::Error> {
@@ -667,10 +677,14 @@ async fn write_pump<S>(
     S: Sink<Message, Error = WebSocketError> + Unpin,
 {
     while let Some(command) = outgoing.recv().await {
-        let result = writer
-            .send(command.message)
-            .await
-            .map_err(|error| error.to_string());
+        let length = message_bytes(&command.message);
+        let result = match writer.send(command.message).await {
+            // The peer's Close arrived first and tungstenite already answered it, so our own
+            // Close has nothing left to do: the handshake completed in the peer's direction.
+            Err(error) if command.close && closed_by_peer(&error) => Ok(()),
+            result => result.map_err(|error| error.to_string()),
+        };
+        transport.queued_bytes.fetch_sub(length, Ordering::Relaxed);
         if let Some(written) = command.written {
             let _ = written.send(result.clone());
         }
@@ -879,6 +893,7 @@ impl Transport for WsTransport {
                 }
                 closed = self.wait_closed() => return closed,
             }
+            let sent = Instant::now();
             let matched = tokio::select! {
                 () = tokio::time::sleep(policy.timeout) => false,
                 closed = self.wait_closed() => return closed,
@@ -894,6 +909,7 @@ impl Transport for WsTransport {
             };
             if matched {
                 misses = 0;
+                *mutex_lock(&self.keepalive_rtt) = Some(sent.elapsed());
             } else {
                 misses += 1;
                 if misses >= policy.max_misses {
@@ -903,6 +919,18 @@ impl Transport for WsTransport {
             }
         }
     }
+
+    async fn stats(&self) -> TransportStats {
+        TransportStats {
+            rtt: *mutex_lock(&self.keepalive_rtt),
+            queued_bytes: Some(self.queued_bytes.load(Ordering::Relaxed)),
+            ..TransportStats::default()
+        }
+    }
+}
+
+fn message_bytes(message: &Message) -> u64 {
+    u64::try_from(message.len()).unwrap_or(u64::MAX)
 }
 
 struct Inbox<T> {
@@ -1020,6 +1048,17 @@ fn normalize_socket_error(error: WebSocketError) -> Terminal {
     }
 }
 
+fn closed_by_peer(error: &WebSocketError) -> bool {
+    matches!(
+        error,
+        WebSocketError::ConnectionClosed
+            | WebSocketError::AlreadyClosed
+            | WebSocketError::Protocol(
+                tokio_tungstenite::tungstenite::error::ProtocolError::SendAfterClosing,
+            )
+    )
+}
+
 fn normalize_error_message(message: String) -> Terminal {
     if message.contains("Connection closed") || message.contains("closed") {
         Terminal::Orderly
@@ -1,15 +1,26 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
+use rtvbp::audio::vad::{VadConfig, VadMonitor};
+use rtvbp::audio::{AudioStream, PacerConfig, Underrun};
 use rtvbp::bridge::babelforcev1::{
-    DtmfCallback, HangupCallback, TelephonyAdapter, VoiceBridge, VoiceBridgeConfig,
+    ApplicationBridge, ApplicationBridgeConfig, ApplicationEventAdapter, CallerAction,
+    DtmfCallback, EVENT_STREAM_CAPACITY, HangupCallback, HangupReason, Interruption, LatencyAction,
+    LatencyMonitor, LatencyMonitorConfig, LatencyThreshold, LocalRole, Prompt, PromptEnd,
+    PromptOutcome, PromptPlayer, PromptSource, ScriptStep, SimulatedRecording, SimulatedTelephony,
+    TelephonyAdapter, TerminationOutcome, TerminationPolicy, ToolCallOutcome, ToolCallRecord,
+    ToolRegistry, TranscriptAggregator, TranscriptEntry, VoiceBridge, VoiceBridgeConfig,
     default_media_format, new_ping_request,
 };
 use rtvbp::catalog::babelforcev1 as catalog;
 use rtvbp::envelope::v1classic;
 use rtvbp::transport::memory::{Config as MemoryConfig, MemoryTransport};
-use rtvbp::{Handler, HandlerContext, Session, SessionConfig, SessionState, Transport};
+use rtvbp::{
+    Handler, HandlerContext, InboundRequest, MediaFormat, Session, SessionConfig, SessionState,
+    Transport,
+};
 use serde_json::{Map, Value, json};
 use tokio::sync::mpsc;
 
@@ -20,6 +31,7 @@ struct FakeTelephony {
     hung_up: Mutex<bool>,
     dtmf: Mutex<Option<DtmfCallback>>,
     hangup: Mutex<Option<HangupCallback>>,
+    tool_calls: Mutex<Vec<ToolCallRecord>>,
 }
 
 impl FakeTelephony {
@@ -110,6 +122,11 @@ impl TelephonyAdapter for FakeTelephony {
         }
         Ok(())
     }
+
+    async fn tool_call(&self, record: ToolCallRecord) -> Result<(), rtvbp::Error> {
+        mutex_lock(&self.tool_calls).push(record);
+        Ok(())
+    }
 }
 
 struct Application {
@@ -201,6 +218,44 @@ impl catalog::ApplicationEventHandler for Application {
     }
 }
 
+/// Records application-emitted events in arrival order.
+struct AgentEvents(mpsc::UnboundedSender<String>);
+
+#[async_trait]
+impl ApplicationEventAdapter for AgentEvents {
+    async fn agent_tool_call(
+        &self,
+        event: catalog::AgentToolCallEvent,
+    ) -> Result<(), rtvbp::Error> {
+        self.0.send(format!("tool:{}", event.name)).unwrap();
+        Ok(())
+    }
+
+    async fn audio_speech_started(
+        &self,
+        event: catalog::AudioSpeechStartedEvent,
+    ) -> Result<(), rtvbp::Error> {
+        self.0.send(format!("speech:{}", event.origin)).unwrap();
+        Ok(())
+    }
+
+    async fn input_transcript(
+        &self,
+        event: catalog::InputTranscriptEvent,
+    ) -> Result<(), rtvbp::Error> {
+        self.0.send(format!("input:{}", event.delta)).unwrap();
+        Ok(())
+    }
+
+    async fn output_transcript_delta(
+        &self,
+        event: catalog::OutputTranscriptDeltaEvent,
+    ) -> Result<(), rtvbp::Error> {
+        self.0.send(format!("delta:{}", event.delta)).unwrap();
+        Ok(())
+    }
+}
+
 struct RunningBridge {
     application: Session,
     voice: Session,
@@ -211,6 +266,23 @@ struct RunningBridge {
     voice_task: tokio::task::JoinHandle<Result<(), rtvbp::Error>>,
 }
 
+fn voice_config() -> VoiceBridgeConfig {
+    VoiceBridgeConfig {
+        call: catalog::CallInfo {
+            id: "call-1".to_owned(),
+            session_id: "session-1".to_owned(),
+            from: "1000".to_owned(),
+            to: "1001".to_owned(),
+        },
+        application: catalog::AppInfo {
+            id: "app-1".to_owned(),
+        },
+        metadata: Some(Map::from_iter([("test".to_owned(), json!(true))])),
+        audio_formats: vec![default_media_format()],
+        termination: TerminationPolicy::default(),
+    }
+}
+
 async fn start_bridge(observe: bool) -> RunningBridge {
     let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
     let (updated_tx, mut updated_rx) = mpsc::unbounded_channel();
@@ -229,22 +301,10 @@ async fn start_bridge(observe: bool) -> RunningBridge {
     );
 
     let telephony = Arc::new(FakeTelephony::default());
-    let config = VoiceBridgeConfig {
-        call: catalog::CallInfo {
-            id: "call-1".to_owned(),
-            session_id: "session-1".to_owned(),
-            from: "1000".to_owned(),
-            to: "1001".to_owned(),
-        },
-        application: catalog::AppInfo {
-            id: "app-1".to_owned(),
-        },
-        metadata: Some(Map::from_iter([("test".to_owned(), json!(true))])),
-        audio_format: default_media_format(),
-    };
-    let bridge = VoiceBridge::new(Arc::clone(&telephony) as Arc<_>, config);
+    let bridge = VoiceBridge::new(Arc::clone(&telephony) as Arc<_>, voice_config());
     if observe {
         bridge.observe_audio(Duration::from_millis(10)).unwrap();
+        bridge.observe_transport().unwrap();
     }
     let voice = session(right, bridge.handler().unwrap());
     let application_task = tokio::spawn({
@@ -355,6 +415,9 @@ async fn generated_bridge_initializes_dispatches_callbacks_ping_and_observation(
             ApplicationEvent::Audio(event) if event.read.bytes > 0 && event.write.bytes > 0 => {
                 assert_eq!(event.read.bytes_total, 320);
                 assert_eq!(event.write.bytes_total, 320);
+                let transport = event.transport.expect("transport observation is enabled");
+                assert_eq!(transport.candidate_pair, None);
+                assert_eq!(transport.queued_bytes, None);
                 break;
             }
             ApplicationEvent::Audio(_) => {}
@@ -408,6 +471,1225 @@ async fn telephony_hangup_callback_emits_event_and_terminates() {
     running.application_task.await.unwrap().unwrap();
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn application_events_reach_the_adapter_and_caller_speech_barges_in() {
+    let running = start_bridge(false).await;
+    let (agent_tx, mut agent_rx) = mpsc::unbounded_channel();
+    running
+        .bridge
+        .set_event_adapter(Arc::new(AgentEvents(agent_tx)));
+    let mut next = async || {
+        tokio::time::timeout(Duration::from_secs(2), agent_rx.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    };
+    let events = catalog::ApplicationEvents::new(running.application.clone());
+
+    events
+        .input_transcript(catalog::InputTranscriptEvent {
+            delta: "hello".to_owned(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(next().await, "input:hello");
+    events
+        .output_transcript_delta(catalog::OutputTranscriptDeltaEvent {
+            delta: "hi".to_owned(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(next().await, "delta:hi");
+    // `output.transcript.done` falls back to the adapter's default and is ignored.
+    events
+        .output_transcript_done(catalog::OutputTranscriptDoneEvent { text: None })
+        .await
+        .unwrap();
+    events
+        .agent_tool_call(catalog::AgentToolCallEvent {
+            name: "lookup".to_owned(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(next().await, "tool:lookup");
+
+    // Queue two frames of agent audio that the telephony side has not played yet.
+    let mut tap = running.voice.audio().tap(8);
+    running.application.audio().write(&[5; 640]).await.unwrap();
+    for _ in 0..2 {
+        tokio::time::timeout(Duration::from_secs(2), tap.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    events
+        .audio_speech_started(catalog::AudioSpeechStartedEvent {
+            origin: "receiver".to_owned(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(next().await, "speech:receiver");
+    let mut played = [0_u8; 320];
+    running.voice.audio().read(&mut played).await.unwrap();
+    assert_eq!(played, [5; 320]);
+    events
+        .audio_speech_started(catalog::AudioSpeechStartedEvent {
+            origin: "sender".to_owned(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(next().await, "speech:sender");
+    assert_eq!(running.voice.audio().clear_read_buffer(), 0);
+
+    running.bridge.terminate("end_of_test").await.unwrap();
+    running.voice_task.await.unwrap().unwrap();
+    running.application_task.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn application_bridge_selects_a_codec_streams_events_and_wraps_requests() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let wideband = MediaFormat {
+        sample_rate: 16_000,
+        ..default_media_format()
+    };
+    let application_bridge = ApplicationBridge::new(ApplicationBridgeConfig {
+        audio_formats: vec![wideband, default_media_format()],
+        ..ApplicationBridgeConfig::default()
+    });
+    let mut updates = application_bridge.session_updates();
+    let mut digits = application_bridge.dtmf();
+    let mut hangups = application_bridge.hangups();
+    let application = session(left, application_bridge.handler().unwrap());
+    let telephony = Arc::new(FakeTelephony::default());
+    let voice_bridge = VoiceBridge::new(Arc::clone(&telephony) as Arc<_>, voice_config());
+    let voice = session(right, voice_bridge.handler().unwrap());
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&application).await;
+    let voice_task = tokio::spawn({
+        let voice = voice.clone();
+        async move { voice.run().await }
+    });
+
+    let update = tokio::time::timeout(Duration::from_secs(2), updates.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(update.audio_codec.unwrap().id, "L16/8000/1");
+    let initialization = application_bridge.initialization().unwrap();
+    assert_eq!(initialization.call.id, "call-1");
+    assert_eq!(application.audio().format(), Some(default_media_format()));
+
+    let now = millis(SystemTime::now());
+    telephony.emit_dtmf(catalog::DtmfEvent {
+        seq: 7,
+        pressed_at: now,
+        released_at: now + 80,
+        digit: "#".to_owned(),
+    });
+    let digit = tokio::time::timeout(Duration::from_secs(2), digits.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((digit.seq, digit.digit.as_str()), (0, "#"));
+
+    application.audio().write(&[3; 320]).await.unwrap();
+    let mut played = [0_u8; 320];
+    voice.audio().read(&mut played).await.unwrap();
+    // Unread audio at the voice side is what `audio.buffer.clear` discards, so wait until the
+    // media pump has taken it past the local buffer that the clear also empties.
+    application.audio().write(&[4; 320]).await.unwrap();
+    let cleared = tokio::time::timeout(Duration::from_secs(2), async {
+        while application.audio().playback_stats().played < 640 {
+            tokio::task::yield_now().await;
+        }
+        loop {
+            let cleared = application_bridge.clear_audio_buffer().await.unwrap();
+            if cleared > 0 {
+                return cleared;
+            }
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(cleared, 320);
+    let recording = application_bridge
+        .start_recording(Some(vec!["consent".to_owned()]))
+        .await
+        .unwrap();
+    assert_eq!(recording, "recording-1");
+    application_bridge.stop_recording(recording).await.unwrap();
+    application_bridge.hangup("agent_done").await.unwrap();
+    assert!(*mutex_lock(&telephony.hung_up));
+
+    telephony.emit_hangup(catalog::CallHangupEvent {
+        reason: Some("caller".to_owned()),
+    });
+    let hangup = tokio::time::timeout(Duration::from_secs(2), hangups.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(hangup.reason.as_deref(), Some("caller"));
+    // The voice side follows the hangup with `session.terminate`, which ends every stream.
+    let ended = tokio::time::timeout(Duration::from_secs(2), digits.recv())
+        .await
+        .unwrap();
+    assert!(ended.is_none());
+    voice_task.await.unwrap().unwrap();
+    application_task.await.unwrap().unwrap();
+    assert_eq!(application.state(), SessionState::Closed);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn application_bridge_streams_drop_events_a_slow_reader_cannot_hold() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let application_bridge = ApplicationBridge::new(ApplicationBridgeConfig::default());
+    let mut digits = application_bridge.dtmf();
+    let application = session(left, application_bridge.handler().unwrap());
+    let telephony = Arc::new(FakeTelephony::default());
+    let voice_bridge = VoiceBridge::new(Arc::clone(&telephony) as Arc<_>, voice_config());
+    let voice = session(right, voice_bridge.handler().unwrap());
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&application).await;
+    let voice_task = tokio::spawn({
+        let voice = voice.clone();
+        async move { voice.run().await }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while application_bridge.initialization().is_none() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let now = millis(SystemTime::now());
+    for _ in 0..EVENT_STREAM_CAPACITY + 2 {
+        telephony.emit_dtmf(catalog::DtmfEvent {
+            seq: 0,
+            pressed_at: now,
+            released_at: now + 80,
+            digit: "1".to_owned(),
+        });
+    }
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while digits.dropped() < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    for _ in 0..EVENT_STREAM_CAPACITY {
+        assert_eq!(digits.recv().await.unwrap().digit, "1");
+    }
+    assert_eq!(digits.dropped(), 2);
+
+    application.close().await.unwrap();
+    application_task.await.unwrap().unwrap();
+    let _ = voice_task.await.unwrap();
+    assert!(digits.recv().await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn application_bridge_streams_end_when_the_voice_transport_drops() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let application_bridge = ApplicationBridge::new(ApplicationBridgeConfig::default());
+    let mut digits = application_bridge.dtmf();
+    let mut hangups = application_bridge.hangups();
+    let mut audio_info = application_bridge.audio_info();
+    let application = session(left, application_bridge.handler().unwrap());
+    let voice_bridge = VoiceBridge::new(SimulatedTelephony::new() as Arc<_>, voice_config());
+    let voice = session(
+        Arc::clone(&right) as Arc<_>,
+        voice_bridge.handler().unwrap(),
+    );
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&application).await;
+    let voice_task = tokio::spawn({
+        let voice = voice.clone();
+        async move { voice.run().await }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while application_bridge.initialization().is_none() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    // The transport goes away under the voice session without `session.terminate`.
+    right.close().await.unwrap();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        assert!(digits.recv().await.is_none());
+        assert!(hangups.recv().await.is_none());
+        while audio_info.recv().await.is_some() {}
+    })
+    .await
+    .unwrap();
+    let _ = application_task.await.unwrap();
+    assert!(matches!(
+        application.state(),
+        SessionState::Closed | SessionState::Failed
+    ));
+    let _ = voice_task.await.unwrap();
+    // Streams subscribed after the session ended are already over.
+    assert!(application_bridge.session_updates().recv().await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn voice_bridge_offers_formats_in_order_and_binds_the_application_choice() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let wideband = MediaFormat {
+        sample_rate: 16_000,
+        ..default_media_format()
+    };
+    let pcmu = MediaFormat {
+        encoding: "PCMU".to_owned(),
+        bit_depth: 8,
+        ..default_media_format()
+    };
+    let application_bridge = ApplicationBridge::new(ApplicationBridgeConfig {
+        audio_formats: vec![pcmu.clone(), default_media_format()],
+        ..ApplicationBridgeConfig::default()
+    });
+    let mut updates = application_bridge.session_updates();
+    let application = session(left, application_bridge.handler().unwrap());
+    let mut config = voice_config();
+    config.audio_formats = vec![wideband, default_media_format(), pcmu.clone()];
+    let telephony = Arc::new(FakeTelephony::default());
+    let voice_bridge = VoiceBridge::new(Arc::clone(&telephony) as Arc<_>, config);
+    let voice = session(right, voice_bridge.handler().unwrap());
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&application).await;
+    let voice_task = tokio::spawn({
+        let voice = voice.clone();
+        async move { voice.run().await }
+    });
+
+    let update = tokio::time::timeout(Duration::from_secs(2), updates.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(update.audio_codec.unwrap().id, "PCMU/8000/1");
+    let offered: Vec<_> = application_bridge
+        .initialization()
+        .unwrap()
+        .audio_codec_offerings
+        .into_iter()
+        .map(|codec| codec.id)
+        .collect();
+    assert_eq!(offered, ["L16/16000/1", "L16/8000/1", "PCMU/8000/1"]);
+    wait_active(&voice).await;
+    assert_eq!(voice.audio().format(), Some(pcmu.clone()));
+    assert_eq!(application.audio().format(), Some(pcmu));
+
+    application.audio().write(&[0xff; 160]).await.unwrap();
+    let mut played = [0_u8; 160];
+    voice.audio().read(&mut played).await.unwrap();
+    assert_eq!(played, [0xff; 160]);
+
+    voice_bridge.terminate("end_of_test").await.unwrap();
+    voice_task.await.unwrap().unwrap();
+    application_task.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn application_bridge_rejects_offers_without_an_accepted_codec() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let application_bridge = ApplicationBridge::new(ApplicationBridgeConfig {
+        audio_formats: vec![MediaFormat {
+            encoding: "PCMU".to_owned(),
+            bit_depth: 8,
+            ..default_media_format()
+        }],
+        ..ApplicationBridgeConfig::default()
+    });
+    let application = session(left, application_bridge.handler().unwrap());
+    let telephony = Arc::new(FakeTelephony::default());
+    let voice_bridge = VoiceBridge::new(Arc::clone(&telephony) as Arc<_>, voice_config());
+    let voice = session(right, voice_bridge.handler().unwrap());
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&application).await;
+    let error = tokio::time::timeout(Duration::from_secs(2), voice.run())
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(error.to_string().contains("415"), "{error}");
+    assert!(application_bridge.initialization().is_none());
+    assert!(matches!(
+        application_bridge.hangup("unused").await,
+        Err(rtvbp::Error::SessionFailed(_))
+    ));
+    application.close().await.unwrap();
+    let _ = application_task.await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn simulated_telephony_plays_a_scripted_caller_against_an_application() {
+    let samples: Vec<u8> = (0..320_u16).flat_map(u16::to_le_bytes).collect();
+    let path = std::env::temp_dir().join(format!("rtvbp-caller-{}.wav", std::process::id()));
+    std::fs::write(&path, l16_wav(&samples)).unwrap();
+
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let application_bridge = ApplicationBridge::new(ApplicationBridgeConfig::default());
+    let mut digits = application_bridge.dtmf();
+    let mut hangups = application_bridge.hangups();
+    let application = session(left, application_bridge.handler().unwrap());
+    let telephony = SimulatedTelephony::new();
+    let voice_bridge = VoiceBridge::new(Arc::clone(&telephony) as Arc<_>, voice_config());
+    let voice = session(right, voice_bridge.handler().unwrap());
+    let script = telephony.run_script(
+        voice.audio(),
+        vec![
+            ScriptStep {
+                at: Duration::from_millis(600),
+                action: CallerAction::Hangup(Some("caller".to_owned())),
+            },
+            ScriptStep {
+                at: Duration::ZERO,
+                action: CallerAction::Audio(path.clone()),
+            },
+            ScriptStep {
+                at: Duration::from_millis(10),
+                action: CallerAction::Dtmf("1*".to_owned()),
+            },
+        ],
+    );
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&application).await;
+    let voice_task = tokio::spawn({
+        let voice = voice.clone();
+        async move { voice.run().await }
+    });
+
+    // The agent works while the caller speaks; the caller hangs up after the last digit.
+    catalog::VoicePeer::new(application.clone())
+        .session_set(catalog::SessionSetRequest {
+            data: Map::from_iter([("intent".to_owned(), json!("billing"))]),
+        })
+        .await
+        .unwrap();
+    let recording = application_bridge
+        .start_recording(Some(vec!["consent".to_owned()]))
+        .await
+        .unwrap();
+    application_bridge.stop_recording(&recording).await.unwrap();
+
+    let mut heard = vec![0_u8; samples.len()];
+    tokio::time::timeout(Duration::from_secs(2), async {
+        let mut filled = 0;
+        while filled < heard.len() {
+            filled += application
+                .audio()
+                .read(&mut heard[filled..])
+                .await
+                .unwrap();
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(heard, samples);
+    let mut pressed = String::new();
+    while pressed.len() < 2 {
+        let event = tokio::time::timeout(Duration::from_secs(2), digits.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(event.released_at - event.pressed_at >= 100, "{event:?}");
+        pressed.push_str(&event.digit);
+    }
+    assert_eq!(pressed, "1*");
+
+    let hangup = tokio::time::timeout(Duration::from_secs(2), hangups.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(hangup.reason.as_deref(), Some("caller"));
+    script.await.unwrap().unwrap();
+    voice_task.await.unwrap().unwrap();
+    application_task.await.unwrap().unwrap();
+
+    assert_eq!(telephony.variables().get("intent"), Some(&json!("billing")));
+    assert_eq!(
+        telephony.recordings(),
+        [SimulatedRecording {
+            id: recording,
+            tags: vec!["consent".to_owned()],
+            active: false,
+        }]
+    );
+    assert!(telephony.hangups().is_empty());
+    assert!(telephony.moves().is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn latency_monitors_collect_statistics_and_act_on_sustained_latency() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let application_bridge = ApplicationBridge::new(ApplicationBridgeConfig::default());
+    let application = session(left, application_bridge.handler().unwrap());
+    let telephony = SimulatedTelephony::new();
+    let voice_bridge = VoiceBridge::new(Arc::clone(&telephony) as Arc<_>, voice_config());
+    let voice = session(right, voice_bridge.handler().unwrap());
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&application).await;
+    let voice_task = tokio::spawn({
+        let voice = voice.clone();
+        async move { voice.run().await }
+    });
+
+    assert!(matches!(
+        LatencyMonitor::start(
+            voice.clone(),
+            LocalRole::Voice,
+            LatencyMonitorConfig {
+                window: 0,
+                ..LatencyMonitorConfig::default()
+            },
+        ),
+        Err(rtvbp::Error::Configuration(_))
+    ));
+
+    // Every round trip reaches a zero threshold, so each second ping raises an alarm.
+    let (alarms, mut alarm) = mpsc::unbounded_channel();
+    let voice_monitor = LatencyMonitor::start(
+        voice.clone(),
+        LocalRole::Voice,
+        LatencyMonitorConfig {
+            interval: Duration::from_millis(20),
+            threshold: Some(LatencyThreshold {
+                rtt: Duration::ZERO,
+                breaches: 2,
+                action: LatencyAction::Notify(Arc::new(move |stats| {
+                    let _ = alarms.send(stats);
+                })),
+            }),
+            ..LatencyMonitorConfig::default()
+        },
+    )
+    .unwrap();
+    let stats = tokio::time::timeout(Duration::from_secs(2), alarm.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stats.samples, 2);
+    assert_eq!((stats.timeouts, stats.errors), (0, 0));
+    let last = stats.last.unwrap();
+    assert!(last.rtt < Duration::from_secs(1), "{last:?}");
+    assert!(last.clock_offset.abs() <= 1_000, "{last:?}");
+    assert!(stats.min_rtt <= stats.avg_rtt && stats.avg_rtt <= stats.p95_rtt);
+    assert!(voice_monitor.stats().samples >= 2);
+
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while application_bridge.initialization().is_none() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let application_monitor = LatencyMonitor::start(
+        application.clone(),
+        LocalRole::Application,
+        LatencyMonitorConfig {
+            interval: Duration::from_millis(20),
+            threshold: Some(LatencyThreshold {
+                rtt: Duration::ZERO,
+                breaches: 3,
+                action: LatencyAction::Terminate("latency".to_owned()),
+            }),
+            ..LatencyMonitorConfig::default()
+        },
+    )
+    .unwrap();
+
+    voice_task.await.unwrap().unwrap();
+    application_task.await.unwrap().unwrap();
+    assert_eq!(
+        telephony.hangups(),
+        [catalog::CallHangupRequest {
+            reason: "latency".to_owned(),
+        }]
+    );
+    assert_eq!(application_monitor.stats().samples, 3);
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !(voice_monitor.is_finished() && application_monitor.is_finished()) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn transcript_aggregator_records_the_conversation_seen_by_the_voice_side() {
+    let running = start_bridge(false).await;
+    let aggregator = Arc::new(TranscriptAggregator::new());
+    running
+        .bridge
+        .set_event_adapter(Arc::clone(&aggregator) as Arc<_>);
+    let events = catalog::ApplicationEvents::new(running.application.clone());
+
+    events
+        .input_transcript(catalog::InputTranscriptEvent {
+            delta: "What is my balance?".to_owned(),
+        })
+        .await
+        .unwrap();
+    events
+        .agent_tool_call(catalog::AgentToolCallEvent {
+            name: "balance".to_owned(),
+        })
+        .await
+        .unwrap();
+    for delta in ["Your balance ", "is twelve"] {
+        events
+            .output_transcript_delta(catalog::OutputTranscriptDeltaEvent {
+                delta: delta.to_owned(),
+            })
+            .await
+            .unwrap();
+    }
+    events
+        .audio_speech_started(catalog::AudioSpeechStartedEvent {
+            origin: "sender".to_owned(),
+        })
+        .await
+        .unwrap();
+    events
+        .output_transcript_done(catalog::OutputTranscriptDoneEvent {
+            text: Some("Your balance is".to_owned()),
+        })
+        .await
+        .unwrap();
+    // `output.transcript.done` is the last event the application sent.
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !aggregator
+            .transcript()
+            .entries
+            .iter()
+            .any(|entry| matches!(entry, TranscriptEntry::Agent(turn) if turn.ended_at.is_some()))
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    // Digits reach the voice side from telephony, outside the event adapter.
+    let pressed_at = millis(SystemTime::now());
+    aggregator.on_dtmf(&catalog::DtmfEvent {
+        seq: 0,
+        pressed_at,
+        released_at: pressed_at + 100,
+        digit: "0".to_owned(),
+    });
+
+    running.bridge.terminate("end_of_test").await.unwrap();
+    running.voice_task.await.unwrap().unwrap();
+    running.application_task.await.unwrap().unwrap();
+
+    let transcript = aggregator.finish().unwrap().to_json();
+    let entries = transcript["entries"].as_array().unwrap();
+    let kinds: Vec<_> = entries.iter().map(|entry| &entry["type"]).collect();
+    assert_eq!(kinds, ["caller", "tool_call", "agent", "dtmf"]);
+    assert_eq!(entries[0]["text"], "What is my balance?");
+    assert_eq!(entries[1]["name"], "balance");
+    let agent = &entries[2];
+    assert_eq!(agent["text"], "Your balance is");
+    let started_at = agent["started_at"].as_i64().unwrap();
+    let interrupted_at = agent["interrupted_at"].as_i64().unwrap();
+    let ended_at = agent["ended_at"].as_i64().unwrap();
+    assert!(
+        started_at <= interrupted_at && interrupted_at <= ended_at,
+        "{agent}"
+    );
+    assert_eq!(entries[3]["digit"], "0");
+    assert_eq!(entries[3]["pressed_at"], pressed_at);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_registry_runs_handlers_and_reports_each_call_to_telephony() {
+    let running = start_bridge(false).await;
+    let registry = ToolRegistry::new();
+    registry
+        .register(
+            "lookup_order",
+            |context: HandlerContext, event| async move {
+                assert_eq!(event.name, "lookup_order");
+                assert!(context.audio().is_some());
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                Ok(())
+            },
+        )
+        .unwrap();
+    registry
+        .register("refund", |_, _| async {
+            Err(rtvbp::Error::Configuration("refunds are closed".to_owned()))
+        })
+        .unwrap();
+    registry
+        .register(
+            "crash",
+            |_, event: catalog::AgentToolCallEvent| async move {
+                assert_ne!(event.name, "crash", "tool handler bug");
+                Ok(())
+            },
+        )
+        .unwrap();
+    assert!(matches!(
+        registry.register("refund", |_, _| async { Ok(()) }),
+        Err(rtvbp::Error::Configuration(_))
+    ));
+    running.bridge.set_tool_registry(Arc::clone(&registry));
+    let events = catalog::ApplicationEvents::new(running.application.clone());
+
+    for name in ["lookup_order", "refund", "crash", "transfer"] {
+        events
+            .agent_tool_call(catalog::AgentToolCallEvent {
+                name: name.to_owned(),
+            })
+            .await
+            .unwrap();
+    }
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while mutex_lock(&running.telephony.tool_calls).len() < 4 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    // Records keep arrival order although the slow lookup finishes last.
+    let records = registry.records();
+    assert!(
+        matches!(&records[2].outcome, ToolCallOutcome::Failed(message) if message.contains("panicked"))
+    );
+    let outcomes: Vec<_> = records
+        .iter()
+        .map(|record| (record.name.as_str(), record.outcome.clone()))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            ("lookup_order", ToolCallOutcome::Completed),
+            (
+                "refund",
+                ToolCallOutcome::Failed("configuration: refunds are closed".to_owned())
+            ),
+            ("crash", records[2].outcome.clone()),
+            ("transfer", ToolCallOutcome::Unhandled),
+        ]
+    );
+    let lookup = records
+        .iter()
+        .find(|record| record.name == "lookup_order")
+        .unwrap();
+    assert!(lookup.duration >= Duration::from_millis(30), "{lookup:?}");
+    assert!(lookup.called_at <= millis(SystemTime::now()));
+    let mut reported = mutex_lock(&running.telephony.tool_calls).clone();
+    reported.sort_by(|left, right| left.name.cmp(&right.name));
+    let mut records = records;
+    records.sort_by(|left, right| left.name.cmp(&right.name));
+    assert_eq!(reported, records);
+
+    running.bridge.terminate("end_of_test").await.unwrap();
+    running.voice_task.await.unwrap().unwrap();
+    running.application_task.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn termination_policy_maps_hangup_reasons_and_reports_when_the_sequence_ends() {
+    // A mapped caller hangup waits for the application's acknowledgement.
+    let termination = TerminationPolicy {
+        hangup_reason: HangupReason::Map {
+            reasons: HashMap::from([("no_answer".to_owned(), "caller_left".to_owned())]),
+            fallback: "hangup".to_owned(),
+        },
+        ..TerminationPolicy::default()
+    };
+    let pair = start_simulated(termination).await;
+    let mut hangups = pair.application_bridge.hangups();
+    let script = pair.telephony.run_script(
+        pair.voice.audio(),
+        vec![ScriptStep {
+            at: Duration::ZERO,
+            action: CallerAction::Hangup(Some("no_answer".to_owned())),
+        }],
+    );
+    let outcome = tokio::time::timeout(Duration::from_secs(2), pair.voice_bridge.terminated())
+        .await
+        .unwrap();
+    assert_eq!(
+        outcome,
+        TerminationOutcome {
+            reason: "caller_left".to_owned(),
+            acknowledged: true,
+            error: None,
+            closed: true,
+        }
+    );
+    let hangup = hangups.recv().await.unwrap();
+    assert_eq!(hangup.reason.as_deref(), Some("no_answer"));
+    script.await.unwrap().unwrap();
+    pair.voice_task.await.unwrap().unwrap();
+    pair.application_task.await.unwrap().unwrap();
+
+    // Without waiting, the bridge closes once the request is written and the grace has passed.
+    let pair = start_simulated(TerminationPolicy {
+        await_acknowledgement: false,
+        ..TerminationPolicy::default()
+    })
+    .await;
+    pair.voice_bridge.terminate("done").await.unwrap();
+    let outcome = pair.voice_bridge.terminated().await;
+    assert_eq!(
+        outcome,
+        TerminationOutcome {
+            reason: "done".to_owned(),
+            acknowledged: false,
+            error: None,
+            closed: true,
+        }
+    );
+    // A second request joins the finished sequence.
+    pair.voice_bridge.terminate("again").await.unwrap();
+    pair.voice_task.await.unwrap().unwrap();
+    pair.application_task.await.unwrap().unwrap();
+    let terminates: Vec<_> = mutex_lock(&pair.application_requests)
+        .iter()
+        .filter(|request| request.method == "session.terminate")
+        .map(|request| request.payload.clone())
+        .collect();
+    assert_eq!(terminates, [Some(json!({"reason": "done"}))]);
+
+    let invalid = VoiceBridgeConfig {
+        termination: TerminationPolicy {
+            terminate_timeout: Duration::ZERO,
+            ..TerminationPolicy::default()
+        },
+        ..voice_config()
+    };
+    let (_, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let voice = session(
+        right,
+        VoiceBridge::new(SimulatedTelephony::new() as Arc<_>, invalid)
+            .handler()
+            .unwrap(),
+    );
+    assert!(voice.run().await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn terminated_resolves_when_the_session_ends_without_a_sequence_or_its_caller() {
+    // The application ends the session; the voice side never sends session.terminate.
+    let pair = start_simulated(TerminationPolicy::default()).await;
+    pair.application.close().await.unwrap();
+    let outcome = tokio::time::timeout(Duration::from_secs(2), pair.voice_bridge.terminated())
+        .await
+        .unwrap();
+    assert_eq!(outcome.reason, "");
+    assert!(!outcome.acknowledged);
+    assert!(outcome.closed);
+    let _ = pair.voice_task.await.unwrap();
+    pair.application_task.await.unwrap().unwrap();
+
+    // Dropping the terminate future does not leave the sequence without an outcome.
+    let pair = start_simulated(TerminationPolicy::default()).await;
+    let _ = tokio::time::timeout(
+        Duration::from_millis(1),
+        pair.voice_bridge.terminate("dropped"),
+    )
+    .await;
+    let outcome = tokio::time::timeout(Duration::from_secs(2), pair.voice_bridge.terminated())
+        .await
+        .unwrap();
+    assert_eq!(outcome.reason, "dropped");
+    assert!(outcome.closed);
+    pair.voice_bridge.terminate("again").await.unwrap();
+    pair.voice_task.await.unwrap().unwrap();
+    pair.application_task.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn barge_in_cuts_the_agent_transcript_at_the_playback_position() {
+    let pair = start_simulated(TerminationPolicy::default()).await;
+    let aggregator = Arc::new(TranscriptAggregator::new());
+    pair.voice_bridge
+        .set_event_adapter(Arc::clone(&aggregator) as Arc<_>);
+    let mut arrivals = pair.voice.audio().tap(64);
+
+    // Three deltas with 200 ms of audio each, all delivered to the voice side.
+    let speech = pair.application.audio();
+    for delta in ["Hello ", "there, ", "how can I help?"] {
+        pair.application_bridge
+            .output_transcript_delta(delta)
+            .await
+            .unwrap();
+        speech.write(&[7; 3_200]).await.unwrap();
+    }
+    for _ in 0..30 {
+        tokio::time::timeout(Duration::from_secs(2), arrivals.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    assert_eq!(speech.playback_position(), Some(Duration::from_millis(600)));
+
+    // Telephony plays 300 ms before the caller barges in.
+    let mut played = vec![0_u8; 4_800];
+    let mut filled = 0;
+    while filled < played.len() {
+        filled += pair
+            .voice
+            .audio()
+            .read(&mut played[filled..])
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        pair.application_bridge.clear_audio_buffer().await.unwrap(),
+        4_800
+    );
+    assert_eq!(
+        pair.application_bridge
+            .output_transcript_done()
+            .await
+            .unwrap(),
+        "Hello there, "
+    );
+
+    // Without a clear, the deltas stand.
+    pair.application_bridge
+        .output_transcript_delta("Goodbye.")
+        .await
+        .unwrap();
+    assert_eq!(
+        pair.application_bridge
+            .output_transcript_done()
+            .await
+            .unwrap(),
+        "Goodbye."
+    );
+    let stats = speech.playback_stats();
+    assert_eq!(
+        (stats.written, stats.played, stats.queued),
+        (9_600, 9_600, 0)
+    );
+
+    pair.voice_bridge.terminate("end_of_test").await.unwrap();
+    pair.voice_task.await.unwrap().unwrap();
+    pair.application_task.await.unwrap().unwrap();
+    let agent: Vec<_> = aggregator
+        .finish()
+        .unwrap()
+        .entries
+        .into_iter()
+        .filter_map(|entry| match entry {
+            TranscriptEntry::Agent(turn) => Some(turn.text),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(agent, ["Hello there, ", "Goodbye."]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn clearing_a_paced_stream_drops_local_audio_and_cuts_the_transcript() {
+    let pair = start_simulated_with(
+        TerminationPolicy::default(),
+        Some(PacerConfig {
+            underrun: Underrun::Skip,
+        }),
+    )
+    .await;
+    // 600 ms of agent audio, which the pacer holds locally and releases in real time.
+    let speech = pair.application.audio();
+    for delta in ["Hello ", "there, ", "how can I help?"] {
+        pair.application_bridge
+            .output_transcript_delta(delta)
+            .await
+            .unwrap();
+        speech.write(&[7; 3_200]).await.unwrap();
+    }
+
+    // Telephony plays 300 ms before the caller barges in.
+    let mut played = vec![0_u8; 4_800];
+    let mut filled = 0;
+    while filled < played.len() {
+        filled += pair
+            .voice
+            .audio()
+            .read(&mut played[filled..])
+            .await
+            .unwrap();
+    }
+    pair.application_bridge.clear_audio_buffer().await.unwrap();
+    let stats = speech.playback_stats();
+    assert_eq!(stats.queued, 0);
+    assert!(stats.cleared >= 3_200, "{stats:?}");
+    assert_eq!(stats.played + stats.cleared, stats.written);
+    assert_eq!(
+        pair.application_bridge
+            .output_transcript_done()
+            .await
+            .unwrap(),
+        "Hello there, "
+    );
+    // Nothing the pacer still held reaches the voice side.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(speech.playback_stats().played, stats.played);
+
+    pair.voice_bridge.terminate("end_of_test").await.unwrap();
+    pair.voice_task.await.unwrap().unwrap();
+    pair.application_task.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn prompt_player_paces_queues_and_reports_where_prompts_stopped() {
+    let pair = start_simulated(TerminationPolicy::default()).await;
+    let audio = pair.application.audio();
+    let player = PromptPlayer::start(
+        Arc::clone(&audio),
+        Some(pair.application_bridge.dtmf()),
+        Some(VadMonitor::start(&audio, VadConfig::default()).unwrap()),
+    )
+    .unwrap();
+    let (heard, listener) = count_reads(pair.voice.audio());
+    let bytes = |position: Duration| usize::try_from(position.as_millis()).unwrap() * 16;
+
+    // A WAV prompt plays to its end at real-time pace.
+    let path = std::env::temp_dir().join(format!("rtvbp-prompt-{}.wav", std::process::id()));
+    std::fs::write(&path, l16_wav(&[5; 3_200])).unwrap();
+    let started = tokio::time::Instant::now();
+    let outcome = player
+        .play(Prompt::new(PromptSource::Wav(path.clone())))
+        .await
+        .unwrap()
+        .finished()
+        .await
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(outcome.end, PromptEnd::Completed);
+    assert_eq!(outcome.position, Duration::from_millis(200));
+    assert_eq!(outcome.duration, Duration::from_millis(200));
+    assert!(started.elapsed() >= Duration::from_millis(180));
+
+    // A digit interrupts the menu and cancels the prompt queued behind it.
+    let menu = player
+        .play(Prompt::new(PromptSource::Memory(vec![6; 32_000])).interrupt_on_dtmf())
+        .await
+        .unwrap();
+    let queued = player
+        .play(Prompt::new(PromptSource::Memory(vec![6; 3_200])))
+        .await
+        .unwrap();
+    let script = pair.telephony.run_script(
+        pair.voice.audio(),
+        vec![ScriptStep {
+            at: Duration::from_millis(100),
+            action: CallerAction::Dtmf("2".to_owned()),
+        }],
+    );
+    let menu = menu.finished().await.unwrap();
+    script.await.unwrap().unwrap();
+    match &menu.end {
+        PromptEnd::Interrupted(Interruption::Dtmf(event)) => assert_eq!(event.digit, "2"),
+        other => panic!("unexpected end: {other:?}"),
+    }
+    assert!(menu.position > Duration::ZERO && menu.position < menu.duration);
+    assert_eq!(
+        queued.finished().await.unwrap(),
+        PromptOutcome {
+            end: PromptEnd::Cancelled,
+            position: Duration::ZERO,
+            duration: Duration::from_millis(200),
+        }
+    );
+
+    // Caller speech interrupts a prompt that allows it.
+    let path = std::env::temp_dir().join(format!("rtvbp-speech-{}.wav", std::process::id()));
+    let loud: Vec<u8> = std::iter::repeat_n(10_000_i16.to_le_bytes(), 1_600)
+        .flatten()
+        .collect();
+    std::fs::write(&path, l16_wav(&loud)).unwrap();
+    let greeting = player
+        .play(Prompt::new(PromptSource::Memory(vec![7; 32_000])).interrupt_on_speech())
+        .await
+        .unwrap();
+    let script = pair.telephony.run_script(
+        pair.voice.audio(),
+        vec![ScriptStep {
+            at: Duration::from_millis(100),
+            action: CallerAction::Audio(path.clone()),
+        }],
+    );
+    let greeting = greeting.finished().await.unwrap();
+    script.await.unwrap().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(greeting.end, PromptEnd::Interrupted(Interruption::Speech));
+    assert!(greeting.position < greeting.duration);
+
+    // Cancelling stops a prompt that ignores caller input.
+    let hold = player
+        .play(Prompt::new(PromptSource::Memory(vec![8; 32_000])))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    player.cancel();
+    let hold = hold.finished().await.unwrap();
+    assert_eq!(hold.end, PromptEnd::Cancelled);
+    assert!(hold.position < hold.duration);
+
+    // The voice side heard exactly the audio the outcomes report as played.
+    let taken = 3_200 + bytes(menu.position) + bytes(greeting.position) + bytes(hold.position);
+    assert_eq!(audio.playback_stats().played, u64::try_from(taken).unwrap());
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while *mutex_lock(&heard) < taken {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(*mutex_lock(&heard), taken);
+
+    player.stop();
+    pair.voice_bridge.terminate("end_of_test").await.unwrap();
+    pair.voice_task.await.unwrap().unwrap();
+    pair.application_task.await.unwrap().unwrap();
+    listener.await.unwrap();
+}
+
+struct SimulatedPair {
+    application_bridge: Arc<ApplicationBridge>,
+    voice_bridge: Arc<VoiceBridge>,
+    telephony: Arc<SimulatedTelephony>,
+    application: Session,
+    voice: Session,
+    /// Requests the application received, by method and payload.
+    application_requests: Arc<Mutex<Vec<InboundRequest>>>,
+    application_task: tokio::task::JoinHandle<Result<(), rtvbp::Error>>,
+    voice_task: tokio::task::JoinHandle<Result<(), rtvbp::Error>>,
+}
+
+async fn start_simulated(termination: TerminationPolicy) -> SimulatedPair {
+    start_simulated_with(termination, None).await
+}
+
+/// Start a simulated pair whose application session paces its outbound audio with `pacer`.
+async fn start_simulated_with(
+    termination: TerminationPolicy,
+    pacer: Option<PacerConfig>,
+) -> SimulatedPair {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let application_bridge = ApplicationBridge::new(ApplicationBridgeConfig::default());
+    let application_requests = Arc::new(Mutex::new(Vec::new()));
+    let handler = application_bridge
+        .handler()
+        .unwrap()
+        .with_request_middleware({
+            let requests = Arc::clone(&application_requests);
+            move |_, request| {
+                mutex_lock(&requests).push(request);
+                async { Ok(()) }
+            }
+        });
+    let mut config = SessionConfig::with_transport(left);
+    config.request_timeout = Duration::from_secs(2);
+    config.close_timeout = Duration::from_secs(2);
+    config.pacer = pacer;
+    let application = Session::new(Arc::new(v1classic::Envelope), handler, config);
+    let telephony = SimulatedTelephony::new();
+    let voice_bridge = VoiceBridge::new(
+        Arc::clone(&telephony) as Arc<_>,
+        VoiceBridgeConfig {
+            termination,
+            ..voice_config()
+        },
+    );
+    let voice = session(right, voice_bridge.handler().unwrap());
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&application).await;
+    let voice_task = tokio::spawn({
+        let voice = voice.clone();
+        async move { voice.run().await }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while application_bridge.initialization().is_none() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    wait_active(&voice).await;
+    SimulatedPair {
+        application_bridge,
+        voice_bridge,
+        telephony,
+        application,
+        voice,
+        application_requests,
+        application_task,
+        voice_task,
+    }
+}
+
+/// Count the bytes read from `audio` until its stream closes.
+fn count_reads(audio: Arc<AudioStream>) -> (Arc<Mutex<usize>>, tokio::task::JoinHandle<()>) {
+    let count = Arc::new(Mutex::new(0));
+    let reader = tokio::spawn({
+        let count = Arc::clone(&count);
+        async move {
+            let mut buffer = [0_u8; 320];
+            while let Ok(read) = audio.read(&mut buffer).await {
+                *mutex_lock(&count) += read;
+            }
+        }
+    });
+    (count, reader)
+}
+
+/// A mono L16/8000 WAV file holding `samples`.
+fn l16_wav(samples: &[u8]) -> Vec<u8> {
+    let data_len = u32::try_from(samples.len()).unwrap();
+    let mut wav = b"RIFF".to_vec();
+    wav.extend((36 + data_len).to_le_bytes());
+    wav.extend(b"WAVEfmt ");
+    wav.extend(16_u32.to_le_bytes());
+    wav.extend(1_u16.to_le_bytes());
+    wav.extend(1_u16.to_le_bytes());
+    wav.extend(8_000_u32.to_le_bytes());
+    wav.extend(16_000_u32.to_le_bytes());
+    wav.extend(2_u16.to_le_bytes());
+    wav.extend(16_u16.to_le_bytes());
+    wav.extend(b"data");
+    wav.extend(data_len.to_le_bytes());
+    wav.extend(samples);
+    wav
+}
+
 fn millis(time: SystemTime) -> i64 {
     i64::try_from(time.duration_since(UNIX_EPOCH).unwrap().as_millis()).unwrap()
 }
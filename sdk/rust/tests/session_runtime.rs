@@ -3,12 +3,20 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_trait::async_trait;
+use rtvbp::audio::dtmf::{self, DtmfMonitor, ToneConfig};
+use rtvbp::audio::vad::{VadConfig, VadEvent};
+use rtvbp::audio::{
+    BufferLimit, Direction, JitterBufferConfig, Overflow, PacerConfig, ResamplerConfig,
+    ResamplerQuality, Underrun,
+};
+use rtvbp::bridge::babelforcev1::{self, LocalRecordings};
+use rtvbp::catalog::babelforcev1::{AudioSpeechStartedEvent, RecordingStartRequest};
 use rtvbp::envelope::v1classic;
 use rtvbp::transport::memory::{Config as MemoryConfig, MemoryTransport};
 use rtvbp::{
     ControlChannel, EventRegistration, Handler, KeepalivePolicy, MediaChannel, MediaFormat,
-    NamedEvent, NamedRequest, Notifier, RequestRegistration, Requester, Session, SessionConfig,
-    SessionState, Transport, TransportFactory, Validate,
+    MediaFrame, NamedEvent, NamedRequest, Notifier, RequestRegistration, Requester, Session,
+    SessionConfig, SessionState, Transport, TransportFactory, Validate,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -575,3 +583,483 @@ async fn session_audio_binds_once_chunks_ptime_and_preserves_timed_inbound_frame
     first_task.await.unwrap().unwrap();
     second_task.await.unwrap().unwrap();
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn jitter_buffer_reorders_inbound_frames_and_conceals_gaps() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let handler = Handler::new([], [])
+        .unwrap()
+        .with_on_begin(|context| async move { context.accept_audio().await });
+    let mut config = SessionConfig::with_transport(right);
+    config.jitter_buffer = Some(JitterBufferConfig::default());
+    let session = Session::new(Arc::new(v1classic::Envelope), handler, config);
+    let task = tokio::spawn({
+        let session = session.clone();
+        async move { session.run().await }
+    });
+    let channel = left.open_media("audio", audio_format()).await.unwrap();
+    wait_active(&session).await;
+
+    let frame = |index: u32, sample: i16| MediaFrame {
+        data: (0..160).flat_map(|_| sample.to_le_bytes()).collect(),
+        pts: Some(Duration::from_millis(20) * index),
+    };
+    for (index, sample) in [(0, 100), (2, 400), (1, 200), (4, 800)] {
+        channel.write_frame(frame(index, sample)).await.unwrap();
+    }
+    let mut received = vec![0; 5 * 320];
+    let mut offset = 0;
+    while offset < received.len() {
+        offset += tokio::time::timeout(
+            Duration::from_secs(2),
+            session.audio().read(&mut received[offset..]),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    }
+    let samples: Vec<_> = received
+        .chunks_exact(320)
+        .map(|frame| i16::from_le_bytes([frame[0], frame[1]]))
+        .collect();
+    assert_eq!(samples, [100, 200, 400, 200, 800]);
+
+    channel.write_frame(frame(3, 600)).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while session.jitter_stats().unwrap().late == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let stats = session.jitter_stats().unwrap();
+    assert_eq!((stats.late, stats.lost), (1, 1));
+    // The burst itself reads as jitter, so the target delay has grown past the minimum.
+    assert!(stats.jitter > Duration::ZERO);
+    assert!(stats.delay > Duration::from_millis(40));
+
+    session.close().await.unwrap();
+    task.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resampler_exposes_the_application_rate_and_layout_in_both_directions() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let handler = Handler::new([], [])
+        .unwrap()
+        .with_on_begin(|context| async move { context.accept_audio().await });
+    let mut config = SessionConfig::with_transport(right);
+    config.resampler = Some(ResamplerConfig {
+        sample_rate: 16_000,
+        channels: 2,
+        quality: ResamplerQuality::Medium,
+    });
+    let session = Session::new(Arc::new(v1classic::Envelope), handler, config);
+    let task = tokio::spawn({
+        let session = session.clone();
+        async move { session.run().await }
+    });
+    let channel = left.open_media("audio", audio_format()).await.unwrap();
+    wait_active(&session).await;
+    let audio = session.audio();
+    assert_eq!(audio.format().unwrap().sample_rate, 16_000);
+    assert_eq!(audio.format().unwrap().frame_bytes().unwrap(), 1_280);
+
+    for index in 0..5 {
+        channel
+            .write_frame(MediaFrame {
+                data: (0..160).flat_map(|_| 1_000_i16.to_le_bytes()).collect(),
+                pts: Some(Duration::from_millis(20) * index),
+            })
+            .await
+            .unwrap();
+    }
+    let mut converted = Vec::new();
+    for index in 0..4 {
+        let frame = tokio::time::timeout(Duration::from_secs(2), audio.read_timed_frame())
+            .await
+            .unwrap()
+            .unwrap();
+        // Converted audio is regrouped into whole frames, each timed where its audio starts.
+        assert_eq!(frame.data.len(), 1_280);
+        let pts = frame.pts.unwrap();
+        let expected = Duration::from_millis(20) * index;
+        assert!(
+            pts.abs_diff(expected) <= Duration::from_micros(1),
+            "{pts:?}"
+        );
+        converted.extend(frame.data);
+    }
+    let stereo: Vec<_> = converted[1_280..]
+        .chunks_exact(4)
+        .map(|frame| {
+            (
+                i16::from_le_bytes([frame[0], frame[1]]),
+                i16::from_le_bytes([frame[2], frame[3]]),
+            )
+        })
+        .collect();
+    assert!(
+        stereo
+            .iter()
+            .all(|(left, right)| left == right && *left == 1_000)
+    );
+
+    // One 20 ms application frame at 16 kHz stereo leaves as one 8 kHz mono channel frame.
+    let tone: Vec<u8> = (0..640_i16).flat_map(|_| 2_000_i16.to_le_bytes()).collect();
+    for _ in 0..3 {
+        audio.write(&tone).await.unwrap();
+    }
+    let mut sent = Vec::new();
+    for _ in 0..2 {
+        let frame = tokio::time::timeout(Duration::from_secs(2), channel.read_frame())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.data.len(), 320);
+        sent.extend(frame.data);
+    }
+    assert_eq!(i16::from_le_bytes([sent[400], sent[401]]), 2_000);
+
+    session.close().await.unwrap();
+    task.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn audio_taps_copy_both_directions_with_timing() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let handler = Handler::new([], [])
+        .unwrap()
+        .with_on_begin(|context| async move { context.accept_audio().await });
+    let session = Session::new(
+        Arc::new(v1classic::Envelope),
+        handler,
+        SessionConfig::with_transport(right),
+    );
+    let task = tokio::spawn({
+        let session = session.clone();
+        async move { session.run().await }
+    });
+    let channel = left.open_media("audio", audio_format()).await.unwrap();
+    wait_active(&session).await;
+    let audio = session.audio();
+    let mut monitor = audio.tap(16);
+    let mut recorder = audio.tap(16);
+
+    let inbound = MediaFrame {
+        data: vec![7; 320],
+        pts: Some(Duration::from_millis(60)),
+    };
+    channel.write_frame(inbound.clone()).await.unwrap();
+    audio.write(&[[1; 320], [2; 320]].concat()).await.unwrap();
+    for _ in 0..2 {
+        channel.read_frame().await.unwrap();
+    }
+    let mut received = [0; 320];
+    let mut offset = 0;
+    while offset < received.len() {
+        offset += audio.read(&mut received[offset..]).await.unwrap();
+    }
+
+    for tap in [&mut monitor, &mut recorder] {
+        let mut frames = Vec::new();
+        while frames.len() < 3 {
+            let frame = tokio::time::timeout(Duration::from_secs(2), tap.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            frames.push(frame);
+        }
+        let inbound_frame = frames
+            .iter()
+            .find(|frame| frame.direction == Direction::Inbound)
+            .unwrap();
+        assert_eq!(inbound_frame.data, inbound.data);
+        assert_eq!(inbound_frame.pts, inbound.pts);
+        let outbound: Vec<_> = frames
+            .iter()
+            .filter(|frame| frame.direction == Direction::Outbound)
+            .map(|frame| (frame.data[0], frame.pts.unwrap()))
+            .collect();
+        assert_eq!(
+            outbound,
+            [(1, Duration::ZERO), (2, Duration::from_millis(20))]
+        );
+        assert_eq!(tap.dropped(), 0);
+    }
+
+    session.close().await.unwrap();
+    task.await.unwrap().unwrap();
+    assert!(monitor.recv().await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn vad_monitor_reports_speech_and_emits_speech_started_to_the_peer() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let (started_tx, mut started_rx) = mpsc::unbounded_channel();
+    let speech = EventRegistration::typed::<AudioSpeechStartedEvent, _, _>(
+        AudioSpeechStartedEvent::EVENT,
+        move |_, event| {
+            let started_tx = started_tx.clone();
+            async move {
+                started_tx.send(event.origin).unwrap();
+                Ok(())
+            }
+        },
+    );
+    let voice_handler = Handler::new([], [speech])
+        .unwrap()
+        .with_on_begin(|context| async move { context.open_audio(audio_format()).await });
+    let application_handler = Handler::new([], [])
+        .unwrap()
+        .with_on_begin(|context| async move { context.accept_audio().await });
+    let voice = session(left, voice_handler);
+    let application = session(right, application_handler);
+    let voice_task = tokio::spawn({
+        let voice = voice.clone();
+        async move { voice.run().await }
+    });
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&voice).await;
+    wait_active(&application).await;
+    let mut monitor = babelforcev1::start_vad_emitting(
+        &application.audio(),
+        VadConfig::default(),
+        application.clone(),
+    )
+    .unwrap();
+
+    let loud: Vec<u8> = (0..160_i16)
+        .flat_map(|index| (if index % 2 == 0 { 4_000_i16 } else { -4_000 }).to_le_bytes())
+        .collect();
+    let mut audio = loud.repeat(10);
+    audio.extend(vec![0; 320 * 20]);
+    voice.audio().write(&audio).await.unwrap();
+
+    let mut events = Vec::new();
+    while events.len() < 2 {
+        let event = tokio::time::timeout(Duration::from_secs(2), monitor.recv())
+            .await
+            .unwrap();
+        events.push(event.unwrap());
+    }
+    assert_eq!(
+        events,
+        [
+            VadEvent::SpeechStarted { at: Duration::ZERO },
+            VadEvent::SpeechEnded {
+                at: Duration::from_millis(200)
+            },
+        ]
+    );
+    let origin = tokio::time::timeout(Duration::from_secs(2), started_rx.recv())
+        .await
+        .unwrap();
+    assert_eq!(origin.as_deref(), Some("sender"));
+
+    finish_pair(&voice, voice_task, application_task).await;
+}
+
+#[tokio::test]
+async fn dtmf_monitor_detects_in_band_digits_written_by_the_peer() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let voice_handler = Handler::new([], [])
+        .unwrap()
+        .with_on_begin(|context| async move { context.open_audio(audio_format()).await });
+    let application_handler = Handler::new([], [])
+        .unwrap()
+        .with_on_begin(|context| async move { context.accept_audio().await });
+    let voice = session(left, voice_handler);
+    let application = session(right, application_handler);
+    let voice_task = tokio::spawn({
+        let voice = voice.clone();
+        async move { voice.run().await }
+    });
+    let application_task = tokio::spawn({
+        let application = application.clone();
+        async move { application.run().await }
+    });
+    wait_active(&voice).await;
+    wait_active(&application).await;
+    let mut monitor = DtmfMonitor::start(&application.audio()).unwrap();
+
+    let tones = dtmf::generate(&audio_format(), "42#", ToneConfig::default()).unwrap();
+    voice.audio().write(&tones).await.unwrap();
+
+    let mut events = Vec::new();
+    while events.len() < 3 {
+        let event = tokio::time::timeout(Duration::from_secs(2), monitor.recv())
+            .await
+            .unwrap();
+        events.push(event.unwrap());
+    }
+    let digits: Vec<_> = events.iter().map(|event| event.digit.as_str()).collect();
+    assert_eq!(digits, ["4", "2", "#"]);
+    assert!(
+        events
+            .iter()
+            .all(|event| event.pressed_at < event.released_at)
+    );
+    let event = babelforcev1::dtmf_event(events[2].clone(), 2).unwrap();
+    assert_eq!((event.seq, event.digit.as_str()), (2, "#"));
+    assert!(event.pressed_at < event.released_at);
+
+    finish_pair(&voice, voice_task, application_task).await;
+}
+
+#[tokio::test]
+async fn capped_inbound_buffer_keeps_the_newest_audio_and_counts_drops() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let handler = Handler::new([], [])
+        .unwrap()
+        .with_on_begin(|context| async move { context.accept_audio().await });
+    let mut config = SessionConfig::with_transport(right);
+    config.inbound_buffer = Some(BufferLimit {
+        max_delay: Duration::from_millis(40),
+        overflow: Overflow::DropOldest,
+    });
+    let session = Session::new(Arc::new(v1classic::Envelope), handler, config);
+    let task = tokio::spawn({
+        let session = session.clone();
+        async move { session.run().await }
+    });
+    let channel = left.open_media("audio", audio_format()).await.unwrap();
+    wait_active(&session).await;
+
+    for index in 1..=5 {
+        channel
+            .write_frame(MediaFrame::untimed(vec![index; 320]))
+            .await
+            .unwrap();
+    }
+    let audio = session.audio();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while audio.buffer_stats().inbound_dropped < 960 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let mut received = [0; 640];
+    let mut offset = 0;
+    while offset < received.len() {
+        offset += audio.read(&mut received[offset..]).await.unwrap();
+    }
+    assert_eq!(received[..320], [4; 320]);
+    assert_eq!(received[320..], [5; 320]);
+    let stats = audio.buffer_stats();
+    assert_eq!((stats.inbound_dropped, stats.timed_dropped), (960, 3));
+
+    session.close().await.unwrap();
+    task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn local_recordings_write_aligned_stereo_wav_files() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let handler = Handler::new([], [])
+        .unwrap()
+        .with_on_begin(|context| async move { context.accept_audio().await });
+    let session = Session::new(
+        Arc::new(v1classic::Envelope),
+        handler,
+        SessionConfig::with_transport(right),
+    );
+    let task = tokio::spawn({
+        let session = session.clone();
+        async move { session.run().await }
+    });
+    let channel = left.open_media("audio", audio_format()).await.unwrap();
+    wait_active(&session).await;
+    let directory = std::env::temp_dir().join(format!("rtvbp-recording-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let recordings = LocalRecordings::new(session.audio(), &directory);
+
+    let id = recordings
+        .start(RecordingStartRequest {
+            tags: Some(vec!["support".to_owned()]),
+        })
+        .unwrap()
+        .id;
+    assert!(id.starts_with("recording-"));
+    let other = LocalRecordings::new(session.audio(), &directory);
+    let other_id = other
+        .start(RecordingStartRequest { tags: None })
+        .unwrap()
+        .id;
+    assert_ne!(other_id, id);
+    other.stop(&other_id).await.unwrap();
+    channel
+        .write_frame(MediaFrame::untimed(vec![7; 320]))
+        .await
+        .unwrap();
+    session.audio().write(&[9; 320]).await.unwrap();
+    channel.read_frame().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    recordings.pause(&id).unwrap();
+    let summary = recordings.stop(&id).await.unwrap();
+    assert!(recordings.stop(&id).await.is_err());
+    session.close().await.unwrap();
+    task.await.unwrap().unwrap();
+
+    let wav = std::fs::read(directory.join(format!("{id}.wav"))).unwrap();
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(summary.duration, Duration::from_millis(20));
+    assert_eq!(summary.dropped, 0);
+    assert_eq!(&wav[..4], b"RIFF");
+    assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+    let data = wav.windows(4).position(|chunk| chunk == b"data").unwrap() + 8;
+    assert_eq!(wav.len() - data, 640);
+    for frame in wav[data..].chunks_exact(4) {
+        assert_eq!(frame, [7, 7, 9, 9]);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pacer_releases_a_burst_in_real_time_and_fills_underruns_with_silence() {
+    let (left, right) = MemoryTransport::pair(MemoryConfig { media: true });
+    let handler = Handler::new([], [])
+        .unwrap()
+        .with_on_begin(|context| async move { context.accept_audio().await });
+    let mut config = SessionConfig::with_transport(right);
+    config.pacer = Some(PacerConfig {
+        underrun: Underrun::Silence,
+    });
+    let session = Session::new(Arc::new(v1classic::Envelope), handler, config);
+    let task = tokio::spawn({
+        let session = session.clone();
+        async move { session.run().await }
+    });
+    let channel = left.open_media("audio", audio_format()).await.unwrap();
+    wait_active(&session).await;
+
+    let burst: Vec<u8> = (1..=10_i16)
+        .flat_map(|sample| (0..160).flat_map(move |_| sample.to_le_bytes()))
+        .collect();
+    let started = tokio::time::Instant::now();
+    session.audio().write(&burst).await.unwrap();
+    assert!(session.pacer_stats().unwrap().ahead >= Duration::from_millis(100));
+
+    let mut samples = Vec::new();
+    for _ in 0..12 {
+        let frame = tokio::time::timeout(Duration::from_secs(2), channel.read_frame())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.data.len(), 320);
+        samples.push(i16::from_le_bytes([frame.data[0], frame.data[1]]));
+    }
+    // Twelve frames at 20ms each leave no earlier than 220ms after the first.
+    assert!(started.elapsed() >= Duration::from_millis(220));
+    assert_eq!(samples, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0, 0]);
+    let stats = session.pacer_stats().unwrap();
+    assert_eq!(stats.frames, 10);
+    assert!(stats.underruns >= 2);
+    assert_eq!(stats.ahead, Duration::ZERO);
+
+    session.close().await.unwrap();
+    task.await.unwrap().unwrap();
+}
@@ -291,6 +291,11 @@ async fn sessions_exchange_typed_control_and_non_silent_audio_over_webrtc_profil
     .unwrap();
     assert_ne!(client_audio, [0; 320]);
 
+    let stats = server_session.stats().await.unwrap();
+    assert!(stats.candidate_pair.is_some(), "stats: {stats:?}");
+    assert!(stats.packets_received >= Some(1), "stats: {stats:?}");
+    assert!(stats.jitter.is_some(), "stats: {stats:?}");
+
     catalog::ApplicationPeer::new(client.clone())
         .session_terminate(catalog::SessionTerminateRequest {
             reason: "test complete".to_owned(),
@@ -310,6 +315,502 @@ async fn sessions_exchange_typed_control_and_non_silent_audio_over_webrtc_profil
     server.shutdown().await.unwrap();
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn trickled_candidates_connect_media_without_reaching_session_control() {
+    for server_trickles in [true, false] {
+        let server = ws::Server::bind(webrtcws::add_to_server(ws::ServerConfig::new(
+            "127.0.0.1:0".parse().unwrap(),
+        )))
+        .await
+        .unwrap();
+        let envelope: Arc<dyn Envelope> = Arc::new(v1classic::Envelope);
+        let server_envelope = Arc::clone(&envelope);
+        let accepted_server = Arc::clone(&server);
+        let server_task = tokio::spawn(async move {
+            let base = accepted_server.accept().await.unwrap();
+            webrtcws::accept(
+                base,
+                server_envelope,
+                webrtcws::Config {
+                    audio_format: Some(audio_format()),
+                    trickle_ice: server_trickles,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+        });
+        let client_factory = webrtcws::ClientFactory::new(
+            ws::ClientConfig::new(server.url()),
+            webrtcws::Config {
+                trickle_ice: true,
+                ..Default::default()
+            },
+        );
+        let client = client_factory.connect(Arc::clone(&envelope)).await.unwrap();
+        let server_transport = server_task.await.unwrap();
+        assert!(
+            !server_transport
+                .remote_sdp()
+                .await
+                .unwrap()
+                .contains("a=candidate:"),
+            "a trickled offer must not wait for gathered candidates"
+        );
+
+        let (client_media, server_media) = tokio::join!(
+            client.open_media("audio", audio_format()),
+            server_transport.accept_media()
+        );
+        let client_media = client_media.unwrap();
+        let server_media = server_media.unwrap();
+        client_media
+            .write_frame(MediaFrame::untimed(pcm_frame(1_000)))
+            .await
+            .unwrap();
+        let frame = tokio::time::timeout(Duration::from_secs(5), server_media.read_frame())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(i16::from_le_bytes([frame.data[0], frame.data[1]]), 988);
+
+        let encoded = envelope
+            .encode(&ControlFrame::event("after-ice", "dtmf", None))
+            .unwrap();
+        client.control().send(encoded.clone()).await.unwrap();
+        server_transport.control().send(encoded).await.unwrap();
+        for control in [client.control(), server_transport.control()] {
+            let received = tokio::time::timeout(Duration::from_secs(5), control.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(envelope.decode(&received.data).unwrap().id, "after-ice");
+        }
+
+        client.close().await.unwrap();
+        server_transport.close().await.unwrap();
+        server.shutdown().await.unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn ice_restart_keeps_the_media_channel_and_session_control() {
+    for trickle_ice in [false, true] {
+        let server = ws::Server::bind(webrtcws::add_to_server(ws::ServerConfig::new(
+            "127.0.0.1:0".parse().unwrap(),
+        )))
+        .await
+        .unwrap();
+        let envelope: Arc<dyn Envelope> = Arc::new(v1classic::Envelope);
+        let config = webrtcws::Config {
+            audio_format: Some(audio_format()),
+            trickle_ice,
+            ice_restarts: 1,
+            ..Default::default()
+        };
+        let server_envelope = Arc::clone(&envelope);
+        let server_config = config.clone();
+        let accepted_server = Arc::clone(&server);
+        let server_task = tokio::spawn(async move {
+            let base = accepted_server.accept().await.unwrap();
+            webrtcws::accept(base, server_envelope, server_config)
+                .await
+                .unwrap()
+        });
+        let client = webrtcws::connect(
+            ws::ClientConfig::new(server.url()),
+            Arc::clone(&envelope),
+            config,
+        )
+        .await
+        .unwrap();
+        let server_transport = server_task.await.unwrap();
+        let (client_media, server_media) = tokio::join!(
+            client.open_media("audio", audio_format()),
+            server_transport.accept_media()
+        );
+        let client_media = client_media.unwrap();
+        let server_media = server_media.unwrap();
+        let first_offer = server_transport.remote_sdp().await.unwrap();
+
+        assert!(matches!(
+            server_transport.restart_ice().await,
+            Err(rtvbp::Error::Configuration(_))
+        ));
+        tokio::time::timeout(Duration::from_secs(5), client.restart_ice())
+            .await
+            .unwrap()
+            .unwrap();
+        let restarted_offer = server_transport.remote_sdp().await.unwrap();
+        assert_ne!(ice_ufrag(&first_offer), ice_ufrag(&restarted_offer));
+
+        let encoded = envelope
+            .encode(&ControlFrame::event("after-restart", "dtmf", None))
+            .unwrap();
+        client.control().send(encoded).await.unwrap();
+        let received =
+            tokio::time::timeout(Duration::from_secs(5), server_transport.control().recv())
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(envelope.decode(&received.data).unwrap().id, "after-restart");
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                client_media
+                    .write_frame(MediaFrame::untimed(pcm_frame(-1_000)))
+                    .await
+                    .unwrap();
+                if let Ok(frame) =
+                    tokio::time::timeout(Duration::from_millis(100), server_media.read_frame())
+                        .await
+                {
+                    let frame = frame.unwrap();
+                    assert_eq!(i16::from_le_bytes([frame.data[0], frame.data[1]]), -988);
+                    break;
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        client.close().await.unwrap();
+        server_transport.close().await.unwrap();
+        server.shutdown().await.unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[allow(clippy::too_many_lines)]
+async fn opus_carries_wideband_audio_and_pcmu_remains_the_fallback() {
+    let wideband = MediaFormat {
+        sample_rate: 16_000,
+        ..audio_format()
+    };
+    for (server_codecs, server_format, codec) in [
+        (
+            vec![webrtcws::Codec::Opus, webrtcws::Codec::Pcmu],
+            wideband.clone(),
+            webrtcws::Codec::Opus,
+        ),
+        (
+            vec![webrtcws::Codec::Pcmu],
+            audio_format(),
+            webrtcws::Codec::Pcmu,
+        ),
+    ] {
+        let server = ws::Server::bind(webrtcws::add_to_server(ws::ServerConfig::new(
+            "127.0.0.1:0".parse().unwrap(),
+        )))
+        .await
+        .unwrap();
+        let envelope: Arc<dyn Envelope> = Arc::new(v1classic::Envelope);
+        let server_envelope = Arc::clone(&envelope);
+        let accepted_server = Arc::clone(&server);
+        let accepted_format = server_format.clone();
+        let server_task = tokio::spawn(async move {
+            let base = accepted_server.accept().await.unwrap();
+            webrtcws::accept(
+                base,
+                server_envelope,
+                webrtcws::Config {
+                    audio_format: Some(accepted_format),
+                    codecs: server_codecs,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+        });
+        let client = webrtcws::connect(
+            ws::ClientConfig::new(server.url()),
+            Arc::clone(&envelope),
+            webrtcws::Config {
+                audio_format: Some(wideband.clone()),
+                codecs: vec![webrtcws::Codec::Opus, webrtcws::Codec::Pcmu],
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let server_transport = server_task.await.unwrap();
+        assert!(
+            server_transport
+                .remote_sdp()
+                .await
+                .unwrap()
+                .contains("opus/48000/2")
+        );
+        let (client_media, server_media) = tokio::join!(
+            client.open_media("audio", wideband.clone()),
+            server_transport.accept_media()
+        );
+        let client_media = client_media.unwrap();
+        let server_media = server_media.unwrap();
+        assert_eq!(client.codec(), Some(codec));
+        assert_eq!(server_transport.codec(), Some(codec));
+        assert!(client.dtmf().is_none());
+
+        let server_bytes = server_format.frame_bytes().unwrap();
+        let mut tone = tone_frames(16_000);
+        let heard = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                client_media
+                    .write_frame(MediaFrame::untimed(tone.next().unwrap()))
+                    .await
+                    .unwrap();
+                // Resampling to or from PCMU holds back the filter's first frame.
+                let Ok(frame) =
+                    tokio::time::timeout(Duration::from_millis(100), server_media.read_frame())
+                        .await
+                else {
+                    continue;
+                };
+                let frame = frame.unwrap();
+                assert_eq!(frame.data.len(), server_bytes);
+                if frame.data.iter().any(|byte| *byte != 0) {
+                    break frame;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert!(heard.pts.unwrap() < Duration::from_secs(5));
+
+        let mut tone = tone_frames(server_format.sample_rate);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                server_media
+                    .write_frame(MediaFrame::untimed(tone.next().unwrap()))
+                    .await
+                    .unwrap();
+                let Ok(frame) =
+                    tokio::time::timeout(Duration::from_millis(100), client_media.read_frame())
+                        .await
+                else {
+                    continue;
+                };
+                let frame = frame.unwrap();
+                assert_eq!(frame.data.len(), 640);
+                if frame.data.iter().any(|byte| *byte != 0) {
+                    break;
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        client.close().await.unwrap();
+        server_transport.close().await.unwrap();
+        server.shutdown().await.unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn g711_sdk_formats_pass_through_or_transcode_over_pcmu() {
+    let mu_law = MediaFormat {
+        encoding: "PCMU".to_owned(),
+        bit_depth: 8,
+        ..audio_format()
+    };
+    let a_law = MediaFormat {
+        encoding: "PCMA".to_owned(),
+        ..mu_law.clone()
+    };
+    let server = ws::Server::bind(webrtcws::add_to_server(ws::ServerConfig::new(
+        "127.0.0.1:0".parse().unwrap(),
+    )))
+    .await
+    .unwrap();
+    let envelope: Arc<dyn Envelope> = Arc::new(v1classic::Envelope);
+    let server_envelope = Arc::clone(&envelope);
+    let accepted_server = Arc::clone(&server);
+    let accepted_format = mu_law.clone();
+    let server_task = tokio::spawn(async move {
+        let base = accepted_server.accept().await.unwrap();
+        webrtcws::accept(
+            base,
+            server_envelope,
+            webrtcws::Config {
+                audio_format: Some(accepted_format),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+    });
+    let client = webrtcws::connect(
+        ws::ClientConfig::new(server.url()),
+        Arc::clone(&envelope),
+        webrtcws::Config {
+            audio_format: Some(a_law.clone()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let server_transport = server_task.await.unwrap();
+    let (client_media, server_media) = tokio::join!(
+        client.open_media("audio", a_law),
+        server_transport.accept_media()
+    );
+    let client_media = client_media.unwrap();
+    let server_media = server_media.unwrap();
+    assert_eq!(server_media.format(), &mu_law);
+
+    // A-law 250 and μ-law 206 both decode to about +1000; the PCMU side passes its bytes through.
+    for (writer, reader, written, expected) in [
+        (&client_media, &server_media, 250, 206),
+        (&server_media, &client_media, 206, 251),
+    ] {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                writer
+                    .write_frame(MediaFrame::untimed(vec![written; 160]))
+                    .await
+                    .unwrap();
+                let frame = reader.read_frame().await.unwrap();
+                assert_eq!(frame.data.len(), 160);
+                if frame.data == [expected; 160] {
+                    break;
+                }
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    client.close().await.unwrap();
+    server_transport.close().await.unwrap();
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn telephone_events_carry_dtmf_both_ways_at_the_audio_clock_rate() {
+    let wideband = MediaFormat {
+        sample_rate: 16_000,
+        ..audio_format()
+    };
+    for (codecs, format, rtpmap) in [
+        (
+            vec![webrtcws::Codec::Pcmu],
+            audio_format(),
+            "telephone-event/8000",
+        ),
+        (
+            vec![webrtcws::Codec::Opus, webrtcws::Codec::Pcmu],
+            wideband,
+            "telephone-event/48000",
+        ),
+    ] {
+        let server = ws::Server::bind(webrtcws::add_to_server(ws::ServerConfig::new(
+            "127.0.0.1:0".parse().unwrap(),
+        )))
+        .await
+        .unwrap();
+        let envelope: Arc<dyn Envelope> = Arc::new(v1classic::Envelope);
+        let config = webrtcws::Config {
+            audio_format: Some(format.clone()),
+            codecs,
+            telephone_events: true,
+            ..Default::default()
+        };
+        let server_envelope = Arc::clone(&envelope);
+        let server_config = config.clone();
+        let accepted_server = Arc::clone(&server);
+        let server_task = tokio::spawn(async move {
+            let base = accepted_server.accept().await.unwrap();
+            webrtcws::accept(base, server_envelope, server_config)
+                .await
+                .unwrap()
+        });
+        let client = webrtcws::connect(
+            ws::ClientConfig::new(server.url()),
+            Arc::clone(&envelope),
+            config,
+        )
+        .await
+        .unwrap();
+        let server_transport = server_task.await.unwrap();
+        assert!(
+            server_transport
+                .remote_sdp()
+                .await
+                .unwrap()
+                .contains(rtpmap)
+        );
+        let (client_media, server_media) = tokio::join!(
+            client.open_media("audio", format.clone()),
+            server_transport.accept_media()
+        );
+        let client_media = client_media.unwrap();
+        let _server_media = server_media.unwrap();
+        client_media
+            .write_frame(MediaFrame::untimed(
+                tone_frames(format.sample_rate).next().unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        let client_dtmf = client.dtmf().unwrap();
+        let server_dtmf = server_transport.dtmf().unwrap();
+        assert!(matches!(
+            client_dtmf.send_dtmf("E", Duration::from_millis(100)).await,
+            Err(rtvbp::Error::Validation(_))
+        ));
+        for (sender, receiver, digit) in [
+            (&client_dtmf, &server_dtmf, "5"),
+            (&server_dtmf, &client_dtmf, "#"),
+        ] {
+            sender
+                .send_dtmf(digit, Duration::from_millis(100))
+                .await
+                .unwrap();
+            let event = tokio::time::timeout(Duration::from_secs(5), receiver.read_dtmf())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(event.digit, digit);
+            assert_eq!(
+                event.released_at.duration_since(event.pressed_at).unwrap(),
+                Duration::from_millis(100)
+            );
+        }
+
+        client.close().await.unwrap();
+        server_transport.close().await.unwrap();
+        assert!(matches!(
+            server_dtmf.read_dtmf().await,
+            Err(rtvbp::Error::Closed)
+        ));
+        server.shutdown().await.unwrap();
+    }
+}
+
+/// Endless 20 ms frames of a 400 Hz square wave at `sample_rate`.
+fn tone_frames(sample_rate: u32) -> impl Iterator<Item = Vec<u8>> {
+    let samples = sample_rate / 50;
+    let half_period = sample_rate / 800;
+    std::iter::repeat_with(move || {
+        (0..samples)
+            .flat_map(|index| {
+                let sample: i16 = if (index / half_period).is_multiple_of(2) {
+                    4_000
+                } else {
+                    -4_000
+                };
+                sample.to_le_bytes()
+            })
+            .collect()
+    })
+}
+
+fn ice_ufrag(sdp: &str) -> &str {
+    sdp.lines()
+        .find_map(|line| line.strip_prefix("a=ice-ufrag:"))
+        .unwrap()
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn negotiation_timeout_closes_the_partial_websocket() {
     let server = ws::Server::bind(webrtcws::add_to_server(ws::ServerConfig::new(
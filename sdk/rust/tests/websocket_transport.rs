@@ -265,6 +265,10 @@ async fn native_ping_pong_keepalive_survives_a_healthy_peer() {
             .await
     });
     tokio::time::sleep(Duration::from_millis(80)).await;
+    let stats = client.stats().await;
+    assert!(stats.rtt.is_some(), "stats: {stats:?}");
+    assert_eq!(stats.queued_bytes, Some(0));
+    assert_eq!(stats.candidate_pair, None);
     server.close().await.unwrap();
     let result = monitor.await.unwrap();
     assert!(result.is_ok(), "keepalive result: {result:?}");
@@ -134,6 +134,17 @@ export const BABELFORCE_V1_SCHEMAS =
       "write": {
         "$ref": "#/schemas/AudioInfoItem",
         "description": "Audio written to the telephony stream."
+      },
+      "transport": {
+        "anyOf": [
+          {
+            "$ref": "#/schemas/AudioTransportInfo"
+          },
+          {
+            "type": "null"
+          }
+        ],
+        "description": "Optional transport statistics sampled at the same time."
       }
     },
     "required": [
@@ -181,6 +192,100 @@ export const BABELFORCE_V1_SCHEMAS =
     ],
     "description": "Data emitted by `audio.speech.started` for barge-in signaling."
   },
+  "AudioTransportInfo": {
+    "type": "object",
+    "properties": {
+      "candidate_pair": {
+        "type": [
+          "string",
+          "null"
+        ],
+        "description": "Selected ICE candidate pair."
+      },
+      "rtt": {
+        "type": [
+          "integer",
+          "null"
+        ],
+        "format": "int64",
+        "description": "Latest transport round-trip time in milliseconds.",
+        "x-go-type": "*int64"
+      },
+      "jitter": {
+        "type": [
+          "integer",
+          "null"
+        ],
+        "format": "int64",
+        "description": "Interarrival jitter of received media in milliseconds.",
+        "x-go-type": "*int64"
+      },
+      "packets_received": {
+        "type": [
+          "integer",
+          "null"
+        ],
+        "format": "int64",
+        "description": "Media packets received.",
+        "x-go-type": "*int64"
+      },
+      "packets_lost": {
+        "type": [
+          "integer",
+          "null"
+        ],
+        "format": "int64",
+        "description": "Received media packets missing from the sequence.",
+        "x-go-type": "*int64"
+      },
+      "bytes_sent": {
+        "type": [
+          "integer",
+          "null"
+        ],
+        "format": "int64",
+        "description": "Media bytes sent.",
+        "x-go-type": "*int64"
+      },
+      "bytes_received": {
+        "type": [
+          "integer",
+          "null"
+        ],
+        "format": "int64",
+        "description": "Media bytes received.",
+        "x-go-type": "*int64"
+      },
+      "nacks_sent": {
+        "type": [
+          "integer",
+          "null"
+        ],
+        "format": "int64",
+        "description": "Negative acknowledgements sent for received media.",
+        "x-go-type": "*int64"
+      },
+      "nacks_received": {
+        "type": [
+          "integer",
+          "null"
+        ],
+        "format": "int64",
+        "description": "Negative acknowledgements received for sent media.",
+        "x-go-type": "*int64"
+      },
+      "queued_bytes": {
+        "type": [
+          "integer",
+          "null"
+        ],
+        "format": "int64",
+        "description": "Bytes admitted for sending but not yet written.",
+        "x-go-type": "*int64"
+      }
+    },
+    "description": "Transport health sampled with one `audio.info` report; unmeasured values are absent."
+  },
   "CallHangupEvent": {
     "type": "object",
     "properties": {
@@ -614,6 +719,8 @@ export interface AudioInfoEvent {
   readonly "read": AudioInfoItem;
   /** Audio written to the telephony stream. */
   readonly "write": AudioInfoItem;
+  /** Optional transport statistics sampled at the same time. */
+  readonly "transport"?: AudioTransportInfo;
 }
 
 /** Throughput counters for one audio direction. */
@@ -632,6 +739,30 @@ export interface AudioSpeechStartedEvent {
   readonly "origin": string;
 }
 
+/** Transport health sampled with one `audio.info` report; unmeasured values are absent. */
+export interface AudioTransportInfo {
+  /** Selected ICE candidate pair. */
+  readonly "candidate_pair"?: string;
+  /** Latest transport round-trip time in milliseconds. */
+  readonly "rtt"?: number;
+  /** Interarrival jitter of received media in milliseconds. */
+  readonly "jitter"?: number;
+  /** Media packets received. */
+  readonly "packets_received"?: number;
+  /** Received media packets missing from the sequence. */
+  readonly "packets_lost"?: number;
+  /** Media bytes sent. */
+  readonly "bytes_sent"?: number;
+  /** Media bytes received. */
+  readonly "bytes_received"?: number;
+  /** Negative acknowledgements sent for received media. */
+  readonly "nacks_sent"?: number;
+  /** Negative acknowledgements received for sent media. */
+  readonly "nacks_received"?: number;
+  /** Bytes admitted for sending but not yet written. */
+  readonly "queued_bytes"?: number;
+}
+
 /** Data emitted by `call.hangup` when the voice peer reports call termination. */
 export interface CallHangupEvent {
   /** Optional hangup reason. */
@@ -1017,6 +1148,28 @@ export function decodeAudioSpeechStartedEvent(bytes: string): AudioSpeechStarted
   return value as AudioSpeechStartedEvent;
 }
 
+export function validateAudioTransportInfo(value: unknown): readonly ValidationIssue[] {
+  return validateCatalogValue(value, "AudioTransportInfo", BABELFORCE_V1_SCHEMAS);
+}
+
+export function assertAudioTransportInfo(value: unknown): asserts value is AudioTransportInfo {
+  assertCatalogValue(value, "AudioTransportInfo", BABELFORCE_V1_SCHEMAS);
+}
+
+export function serializeAudioTransportInfo(value: AudioTransportInfo): WireEncodable {
+  return serializeCatalogValue(value, "AudioTransportInfo", BABELFORCE_V1_SCHEMAS);
+}
+
+export function encodeAudioTransportInfo(value: AudioTransportInfo): string {
+  return encodeWireJson(serializeAudioTransportInfo(value));
+}
+
+export function decodeAudioTransportInfo(bytes: string): AudioTransportInfo {
+  const value: WireJsonValue = decodeWireJson(bytes);
+  assertAudioTransportInfo(value);
+  return value as AudioTransportInfo;
+}
+
 export function validateCallHangupEvent(value: unknown): readonly ValidationIssue[] {
   return validateCatalogValue(value, "CallHangupEvent", BABELFORCE_V1_SCHEMAS);
 }
@@ -7,6 +7,7 @@ export const PROFILE_RTVBP_V1 = "rtvbp.v1" as const;
 export const PROFILE_RTVBP_DEMO_V1 = "rtvbp.demo.v1" as const;
 export const PROFILE_RTVBP_WEBRTC_V1 = "rtvbp.webrtc.v1" as const;
 export const SIGNALING_TRANSPORT_WEBRTC_OFFER = "transport.webrtc.offer" as const;
+export const SIGNALING_TRANSPORT_WEBRTC_CANDIDATE = "transport.webrtc.candidate" as const;
 export const DEFAULT_PROFILE = PROFILE_RTVBP_V1;
 export const HEADERLESS_PROFILE = PROFILE_RTVBP_V1;
 export const SERVER_PREFERENCE = [PROFILE_RTVBP_V1,PROFILE_RTVBP_DEMO_V1,PROFILE_RTVBP_WEBRTC_V1,] as const;
@@ -39,7 +40,7 @@ export const PROFILES = [
     transport: "webrtcws.v1",
     envelope: "classic.v1",
     catalog: "babelforce.v1",
-    signaling: ["transport.webrtc.offer"],
+    signaling: ["transport.webrtc.offer","transport.webrtc.candidate"],
     media: [
       { channel: "audio", carrier: "webrtc-rtp", wireFormat: "pcmu-8000-8-1-20ms", sdkFormat: "l16-8000-16-1-20ms" },
     ],
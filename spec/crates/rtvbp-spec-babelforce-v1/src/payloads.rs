@@ -164,6 +164,50 @@ pub struct AudioInfoItem {
     pub bytes_total: i64,
 }
 
+/// Transport health sampled with one `audio.info` report; unmeasured values are absent.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct AudioTransportInfo {
+    /// Selected ICE candidate pair.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_pair: Option<String>,
+    /// Latest transport round-trip time in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("x-go-type" = "*int64"))]
+    pub rtt: Option<i64>,
+    /// Interarrival jitter of received media in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("x-go-type" = "*int64"))]
+    pub jitter: Option<i64>,
+    /// Media packets received.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("x-go-type" = "*int64"))]
+    pub packets_received: Option<i64>,
+    /// Received media packets missing from the sequence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("x-go-type" = "*int64"))]
+    pub packets_lost: Option<i64>,
+    /// Media bytes sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("x-go-type" = "*int64"))]
+    pub bytes_sent: Option<i64>,
+    /// Media bytes received.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("x-go-type" = "*int64"))]
+    pub bytes_received: Option<i64>,
+    /// Negative acknowledgements sent for received media.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("x-go-type" = "*int64"))]
+    pub nacks_sent: Option<i64>,
+    /// Negative acknowledgements received for sent media.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("x-go-type" = "*int64"))]
+    pub nacks_received: Option<i64>,
+    /// Bytes admitted for sending but not yet written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("x-go-type" = "*int64"))]
+    pub queued_bytes: Option<i64>,
+}
+
 /// Data emitted by `audio.info` with read and write throughput counters.
 #[derive(Clone, Debug, Default, PartialEq, Deserialize, JsonSchema, Serialize)]
 pub struct AudioInfoEvent {
@@ -171,6 +215,9 @@ pub struct AudioInfoEvent {
     pub read: AudioInfoItem,
     /// Audio written to the telephony stream.
     pub write: AudioInfoItem,
+    /// Optional transport statistics sampled at the same time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<AudioTransportInfo>,
 }
 
 /// Data emitted by `dtmf` when the caller releases a key.
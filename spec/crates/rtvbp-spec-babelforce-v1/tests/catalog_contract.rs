@@ -4,9 +4,9 @@ use std::path::PathBuf;
 
 use rtvbp_spec_babelforce_v1::{
     AppInfo, ApplicationMoveRequest, AudioBufferClearRequest, AudioCodec, AudioInfoEvent,
-    AudioSpeechStartedEvent, CallHangupEvent, CallInfo, DtmfEvent, OutputTranscriptDoneEvent,
-    RecordingStartRequest, SessionInitializeRequest, SessionInitializeResponse,
-    SessionUpdatedEvent, catalog,
+    AudioSpeechStartedEvent, AudioTransportInfo, CallHangupEvent, CallInfo, DtmfEvent,
+    OutputTranscriptDoneEvent, RecordingStartRequest, SessionInitializeRequest,
+    SessionInitializeResponse, SessionUpdatedEvent, catalog,
 };
 use rtvbp_spec_model::{Nullable, Role, ScenarioStep};
 use schemars::schema_for;
@@ -382,6 +382,15 @@ fn presence_and_go_type_hints_match_the_legacy_contract() {
         audio_info["properties"]["read"]["$ref"],
         "#/$defs/AudioInfoItem"
     );
+    let transport: Value = schema_for!(AudioTransportInfo).to_value();
+    for (field, schema) in transport["properties"].as_object().unwrap() {
+        let expected = if field == "candidate_pair" {
+            Value::Null
+        } else {
+            json!("*int64")
+        };
+        assert_eq!(schema["x-go-type"], expected, "{field}");
+    }
     let speech: Value = schema_for!(AudioSpeechStartedEvent).to_value();
     assert!(
         speech["required"]
@@ -194,11 +194,15 @@ fn resolve_field(
         ));
     }
     let optional = !required;
-    let optional_pointer = object.get("x-go-type").and_then(Value::as_str) == Some("*string");
-    if optional_pointer && !optional {
+    let pointer_hint = object
+        .get("x-go-type")
+        .and_then(Value::as_str)
+        .filter(|hint| matches!(*hint, "*string" | "*int64"));
+    let optional_pointer = pointer_hint.is_some();
+    if let Some(hint) = pointer_hint.filter(|_| !optional) {
         return Err(schema_error(
             owner,
-            format!("field {wire_name:?} uses *string but is not optional"),
+            format!("field {wire_name:?} uses {hint} but is not optional"),
         ));
     }
     let normalized = if optional || nullable {
@@ -213,7 +217,9 @@ fn resolve_field(
         schema.clone()
     };
     let mut field_type = go_type(owner, &normalized, schemas)?;
-    if nullable {
+    // encoding/json never omits an empty struct value, so optional structs are pointers.
+    let optional_struct = optional && references_struct(&normalized, schemas);
+    if nullable || optional_struct {
         field_type.insert(0, '*');
     }
     Ok(GoField {
@@ -228,10 +234,19 @@ fn resolve_field(
         go_type: field_type,
         schema: normalized,
         optional,
-        pointer: nullable || optional_pointer,
+        pointer: nullable || optional_pointer || optional_struct,
     })
 }
 
+fn references_struct(schema: &Value, schemas: &std::collections::BTreeMap<String, Value>) -> bool {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(schema_ref_name)
+        .and_then(|name| schemas.get(&name))
+        .is_some_and(|target| target.get("properties").is_some())
+}
+
 fn go_type(
     owner: &str,
     schema: &Value,
@@ -277,7 +292,7 @@ fn go_type(
     if hint.is_some()
         && !matches!(
             (type_name, hint),
-            (Some("integer"), Some("int")) | (Some("string"), Some("*string"))
+            (Some("integer"), Some("int" | "*int64")) | (Some("string"), Some("*string"))
         )
     {
         return Err(schema_error(
@@ -290,6 +305,7 @@ fn go_type(
         Some("string") => Ok("string".to_owned()),
         Some("boolean") => Ok("bool".to_owned()),
         Some("integer") if hint == Some("int") => Ok("int".to_owned()),
+        Some("integer") if hint == Some("*int64") => Ok("*int64".to_owned()),
         Some("integer") => Ok("int64".to_owned()),
         Some("number") => Ok("float64".to_owned()),
         Some("array") => {
@@ -636,7 +652,12 @@ fn render_value(
                 )?;
                 let mut rendered = render_value(owner, &field.schema, field_value, schemas, None)?;
                 if field.pointer && !field_value.is_null() {
-                    rendered = format!("ptr({rendered})");
+                    // An untyped integer constant would infer `*int`.
+                    rendered = if field.go_type == "*int64" {
+                        format!("ptr[int64]({rendered})")
+                    } else {
+                        format!("ptr({rendered})")
+                    };
                 }
                 fields.push(format!("{}: {}", field.name, rendered));
             }
@@ -913,6 +934,44 @@ mod tests {
         assert!(error.to_string().contains("invalid x-go-type extension"));
     }
 
+    #[test]
+    fn pointer_int64_hint_keeps_optional_integers_distinct_from_zero() {
+        let schemas = BTreeMap::new();
+        let optional =
+            json!({"type": ["integer", "null"], "format": "int64", "x-go-type": "*int64"});
+        let field = resolve_field("Payload", "rtt", &optional, false, &schemas).unwrap();
+        assert_eq!(field.go_type, "*int64");
+        assert!(field.pointer);
+
+        let required = json!({"type": "integer", "x-go-type": "*int64"});
+        let error = resolve_field("Payload", "rtt", &required, true, &schemas).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("uses *int64 but is not optional")
+        );
+
+        let wrong_type = json!({"type": ["string", "null"], "x-go-type": "*int64"});
+        let error = resolve_field("Payload", "rtt", &wrong_type, false, &schemas).unwrap_err();
+        assert!(error.to_string().contains("invalid x-go-type extension"));
+    }
+
+    #[test]
+    fn optional_struct_references_are_pointers_so_omitempty_omits_them() {
+        let mut schemas = BTreeMap::new();
+        schemas.insert(
+            "Named".to_owned(),
+            json!({"type": "object", "properties": {"id": {"type": "string"}}}),
+        );
+        let reference = json!({"$ref": "#/schemas/Named"});
+        let optional = resolve_field("Payload", "named", &reference, false, &schemas).unwrap();
+        assert_eq!(optional.go_type, "*Named");
+        assert!(optional.pointer);
+        let required = resolve_field("Payload", "named", &reference, true, &schemas).unwrap();
+        assert_eq!(required.go_type, "Named");
+        assert!(!required.pointer);
+    }
+
     #[test]
     fn go_type_rejects_malformed_hints_and_hints_on_references() {
         let mut schemas = BTreeMap::new();
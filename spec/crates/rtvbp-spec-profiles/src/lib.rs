@@ -16,7 +16,11 @@ pub const CLASSIC_ENVELOPE: &str = "classic.v1";
 
 pub const L16_SDK_FORMAT: &str = "l16-8000-16-1-20ms";
 pub const PCMU_WIRE_FORMAT: &str = "pcmu-8000-8-1-20ms";
+pub const L16_WIDEBAND_SDK_FORMAT: &str = "l16-16000-16-1-20ms";
+pub const L16_FULLBAND_SDK_FORMAT: &str = "l16-48000-16-1-20ms";
+pub const OPUS_WIRE_FORMAT: &str = "opus-48000-16-1-20ms";
 pub const WEBRTC_OFFER_METHOD: &str = "transport.webrtc.offer";
+pub const WEBRTC_CANDIDATE_METHOD: &str = "transport.webrtc.candidate";
 
 /// The current public transport/profile declaration consumed by every generator target.
 #[must_use]
@@ -55,13 +59,47 @@ pub fn registry() -> ProfileRegistry {
                 channels: 1,
                 packet_time_ms: 20,
             },
+            MediaFormatSpec {
+                id: L16_WIDEBAND_SDK_FORMAT.to_owned(),
+                encoding: "L16".to_owned(),
+                sample_rate: 16_000,
+                bit_depth: 16,
+                channels: 1,
+                packet_time_ms: 20,
+            },
+            MediaFormatSpec {
+                id: L16_FULLBAND_SDK_FORMAT.to_owned(),
+                encoding: "L16".to_owned(),
+                sample_rate: 48_000,
+                bit_depth: 16,
+                channels: 1,
+                packet_time_ms: 20,
+            },
+            MediaFormatSpec {
+                id: OPUS_WIRE_FORMAT.to_owned(),
+                encoding: "OPUS".to_owned(),
+                sample_rate: 48_000,
+                bit_depth: 16,
+                channels: 1,
+                packet_time_ms: 20,
+            },
+        ],
+        signaling: vec![
+            SignalingSpec {
+                method: WEBRTC_OFFER_METHOD.to_owned(),
+                transport: WEBRTC_WEBSOCKET_TRANSPORT.to_owned(),
+                description:
+                    "Exchange an SDP offer and correlated answer, complete unless trickling."
+                        .to_owned(),
+            },
+            SignalingSpec {
+                method: WEBRTC_CANDIDATE_METHOD.to_owned(),
+                transport: WEBRTC_WEBSOCKET_TRANSPORT.to_owned(),
+                description:
+                    "Stream one trickled ICE candidate, or end-of-candidates, as an event."
+                        .to_owned(),
+            },
         ],
-        signaling: vec![SignalingSpec {
-            method: WEBRTC_OFFER_METHOD.to_owned(),
-            transport: WEBRTC_WEBSOCKET_TRANSPORT.to_owned(),
-            description: "Exchange a complete non-trickle SDP offer and correlated answer."
-                .to_owned(),
-        }],
         profiles: vec![
             ProfileSpec {
                 id: CLASSIC_PROFILE.to_owned(),
@@ -92,7 +130,10 @@ pub fn registry() -> ProfileRegistry {
                 transport: WEBRTC_WEBSOCKET_TRANSPORT.to_owned(),
                 envelope: CLASSIC_ENVELOPE.to_owned(),
                 catalog: CatalogId::new("babelforce", 1),
-                signaling: vec![WEBRTC_OFFER_METHOD.to_owned()],
+                signaling: vec![
+                    WEBRTC_OFFER_METHOD.to_owned(),
+                    WEBRTC_CANDIDATE_METHOD.to_owned(),
+                ],
                 media: vec![ProfileMediaSpec {
                     channel: "audio".to_owned(),
                     carrier: MediaCarrier::WebRtcRtp,
@@ -141,7 +182,10 @@ mod tests {
 
         let webrtc = &registry.profiles[2];
         assert_eq!(webrtc.transport, WEBRTC_WEBSOCKET_TRANSPORT);
-        assert_eq!(webrtc.signaling, [WEBRTC_OFFER_METHOD]);
+        assert_eq!(
+            webrtc.signaling,
+            [WEBRTC_OFFER_METHOD, WEBRTC_CANDIDATE_METHOD]
+        );
         assert_eq!(webrtc.media[0].wire_format, PCMU_WIRE_FORMAT);
         assert_eq!(webrtc.media[0].sdk_format, L16_SDK_FORMAT);
     }